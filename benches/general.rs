@@ -0,0 +1,31 @@
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion};
+
+use skribi_language_source::parse::parse;
+use skribi_language_source::tokens::tokenize;
+
+/// 100 classes and 1000 global variables typed with them, so every declaration looks up a class
+fn type_references() -> String {
+    let mut code = String::new();
+    for i in 0..100 {
+        code.push_str(&format!("kat C{}\n", i));
+    }
+    for i in 0..1000 {
+        code.push_str(&format!("fu C{} v{} 0\n", i % 100, i));
+    }
+    code
+}
+
+fn parse_type_references(c: &mut Criterion) {
+    let code = type_references();
+    c.bench_function("parse 1000 type references", |b| {
+        // only the parsing is measured, not the tokenizer
+        b.iter_batched(
+            || tokenize(code.clone()).unwrap(),
+            |tokens| black_box(parse(tokens).unwrap()),
+            BatchSize::SmallInput,
+        )
+    });
+}
+
+criterion_group!(benches, parse_type_references);
+criterion_main!(benches);
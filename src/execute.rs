@@ -1,17 +1,79 @@
-pub type IntType = u32;
-pub type OperationIO = u32;
-pub type OperationContext = ();
+use std::cell::RefCell;
+use std::io::BufRead;
+
+use crate::execute::natives::{read_line, NativeFunction, NativeRegistry};
+use crate::execute::variables::{VariableStack, VariableType};
+use crate::skr_errors::ShortResult;
+
+pub mod check;
+pub mod natives;
+pub mod variables;
+
+pub type IntType = i64;
+pub type FloatType = f64;
+pub type OperationIO = VariableType;
+/// Settings and state shared by every node during the evaluation.
+#[derive(Debug)]
+pub struct OperationContext {
+    /// Maximum number of iterations of a single loop, to stop the programs that never end
+    pub max_loop_iterations: usize,
+    /// Maximum number of nested function calls, to stop the recursions that never end before they
+    /// overflow the stack of the interpreter. The default of 10 000 calls needs a stack of about
+    /// 128 MiB in a debug build, bigger than the stack of the main thread.
+    pub max_call_depth: usize,
+    /// The nodes are evaluated with a shared reference, the variables are changed through a
+    /// [RefCell]. A borrow must never be kept while evaluating another node.
+    pub variables: RefCell<VariableStack>,
+    /// Value of the `ei` being executed. While it is set, the statements are skipped until the end
+    /// of the function.
+    pub return_value: RefCell<Option<OperationIO>>,
+    /// The functions that can be called with `skr_app`
+    pub natives: NativeRegistry,
+    /// Reject the scopes whose value is lost : a `biuli` scope whose value is never used and a
+    /// `kodi` scope producing a value
+    pub strict: bool,
+}
+
+impl Default for OperationContext {
+    fn default() -> Self {
+        Self {
+            max_loop_iterations: 1_000_000,
+            max_call_depth: 10_000,
+            variables: RefCell::new(VariableStack::new()),
+            return_value: RefCell::new(None),
+            natives: NativeRegistry::default(),
+            strict: false,
+        }
+    }
+}
+
+impl OperationContext {
+    /// Make a native function callable with `skr_app`.
+    pub fn register_native(&mut self, name: &str, native: NativeFunction) {
+        self.natives.register(name, native);
+    }
+
+    /// Read the lines given to `skr_app read_line` from `input` instead of the standard input.
+    pub fn set_input(&mut self, input: Box<dyn BufRead>) {
+        self.register_native("read_line", read_line(input));
+    }
+
+    /// Whether an `ei` has been executed and the statements of the function must be skipped.
+    pub fn is_returning(&self) -> bool {
+        self.return_value.borrow().is_some()
+    }
+}
 
 pub trait EvaluateFromInput {
     fn evaluate_from_input(
         &self,
         operation_context: &OperationContext,
         input: OperationIO,
-    ) -> OperationIO;
+    ) -> ShortResult<OperationIO>;
 }
 
 pub trait Evaluate {
-    fn evaluate(&self, operation_context: &OperationContext) -> OperationIO;
+    fn evaluate(&self, operation_context: &OperationContext) -> ShortResult<OperationIO>;
 }
 
 pub trait Execute {
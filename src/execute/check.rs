@@ -0,0 +1,156 @@
+//! Check the variables of a program before running it, without evaluating anything.
+//!
+//! The checker walks the AST with a [Visitor] and follows the scopes of the evaluation in its own
+//! [VariableStack] : a block pushes a scope, a function or a class hides the scopes of the code
+//! around it. The declared variables hold the default value of their type, so only what is known
+//! before the evaluation is checked :
+//!
+//! - a variable is not declared twice in the same scope
+//! - a modification changes a variable that exists and is not a constant
+//! - a literal given to a variable, when it is declared or changed, matches its declared type
+//!
+//! A function can change a global variable declared after the function and before the call, so a
+//! variable unknown in a function body is not reported.
+
+use crate::execute::variables::{Scope, VariableStack, VariableType};
+use crate::execute::OperationContext;
+use crate::parse::nodes::classes::ClassDec;
+use crate::parse::nodes::expressions::{Exp, IdUse, IdUseV, StaL};
+use crate::parse::nodes::functions::{FctDec, Param};
+use crate::parse::nodes::vars::{Change, VarDec, VarMod};
+use crate::parse::nodes::visitor::{Visitable, Visitor};
+use crate::parse::Program;
+use crate::skr_errors::{CustomError, ShortResult};
+
+/// Check `program` against the variables already declared in `operation_context`, which is not
+/// changed. Every error found is returned, in the order of the code.
+pub fn check(
+    program: &Program,
+    operation_context: &OperationContext,
+) -> Result<(), Vec<CustomError>> {
+    let mut checker = Checker {
+        variables: operation_context.variables.borrow().clone(),
+        frames: Vec::new(),
+        errors: Vec::new(),
+    };
+    program.accept(&mut checker);
+    if checker.errors.is_empty() {
+        Ok(())
+    } else {
+        Err(checker.errors)
+    }
+}
+
+struct Checker {
+    variables: VariableStack,
+    /// The scopes hidden by each function or class being checked
+    frames: Vec<Vec<Scope>>,
+    errors: Vec<CustomError>,
+}
+
+impl Checker {
+    fn report(&mut self, result: ShortResult<()>) {
+        if let Err(err) = result {
+            self.errors.push(err);
+        }
+    }
+
+    /// Declare an untyped value, like a function or a class, in the innermost scope.
+    fn declare(&mut self, name: &str) {
+        let result = self.variables.declare(name, VariableType::Null);
+        self.report(result);
+    }
+
+    fn push_frame(&mut self) {
+        // the nesting of the code is not a recursion, the number of calls is not limited here
+        match self.variables.push_frame(usize::MAX) {
+            Ok(caller_scopes) => self.frames.push(caller_scopes),
+            Err(err) => self.errors.push(err),
+        }
+    }
+
+    fn pop_frame(&mut self) {
+        if let Some(caller_scopes) = self.frames.pop() {
+            self.variables.pop_frame(caller_scopes);
+        }
+    }
+
+    fn check_modification(&mut self, identifier: &str, var_mod: &VarMod) {
+        let value = match var_mod.change() {
+            Change::Value(exp) => exp.literal(),
+            Change::Increment | Change::Decrement => None,
+        };
+        match self.variables.check_set(identifier, value, var_mod.line()) {
+            Err(CustomError::UnknownVariable(_)) if !self.frames.is_empty() => {}
+            result => self.report(result),
+        }
+    }
+}
+
+impl Visitor for Checker {
+    fn visit_id_use(&mut self, node: &IdUse) {
+        if let Some((identifier, var_mod)) = node.modification() {
+            self.check_modification(identifier, var_mod);
+        }
+    }
+
+    fn visit_id_use_v(&mut self, node: &IdUseV) {
+        if let Some((identifier, var_mod)) = node.modification() {
+            self.check_modification(identifier, var_mod);
+        }
+    }
+
+    fn visit_sta_l(&mut self, _node: &StaL) {
+        let result = self.variables.push_scope();
+        self.report(result);
+    }
+
+    fn leave_sta_l(&mut self, _node: &StaL) {
+        self.variables.pop_scope();
+    }
+
+    /// The value is evaluated before the variable is declared, so the declaration is made after
+    /// the children.
+    fn leave_var_dec(&mut self, node: &VarDec) {
+        let type_ = node.type_().clone();
+        if let Some(value) = node.exp().and_then(Exp::literal) {
+            let result = type_.assign(value).map(|_| ());
+            self.report(result);
+        }
+        let result = self.variables.declare_typed(
+            node.identifier(),
+            type_.default_value(),
+            type_,
+            node.is_constant(),
+        );
+        self.report(result);
+    }
+
+    fn visit_fct_dec(&mut self, node: &FctDec) {
+        self.declare(node.identifier());
+        self.push_frame();
+        // the function is visible inside itself for the recursion
+        self.declare(node.identifier());
+    }
+
+    fn visit_param(&mut self, node: &Param) {
+        let type_ = node.type_().clone();
+        let result =
+            self.variables
+                .declare_typed(node.identifier(), type_.default_value(), type_, false);
+        self.report(result);
+    }
+
+    fn leave_fct_dec(&mut self, _node: &FctDec) {
+        self.pop_frame();
+    }
+
+    fn visit_class_dec(&mut self, node: &ClassDec) {
+        self.declare(node.identifier());
+        self.push_frame();
+    }
+
+    fn leave_class_dec(&mut self, _node: &ClassDec) {
+        self.pop_frame();
+    }
+}
@@ -0,0 +1,189 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt::{Debug, Formatter};
+use std::io::{stdin, BufRead, BufReader};
+
+use crate::execute::variables::VariableType;
+use crate::execute::{FloatType, IntType};
+use crate::skr_errors::{CustomError, ShortResult};
+
+/// A native function receives the evaluated arguments of the `skr_app` call.
+pub type NativeFunction = Box<dyn Fn(&[VariableType]) -> ShortResult<VariableType>>;
+
+/// `NativeRegistry` stores the native functions that can be called with `skr_app`, by name.
+pub struct NativeRegistry {
+    natives: HashMap<String, NativeFunction>,
+}
+
+impl Default for NativeRegistry {
+    /// A registry with the built-in native functions.
+    fn default() -> Self {
+        let mut registry = Self::new();
+        registry.register("print", Box::new(|args| print(args, "")));
+        registry.register("println", Box::new(|args| print(args, "\n")));
+        registry.register("typeof", Box::new(type_of));
+        registry.register("len", Box::new(len));
+        registry.register("str", Box::new(to_string));
+        registry.register("read_line", read_line(Box::new(BufReader::new(stdin()))));
+        registry.register("abs", Box::new(abs));
+        registry.register(
+            "min",
+            Box::new(|args| min_max("min", args, IntType::min, FloatType::min)),
+        );
+        registry.register(
+            "max",
+            Box::new(|args| min_max("max", args, IntType::max, FloatType::max)),
+        );
+        registry
+    }
+}
+
+impl Debug for NativeRegistry {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let mut names: Vec<_> = self.natives.keys().collect();
+        names.sort();
+        f.debug_struct("NativeRegistry")
+            .field("natives", &names)
+            .finish()
+    }
+}
+
+impl NativeRegistry {
+    /// Create a registry without any native function.
+    pub fn new() -> Self {
+        Self {
+            natives: HashMap::new(),
+        }
+    }
+
+    /// Register a native function, replacing the one with the same name if there is one.
+    pub fn register(&mut self, name: &str, native: NativeFunction) {
+        self.natives.insert(name.to_string(), native);
+    }
+
+    pub fn get(&self, name: &str) -> Option<&NativeFunction> {
+        self.natives.get(name)
+    }
+}
+
+/// Print the values separated by spaces.
+fn print(args: &[VariableType], end: &str) -> ShortResult<VariableType> {
+    let values: Vec<_> = args.iter().map(|arg| arg.to_string()).collect();
+    print!("{}{}", values.join(" "), end);
+    Ok(VariableType::Null)
+}
+
+/// Check the number of arguments given to the native function `name`.
+fn expect_args<'a, const N: usize>(
+    name: &str,
+    args: &'a [VariableType],
+) -> ShortResult<&'a [VariableType; N]> {
+    args.try_into().map_err(|_| {
+        CustomError::InvalidOperation(format!(
+            "The native function {} expects {} arguments, got {}",
+            name,
+            N,
+            args.len()
+        ))
+    })
+}
+
+/// The name of the type of the value, like `int` or `null`.
+fn type_of(args: &[VariableType]) -> ShortResult<VariableType> {
+    let [value] = expect_args("typeof", args)?;
+    Ok(VariableType::String(value.type_name().to_string()))
+}
+
+/// A `read_line` native that reads the next line of `input`, without its new line. At the end of
+/// the input, the value is null.
+pub fn read_line(input: Box<dyn BufRead>) -> NativeFunction {
+    let input = RefCell::new(input);
+    Box::new(move |args| {
+        expect_args::<0>("read_line", args)?;
+        let mut line = String::new();
+        match input.borrow_mut().read_line(&mut line) {
+            Ok(0) => Ok(VariableType::Null),
+            Ok(_) => {
+                if line.ends_with('\n') {
+                    line.pop();
+                    if line.ends_with('\r') {
+                        line.pop();
+                    }
+                }
+                Ok(VariableType::String(line))
+            }
+            Err(err) => Err(CustomError::InvalidOperation(format!(
+                "Cannot read a line: {}",
+                err
+            ))),
+        }
+    })
+}
+
+/// The value written as a string, like [print] writes it : the bools are `io` and `no`, and a
+/// float always has a decimal part (`2.0`).
+fn to_string(args: &[VariableType]) -> ShortResult<VariableType> {
+    let [value] = expect_args("str", args)?;
+    Ok(VariableType::String(value.to_string()))
+}
+
+/// The number of chars of a string, or the number of elements of an array.
+fn len(args: &[VariableType]) -> ShortResult<VariableType> {
+    let length = match expect_args("len", args)? {
+        [VariableType::String(s)] => s.chars().count(),
+        [VariableType::Array(elements)] => elements.len(),
+        [value] => {
+            return Err(CustomError::TypeMismatch(format!(
+                "Cannot get the length of a {}",
+                value.type_name()
+            )))
+        }
+    };
+    Ok(VariableType::Integer(length as IntType))
+}
+
+/// The absolute value of an int or a float. The absolute value of the minimum int does not fit in
+/// an int, this is an overflow error.
+fn abs(args: &[VariableType]) -> ShortResult<VariableType> {
+    match expect_args("abs", args)? {
+        [VariableType::Integer(value)] => value
+            .checked_abs()
+            .map(VariableType::Integer)
+            .ok_or_else(|| CustomError::IntegerOverflow(format!("abs({})", value))),
+        [VariableType::Float(value)] => Ok(VariableType::Float(value.abs())),
+        [value] => Err(CustomError::TypeMismatch(format!(
+            "Cannot get the absolute value of a {}",
+            value.type_name()
+        ))),
+    }
+}
+
+/// The smallest or the biggest of two numbers. Two ints give an int, an int with a float is
+/// converted to a float.
+fn min_max(
+    name: &str,
+    args: &[VariableType],
+    int_op: fn(IntType, IntType) -> IntType,
+    float_op: fn(FloatType, FloatType) -> FloatType,
+) -> ShortResult<VariableType> {
+    match expect_args(name, args)? {
+        [VariableType::Integer(a), VariableType::Integer(b)] => {
+            Ok(VariableType::Integer(int_op(*a, *b)))
+        }
+        [VariableType::Integer(a), VariableType::Float(b)] => {
+            Ok(VariableType::Float(float_op(*a as FloatType, *b)))
+        }
+        [VariableType::Float(a), VariableType::Integer(b)] => {
+            Ok(VariableType::Float(float_op(*a, *b as FloatType)))
+        }
+        [VariableType::Float(a), VariableType::Float(b)] => {
+            Ok(VariableType::Float(float_op(*a, *b)))
+        }
+        [a, b] => Err(CustomError::TypeMismatch(format!(
+            "Cannot get the {} of a {} and a {}",
+            name,
+            a.type_name(),
+            b.type_name()
+        ))),
+    }
+}
@@ -0,0 +1,405 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::rc::Rc;
+
+use crate::execute::{FloatType, IntType};
+use crate::parse::nodes::classes::ClassDec;
+use crate::parse::nodes::functions::FctDec;
+use crate::parse::nodes::vars::Type;
+use crate::skr_errors::{CustomError, ShortResult};
+
+/// `VariableType` is a value known at runtime. This is the result of the evaluation of any
+/// expression, and the value stored in a variable.
+#[derive(Debug, Clone, PartialEq)]
+pub enum VariableType {
+    Integer(IntType),
+    Float(FloatType),
+    Boolean(bool),
+    String(String),
+    Char(char),
+    /// The elements of an array are expected to have the same type
+    Array(Vec<VariableType>),
+    /// A function declared with `ums`, it is stored like any other variable
+    Function(Rc<FctDec>),
+    /// A class declared with `kat`, calling it creates an [Object]
+    Class(Rc<ClassDec>),
+    /// An instance of a class
+    Object(Object),
+    /// Value of the nodes that do not produce anything, like a loop
+    Null,
+}
+
+impl VariableType {
+    /// The order of two values for `<`, `>`, `<=` and `>=` : the numbers between them, an int
+    /// being converted to a float when compared to a float, and the strings and the chars in
+    /// lexicographic order. The other values, and a NaN, have no order.
+    ///
+    /// `1` and `1.0` are `Equal` here, like with `==` in the language, but the two values are not
+    /// equal for [PartialEq], which also compares the types. See the [PartialOrd] of the values.
+    pub fn compare(&self, other: &Self) -> Option<Ordering> {
+        match (self, other) {
+            (VariableType::Integer(left), VariableType::Integer(right)) => left.partial_cmp(right),
+            (VariableType::Float(left), VariableType::Float(right)) => left.partial_cmp(right),
+            (VariableType::Integer(left), VariableType::Float(right)) => {
+                (*left as FloatType).partial_cmp(right)
+            }
+            (VariableType::Float(left), VariableType::Integer(right)) => {
+                left.partial_cmp(&(*right as FloatType))
+            }
+            (VariableType::String(left), VariableType::String(right)) => left.partial_cmp(right),
+            (VariableType::Char(left), VariableType::Char(right)) => left.partial_cmp(right),
+            _ => None,
+        }
+    }
+
+    /// Name of the type of the value, used in error messages.
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            VariableType::Integer(_) => "int",
+            VariableType::Float(_) => "float",
+            VariableType::Boolean(_) => "bool",
+            VariableType::String(_) => "string",
+            VariableType::Char(_) => "char",
+            VariableType::Array(_) => "array",
+            VariableType::Function(_) => "ums",
+            VariableType::Class(_) => "kat",
+            VariableType::Object(_) => "object",
+            VariableType::Null => "null",
+        }
+    }
+
+    /// Get the field `name` of an object.
+    pub fn field(&self, name: &str) -> ShortResult<VariableType> {
+        match self {
+            VariableType::Object(object) => object.fields.get(name).cloned().ok_or_else(|| {
+                CustomError::InvalidOperation(format!(
+                    "The class {} has no field {}",
+                    object.class, name
+                ))
+            }),
+            value => Err(CustomError::InvalidOperation(format!(
+                "Cannot get the field {} of a {}",
+                name,
+                value.type_name()
+            ))),
+        }
+    }
+
+    /// Get the element at `index` of an array.
+    pub fn index(&self, index: IntType) -> ShortResult<VariableType> {
+        match self {
+            VariableType::Array(elements) => usize::try_from(index)
+                .ok()
+                .and_then(|i| elements.get(i))
+                .cloned()
+                .ok_or(CustomError::IndexOutOfBounds(index, elements.len())),
+            value => Err(CustomError::InvalidOperation(format!(
+                "Cannot index a {}",
+                value.type_name()
+            ))),
+        }
+    }
+}
+
+/// The order of [VariableType::compare], kept consistent with [PartialEq] : two equal values are
+/// `Equal`, even when they have no order like two booleans, and an int and a float with the same
+/// value, like `1` and `1.0`, are not ordered.
+impl PartialOrd for VariableType {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        if self == other {
+            return Some(Ordering::Equal);
+        }
+        match self.compare(other) {
+            Some(Ordering::Equal) => None,
+            ordering => ordering,
+        }
+    }
+}
+
+impl Display for VariableType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            VariableType::Integer(value) => write!(f, "{}", value),
+            VariableType::Float(value) => write!(f, "{}", format_float(*value)),
+            VariableType::Boolean(value) => write!(f, "{}", if *value { "io" } else { "no" }),
+            VariableType::String(value) => write!(f, "{}", value),
+            VariableType::Char(value) => write!(f, "{}", value),
+            VariableType::Array(elements) => {
+                write!(f, "[")?;
+                for (i, element) in elements.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", element)?;
+                }
+                write!(f, "]")
+            }
+            VariableType::Function(function) => write!(f, "ums {}", function.identifier()),
+            VariableType::Class(class) => write!(f, "kat {}", class.identifier()),
+            VariableType::Object(object) => write!(f, "{}", object.class),
+            VariableType::Null => write!(f, "null"),
+        }
+    }
+}
+
+/// Write a float as the users see it : the shortest form that reads back as the same value, with
+/// a `.0` for the integral values so a float never looks like an int (`1.0`, `1.5`, `0.1`).
+pub(crate) fn format_float(value: FloatType) -> String {
+    if value.is_finite() && value.fract() == 0.0 {
+        format!("{:.1}", value)
+    } else {
+        value.to_string()
+    }
+}
+
+/// An instance of a class : the name of the class and the values of its fields.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Object {
+    pub class: String,
+    pub fields: HashMap<String, VariableType>,
+}
+
+/// A variable stored in a scope of the [VariableStack].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Variable {
+    value: VariableType,
+    /// Declared type, the new values are checked against it. The functions have no declared type
+    type_: Option<Type>,
+    /// Declared with `ju`, the value cannot be changed
+    constant: bool,
+    /// For a variable declared `null` : the type of its first value that is not null. The next
+    /// values must have this type.
+    inferred: Option<&'static str>,
+}
+
+impl Variable {
+    /// Check that `value` can be stored in this variable and return the value to store.
+    ///
+    /// A variable declared `null` adopts the type of the first value that is not null, then this
+    /// type is fixed : storing a value of another type, null included, is an error.
+    fn check(&mut self, value: VariableType) -> ShortResult<VariableType> {
+        match (&self.type_, self.inferred) {
+            (_, Some(inferred)) if value.type_name() != inferred => {
+                Err(CustomError::TypeMismatch(format!(
+                    "Cannot assign a {} to a variable of type {} inferred from its first value",
+                    value.type_name(),
+                    inferred
+                )))
+            }
+            (_, Some(_)) => Ok(value),
+            (Some(type_), None) if type_.name == "null" && value != VariableType::Null => {
+                self.inferred = Some(value.type_name());
+                Ok(value)
+            }
+            (Some(type_), None) => type_.assign(value),
+            (None, None) => Ok(value),
+        }
+    }
+}
+
+pub type Scope = HashMap<String, Variable>;
+
+/// `VariableStack` stores the variables of the code being evaluated. Each scope is a layer of the
+/// stack : entering a block pushes a layer and leaving it drops every variable declared inside.
+///
+/// A variable of an inner scope can shadow a variable of an outer scope, the outer variable is
+/// visible again when the inner scope is popped.
+///
+/// The number of nested scopes of a function call, or of the code outside the functions, is
+/// limited like the nesting of the parser. The recursions are limited by the number of calls, see
+/// [VariableStack::push_frame].
+#[derive(Debug, Clone, PartialEq)]
+pub struct VariableStack {
+    scopes: Vec<Scope>,
+    max_depth: usize,
+    /// Number of function calls being evaluated
+    call_depth: usize,
+}
+
+/// Default maximum number of nested scopes, and of expressions nested in each other when parsing.
+pub const DEFAULT_MAX_DEPTH: usize = 1024;
+
+impl Default for VariableStack {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl VariableStack {
+    /// Create a stack with only the global scope, that accepts at most [DEFAULT_MAX_DEPTH] nested
+    /// scopes.
+    pub fn new() -> Self {
+        Self::with_max_depth(DEFAULT_MAX_DEPTH)
+    }
+
+    /// Create a stack with only the global scope, that accepts at most `max_depth` nested scopes.
+    pub fn with_max_depth(max_depth: usize) -> Self {
+        Self {
+            scopes: vec![HashMap::new()],
+            max_depth,
+            call_depth: 0,
+        }
+    }
+
+    /// Number of scopes above the global scope, without the scopes hidden by function calls.
+    pub fn depth(&self) -> usize {
+        self.scopes.len() - 1
+    }
+
+    /// Push an empty scope on top of the stack. Fails when the maximum depth is reached.
+    pub fn push_scope(&mut self) -> ShortResult<()> {
+        if self.depth() >= self.max_depth {
+            return Err(CustomError::InvalidOperation(format!(
+                "The maximum nesting depth of {} scopes is exceeded",
+                self.max_depth
+            )));
+        }
+        self.scopes.push(HashMap::new());
+        Ok(())
+    }
+
+    /// Drop the innermost scope and its variables. The global scope is never dropped.
+    pub fn pop_scope(&mut self) {
+        if self.scopes.len() > 1 {
+            self.scopes.pop();
+        }
+    }
+
+    /// Number of function calls being evaluated.
+    pub fn call_depth(&self) -> usize {
+        self.call_depth
+    }
+
+    /// Hide every scope except the global one and push the first scope of a function call. The
+    /// hidden scopes are returned and must be given back to [VariableStack::pop_frame]. Fails when
+    /// `max_call_depth` calls are already being evaluated.
+    pub fn push_frame(&mut self, max_call_depth: usize) -> ShortResult<Vec<Scope>> {
+        if self.call_depth >= max_call_depth {
+            return Err(CustomError::RecursionLimit(max_call_depth));
+        }
+        let caller_scopes = self.scopes.split_off(1);
+        if let Err(err) = self.push_scope() {
+            self.scopes.extend(caller_scopes);
+            return Err(err);
+        }
+        self.call_depth += 1;
+        Ok(caller_scopes)
+    }
+
+    /// Drop the scopes of a function call and restore the scopes of the caller.
+    pub fn pop_frame(&mut self, caller_scopes: Vec<Scope>) {
+        self.call_depth -= 1;
+        self.scopes.truncate(1);
+        self.scopes.extend(caller_scopes);
+    }
+
+    /// Declare a variable in the innermost scope.
+    pub fn declare(&mut self, name: &str, value: VariableType) -> ShortResult<()> {
+        self.insert(
+            name,
+            Variable {
+                value,
+                type_: None,
+                constant: false,
+                inferred: None,
+            },
+        )
+    }
+
+    /// Declare a variable of the type `type_` in the innermost scope. The values given to
+    /// [VariableStack::set] must be of this type, and a `constant` cannot be changed at all. The
+    /// type of a variable declared `null` is inferred from its first value that is not null.
+    pub fn declare_typed(
+        &mut self,
+        name: &str,
+        value: VariableType,
+        type_: Type,
+        constant: bool,
+    ) -> ShortResult<()> {
+        self.insert(
+            name,
+            Variable {
+                value,
+                type_: Some(type_),
+                constant,
+                inferred: None,
+            },
+        )
+    }
+
+    fn insert(&mut self, name: &str, variable: Variable) -> ShortResult<()> {
+        let scope = self
+            .scopes
+            .last_mut()
+            .expect("there is always a global scope");
+        if scope.contains_key(name) {
+            return Err(CustomError::InvalidOperation(format!(
+                "The variable {} is already declared in this scope",
+                name
+            )));
+        }
+        scope.insert(name.to_string(), variable);
+        Ok(())
+    }
+
+    /// Get the value of the variable declared in the innermost scope.
+    pub fn get(&self, name: &str) -> ShortResult<VariableType> {
+        self.scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(name))
+            .map(|variable| variable.value.clone())
+            .ok_or_else(|| CustomError::UnknownVariable(name.to_string()))
+    }
+
+    /// Change the value of the variable declared in the innermost scope and return the value stored,
+    /// after checking it against the declared type. The `line` of the modification is used in the
+    /// error when the variable is a constant.
+    pub fn set(
+        &mut self,
+        name: &str,
+        value: VariableType,
+        line: usize,
+    ) -> ShortResult<VariableType> {
+        match self
+            .scopes
+            .iter_mut()
+            .rev()
+            .find_map(|scope| scope.get_mut(name))
+        {
+            Some(variable) if variable.constant => {
+                Err(CustomError::ConstantReassignment(name.to_string(), line))
+            }
+            Some(variable) => {
+                variable.value = variable.check(value)?;
+                Ok(variable.value.clone())
+            }
+            None => Err(CustomError::UnknownVariable(name.to_string())),
+        }
+    }
+
+    /// Check that the variable `name` could be changed, without changing it : it must be declared
+    /// and not be a constant, and a `value` already known must match its declared type. The type
+    /// inferred by a variable declared `null` is only known during the evaluation.
+    pub fn check_set(
+        &self,
+        name: &str,
+        value: Option<VariableType>,
+        line: usize,
+    ) -> ShortResult<()> {
+        match self.scopes.iter().rev().find_map(|scope| scope.get(name)) {
+            Some(variable) if variable.constant => {
+                Err(CustomError::ConstantReassignment(name.to_string(), line))
+            }
+            Some(Variable {
+                type_: Some(type_), ..
+            }) if type_.name != "null" => match value {
+                Some(value) => type_.assign(value).map(|_| ()),
+                None => Ok(()),
+            },
+            Some(_) => Ok(()),
+            None => Err(CustomError::UnknownVariable(name.to_string())),
+        }
+    }
+}
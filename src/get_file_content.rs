@@ -1,23 +1,34 @@
 use std::io::ErrorKind;
+use std::path::Path;
 
+use crate::skr_errors::CustomError;
 use crate::utils::{input, read};
 use crate::FLAG_CHAR;
 
+/// The extensions of the Skribi files, used when the tools do not give their own list
+pub const DEFAULT_EXTENSIONS: [&str; 2] = ["skrb", "skribi"];
+
 /// This function is used to get the path of the file to run
 ///
-/// The path can either be passed as an argument or entered the terminal
-pub fn get_content(args: Vec<String>, extensions: Vec<String>) -> Result<String, ErrorKind> {
+/// The path can either be passed as an argument or entered the terminal. A path must end with one
+/// of the `extensions`, without the dot.
+pub fn get_content(args: Vec<String>, extensions: &[&str]) -> Result<String, CustomError> {
     if args.len() > 1 && !args[1].starts_with(FLAG_CHAR) {
         let path = args[1].clone();
 
         // Check if the file has the right extension
-        let extension = String::from(path.split('.').next_back().unwrap());
-        if !extensions.contains(&extension) {
-            return Err(ErrorKind::InvalidInput);
+        let extension = Path::new(&path)
+            .extension()
+            .and_then(|extension| extension.to_str());
+        if !extension.is_some_and(|extension| extensions.contains(&extension)) {
+            return Err(CustomError::WrongExtension(path));
         }
 
         // Read the file
-        return read(&path);
+        return read(&path).map_err(|kind| match kind {
+            ErrorKind::NotFound => CustomError::FileNotFound(path),
+            _ => CustomError::InvalidFile(path),
+        });
     }
 
     let mut content = String::new();
@@ -0,0 +1,19 @@
+// *-* coding:utf-8 *-*
+
+/////////////////////
+// Skribi's library //
+/////////////////////
+
+// The interpreter is a library so the benches can use the tokenizer and the parser, the shell is
+// in main.rs
+
+pub mod execute;
+pub mod get_file_content;
+pub mod parse;
+pub mod skr_errors;
+#[cfg(test)]
+mod tests;
+pub mod tokens;
+pub mod utils;
+
+pub const FLAG_CHAR: &str = "--";
@@ -5,55 +5,135 @@
 ////////////////////
 
 use std::env;
-
-use get_file_content::get_content;
+use std::process;
+use std::thread;
 
 // Import
-use crate::tokens::tokenize;
-use crate::utils::clear;
+use skribi_language_source::execute::check::check;
+use skribi_language_source::execute::{Evaluate, OperationContext};
+use skribi_language_source::get_file_content::{get_content, DEFAULT_EXTENSIONS};
+use skribi_language_source::parse;
+use skribi_language_source::parse::nodes::FoldConstants;
+use skribi_language_source::skr_errors::{render_diagnostic, CustomError};
+use skribi_language_source::tokens::{dump_tokens, tokenize_str};
+use skribi_language_source::utils::clear;
+use skribi_language_source::FLAG_CHAR;
 
-pub mod execute;
-mod get_file_content;
-mod parse;
-mod skr_errors;
-#[cfg(test)]
-mod tests;
-mod tokens;
-mod utils;
+/// Size of the stack of the thread running the interpreter. The parser and the evaluation are
+/// recursive, the stack must hold the deepest code accepted by the parser :
+/// [DEFAULT_MAX_DEPTH](skribi_language_source::execute::variables::DEFAULT_MAX_DEPTH)
+/// nested expressions, and the default
+/// [max_call_depth](skribi_language_source::execute::OperationContext::max_call_depth) calls of
+/// functions. Only the pages used are allocated.
+const STACK_SIZE: usize = 1024 * 1024 * 1024;
 
-const FLAG_CHAR: &str = "--";
+/// What the interpreter does with the file, chosen with the flags.
+#[derive(Debug, PartialEq)]
+enum Mode {
+    /// Evaluate the program, the default
+    Run,
+    /// `--tokens` : print the tokens, one per line, to debug the tokenizer
+    Tokens,
+    /// `--ast` : print the AST as a Mermaid graph
+    Ast,
+    /// `--ast-json` : print the AST as JSON for the tools that read the parse tree
+    AstJson,
+    /// `--check` : check the variables of the program without evaluating it
+    Check,
+}
 
-/// Launch the interpreter
-fn main() {
-    // parameters
-    let extension: Vec<String> = vec!["skrb".to_string(), "skribi".to_string()];
+impl Mode {
+    fn from_args(args: &[String]) -> Self {
+        if args.contains(&format!("{FLAG_CHAR}tokens")) {
+            Mode::Tokens
+        } else if args.contains(&format!("{FLAG_CHAR}ast-json")) {
+            Mode::AstJson
+        } else if args.contains(&format!("{FLAG_CHAR}ast")) {
+            Mode::Ast
+        } else if args.contains(&format!("{FLAG_CHAR}check")) {
+            Mode::Check
+        } else {
+            Mode::Run
+        }
+    }
+}
 
-    // generic parameters
-    let args = env::args().collect::<Vec<_>>(); // get the command line arguments
+/// Launch the interpreter. Every error is returned up to here, printed, and ends the process with
+/// a failure code.
+fn main() {
+    let args = env::args().collect();
+    let interpreter = thread::Builder::new()
+        .stack_size(STACK_SIZE)
+        .spawn(move || run(args))
+        .expect("The thread of the interpreter can always be created");
+    let result = interpreter
+        .join()
+        .unwrap_or_else(|panic| std::panic::resume_unwind(panic));
+    if let Err(message) = result {
+        eprintln!("{}", message);
+        process::exit(1);
+    }
+}
 
-    // clear the shell for the user
-    if !args.contains(&format!("{FLAG_CHAR}compiler-debug")) {
+/// Read, parse and evaluate the program. The errors are rendered as the message to print.
+fn run(args: Vec<String>) -> Result<(), String> {
+    let mode = Mode::from_args(&args);
+    let strict = args.contains(&format!("{FLAG_CHAR}strict"));
+    // clear the shell for the user, the dumps are read by other tools and must start with the data
+    if mode == Mode::Run && !args.contains(&format!("{FLAG_CHAR}compiler-debug")) {
         clear();
     }
 
-    match get_content(args, extension.clone()) {
-        Ok(content) => {
-            // Read the file
-            let lines = content;
-
-            // Remove the comments and split the code into instructions
-            match tokenize(lines) {
-                Ok(tokens) => {
-                    let _nodes = parse::parse(tokens);
-                    // TODO
-                }
-                Err(err) => {
-                    panic!("{:?}", err);
-                }
-            }
+    let content = get_content(args, &DEFAULT_EXTENSIONS).map_err(|err| match err {
+        CustomError::WrongExtension(_) => format!(
+            "error: {}. Valid file extensions : {:?}",
+            err, DEFAULT_EXTENSIONS
+        ),
+        err => format!("error: {}", err),
+    })?;
+
+    // Remove the comments and split the code into instructions
+    let tokens = tokenize_str(&content).map_err(|err| render_diagnostic(&content, &err))?;
+
+    match mode {
+        Mode::Tokens => {
+            print!("{}", dump_tokens(&tokens));
+            return Ok(());
         }
-        Err(err) => {
-            panic!("Error while getting the content of the file. Check the file extension and the file path. Valid file extensions : {:?}. Error message : {:?}", extension.clone(), err);
+        Mode::Ast => {
+            let graph =
+                parse::parse_to_graph(tokens).map_err(|err| render_diagnostic(&content, &err))?;
+            println!("{}", graph);
+            return Ok(());
         }
+        Mode::AstJson => {
+            let json =
+                parse::parse_to_json(tokens).map_err(|err| render_diagnostic(&content, &err))?;
+            println!("{}", json);
+            return Ok(());
+        }
+        Mode::Run | Mode::Check => {}
+    }
+
+    // Every parse error is reported, not only the first one
+    let render_all = |errors: Vec<CustomError>| {
+        errors
+            .iter()
+            .map(|err| render_diagnostic(&content, err))
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+    let mut program = parse::parse_all(tokens).map_err(render_all)?;
+    program.fold_constants();
+    let operation_context = OperationContext {
+        strict,
+        ..OperationContext::default()
+    };
+    if mode == Mode::Check {
+        return check(&program, &operation_context).map_err(render_all);
     }
+    program
+        .evaluate(&operation_context)
+        .map_err(|err| render_diagnostic(&content, &err))?;
+    Ok(())
 }
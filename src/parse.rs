@@ -1,13 +1,58 @@
 use std::collections::VecDeque;
 
+use crate::parse::context::ParseContext;
 use crate::parse::nodes::files_node::FileNode;
-use crate::skr_errors::ResultOption;
+use crate::parse::nodes::statements::Statement;
+use crate::parse::nodes::GraphDisplay;
+use crate::skr_errors::{CustomError, ResultOption, ShortResult};
 use crate::tokens::TokenContainer;
 
-pub(crate) mod nodes;
+pub mod context;
+pub mod nodes;
+pub(crate) mod token_stream;
 
-/// Parse the tokens into an AST.
-pub fn parse(mut tokens: VecDeque<TokenContainer>) -> ResultOption<FileNode> {
-    // This function will add more code when the other functions are implemented
-    FileNode::parse(&mut tokens)
+/// Root of the AST of a whole program : the top-level statements of the file, in order.
+pub type Program = FileNode;
+
+/// Parse the tokens of a whole program into its AST, without evaluating anything.
+///
+/// Every token must be used : a token left after the last statement is an
+/// [CustomError::UnexpectedToken](crate::skr_errors::CustomError::UnexpectedToken). The classes
+/// declared in the program are only known during this parsing.
+pub fn parse(mut tokens: VecDeque<TokenContainer>) -> ResultOption<Program> {
+    Program::parse(&mut tokens, &mut ParseContext::new())
+}
+
+/// Parse only the next top-level [Statement] of the tokens, for an interactive prompt that reads
+/// the program one statement at a time. The tokens after the statement are left in `tokens`, and
+/// `context` must be kept between the calls so the classes declared before are known. Returns
+/// `None` when there is no statement left.
+pub fn parse_statement(
+    tokens: &mut VecDeque<TokenContainer>,
+    context: &mut ParseContext,
+) -> ResultOption<Statement> {
+    FileNode::parse_statement(tokens, context)
+}
+
+/// Parse the tokens of a whole program, and return every error found instead of only the first one.
+///
+/// See [FileNode::parse_all].
+pub fn parse_all(mut tokens: VecDeque<TokenContainer>) -> Result<Program, Vec<CustomError>> {
+    Program::parse_all(&mut tokens, &mut ParseContext::new())
+}
+
+/// Parse the tokens and serialize the AST into JSON so other tools can read the parse tree.
+///
+/// Each node is an object named after its variant, e.g. `{"ConstVar": {...}}`, mirroring the
+/// hierarchy shown by the Mermaid graph.
+pub fn parse_to_json(tokens: VecDeque<TokenContainer>) -> ShortResult<String> {
+    let file_node = parse(tokens)?;
+    Ok(serde_json::to_string(&file_node).expect("The AST can always be serialized"))
+}
+
+/// Parse the tokens and write the AST as a Mermaid flowchart, the same graph as the debug output of
+/// the nodes. The root is the `File` subgraph, containing one subgraph per top-level statement.
+pub fn parse_to_graph(tokens: VecDeque<TokenContainer>) -> ShortResult<String> {
+    let file_node = parse(tokens)?;
+    Ok(file_node.map_or_else(String::new, |file_node| file_node.graph()))
 }
@@ -0,0 +1,82 @@
+use std::ops::{Deref, DerefMut};
+
+use crate::execute::variables::DEFAULT_MAX_DEPTH;
+use crate::parse::nodes::classes::ClassManager;
+use crate::skr_errors::{CustomError, ShortResult};
+use crate::tokens::TokenContainer;
+
+/// `ParseContext` is given to every parse function alongside the tokens. It holds what the parser
+/// knows about the code before this point : the [ClassManager] with the types, and the number of
+/// expressions being parsed in each other.
+///
+/// The parser is recursive, so the nesting is limited to stop the code that would overflow the
+/// stack. By default the limit is the maximum number of nested scopes of the evaluation,
+/// [DEFAULT_MAX_DEPTH], so a code that can be parsed is only stopped by the evaluation.
+pub struct ParseContext {
+    pub classes: ClassManager,
+    nesting: usize,
+    max_nesting: usize,
+}
+
+impl Default for ParseContext {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ParseContext {
+    pub fn new() -> Self {
+        Self::with_max_nesting(DEFAULT_MAX_DEPTH)
+    }
+
+    /// Create a context that accepts at most `max_nesting` expressions nested in each other.
+    pub fn with_max_nesting(max_nesting: usize) -> Self {
+        Self {
+            classes: ClassManager::new(),
+            nesting: 0,
+            max_nesting,
+        }
+    }
+
+    /// Start an expression inside the expressions being parsed, it ends when the returned
+    /// [Nested] is dropped. Fails at `token` when the maximum of nested expressions is reached.
+    pub(crate) fn nest(&mut self, token: Option<&TokenContainer>) -> ShortResult<Nested<'_>> {
+        if self.nesting >= self.max_nesting {
+            return Err(CustomError::unexpected_token(
+                &format!(
+                    "The code is nested too deeply, the maximum is {} nested expressions",
+                    self.max_nesting
+                ),
+                token,
+            ));
+        }
+        self.nesting += 1;
+        Ok(Nested { context: self })
+    }
+}
+
+/// The context while an expression started with [ParseContext::nest] is parsed. The expression
+/// ends when it is dropped, even when the parsing fails.
+pub(crate) struct Nested<'a> {
+    context: &'a mut ParseContext,
+}
+
+impl Deref for Nested<'_> {
+    type Target = ParseContext;
+
+    fn deref(&self) -> &ParseContext {
+        self.context
+    }
+}
+
+impl DerefMut for Nested<'_> {
+    fn deref_mut(&mut self) -> &mut ParseContext {
+        self.context
+    }
+}
+
+impl Drop for Nested<'_> {
+    fn drop(&mut self) {
+        self.context.nesting -= 1;
+    }
+}
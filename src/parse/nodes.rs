@@ -1,18 +1,24 @@
 #![allow(dead_code)]
 
+use crate::parse::context::ParseContext;
 use crate::skr_errors::ResultOption;
-use crate::tokens::TokenContainer;
+use crate::some_token;
+use crate::tokens::{SpaceTypes, Token, TokenContainer};
 use std::collections::VecDeque;
 
-mod blocs;
-mod classes;
+pub(crate) mod blocs;
+pub(crate) mod classes;
 pub(crate) mod expressions;
 pub mod files_node;
-mod functions;
+pub(crate) mod functions;
 pub(crate) mod id_nodes;
-mod if_else;
+pub(crate) mod if_else;
+pub(crate) mod loops;
+pub(crate) mod matches;
 pub(crate) mod operations;
-mod vars;
+pub mod statements;
+pub(crate) mod vars;
+pub(crate) mod visitor;
 
 /// Macro to implement the Debug trait for a GraphDisplay
 #[macro_export]
@@ -29,6 +35,9 @@ macro_rules! impl_debug {
 /// Trait to display a graph with mermaid syntax. Used to display trees of nodes easily when
 /// debugging the AST.
 ///
+/// A node opens its subgraph in the [Graph] with its name and a label, writes its children and
+/// closes it. `id` counts the nodes already written, it is incremented by each node.
+///
 /// # Warning
 ///
 /// You must use the macro [impl_debug] to implement the Debug trait for a struct that implements
@@ -37,17 +46,18 @@ macro_rules! impl_debug {
 /// # Example
 ///
 /// ```
-/// use std::fmt;
-/// use skr::parse::nodes::GraphDisplay;
+/// use skribi_language_source::impl_debug;
+/// use skribi_language_source::parse::nodes::{Graph, GraphDisplay};
 ///
 /// struct MyNode {
 ///    name: String,
 /// }
 ///
 /// impl GraphDisplay for MyNode {
-///    fn graph_display(&self, graph: &mut String, id: &mut usize) {
-///       graph.push_str(&format!("\nsubgraph MyNode_{}[MyNode {}]\nend", id, self.name));
+///    fn graph_display(&self, graph: &mut Graph, id: &mut usize) {
+///       graph.open("MyNode", &format!("MyNode {}", self.name), *id);
 ///       *id += 1;
+///       graph.close();
 ///   }
 /// }
 ///
@@ -56,15 +66,180 @@ macro_rules! impl_debug {
 /// let node = MyNode { name: "test".to_string() };
 /// println!("{:?}", node);
 /// ```
-trait GraphDisplay {
-    fn graph_display(&self, graph: &mut String, id: &mut usize);
+pub trait GraphDisplay {
+    fn graph_display(&self, graph: &mut Graph, id: &mut usize);
 
     fn graph(&self) -> String {
-        let mut graph = String::new();
-        graph.push_str("flowchart TD");
-        let mut id = 0;
-        self.graph_display(&mut graph, &mut id);
-        graph
+        let mut graph = Graph::new(GraphSyntax::Mermaid);
+        self.graph_display(&mut graph, &mut 0);
+        graph.text
+    }
+
+    /// The same graph as [GraphDisplay::graph], but the id of each subgraph is its path in the
+    /// tree instead of a counter : the root is `File_0`, its second child `Exp_0_1`, and so on.
+    /// Adding a node only changes the ids of the nodes after it in the same parent, so the graphs
+    /// can be compared in snapshot tests.
+    fn stable_graph(&self) -> String {
+        let mut graph = Graph::new(GraphSyntax::StableMermaid);
+        self.graph_display(&mut graph, &mut 0);
+        graph.text
+    }
+
+    /// The same tree as [GraphDisplay::graph] with Graphviz syntax : every subgraph becomes a
+    /// node `node_<id>`, linked to the node containing it.
+    fn dot(&self) -> String {
+        let mut graph = Graph::new(GraphSyntax::Dot);
+        self.graph_display(&mut graph, &mut 0);
+        graph.text.push_str("\n}");
+        graph.text
+    }
+}
+
+/// The syntax of the text written in a [Graph].
+#[derive(Clone, Copy, PartialEq)]
+enum GraphSyntax {
+    /// Mermaid, the id of a subgraph is the counter of the nodes
+    Mermaid,
+    /// Mermaid, the id of a subgraph is its path in the tree
+    StableMermaid,
+    /// Graphviz, the subgraphs are nodes linked to their parent
+    Dot,
+}
+
+/// A subgraph opened in a [Graph] and not closed yet.
+struct OpenSubgraph {
+    /// Id of the subgraph in the text
+    id: String,
+    /// Path of the subgraph in the tree, like `0_1`
+    path: String,
+    /// Number of subgraphs already written inside it
+    children: usize,
+}
+
+/// The text of a graph being written by [GraphDisplay::graph_display]. The ids of the subgraphs
+/// are given while the nodes are walked, and the labels are escaped for the syntax.
+pub struct Graph {
+    syntax: GraphSyntax,
+    text: String,
+    open: Vec<OpenSubgraph>,
+    /// Number of subgraphs written at the root
+    roots: usize,
+}
+
+impl Graph {
+    fn new(syntax: GraphSyntax) -> Self {
+        let header = match syntax {
+            GraphSyntax::Mermaid | GraphSyntax::StableMermaid => "flowchart TD",
+            GraphSyntax::Dot => "digraph AST {",
+        };
+        Self {
+            syntax,
+            text: String::from(header),
+            open: Vec::new(),
+            roots: 0,
+        }
+    }
+
+    /// Open the subgraph of a node, `id` is the number of nodes written before it. The subgraph
+    /// contains the next ones until [Graph::close] is called.
+    pub fn open(&mut self, name: &str, label: &str, id: usize) {
+        let children = match self.open.last_mut() {
+            Some(parent) => &mut parent.children,
+            None => &mut self.roots,
+        };
+        let index = *children;
+        *children += 1;
+        let path = match self.open.last() {
+            Some(parent) => format!("{}_{}", parent.path, index),
+            None => index.to_string(),
+        };
+        let subgraph_id = match self.syntax {
+            GraphSyntax::Mermaid => format!("{}_{}", name, id),
+            GraphSyntax::StableMermaid => format!("{}_{}", name, path),
+            GraphSyntax::Dot => format!("node_{}", id),
+        };
+        match self.syntax {
+            GraphSyntax::Mermaid | GraphSyntax::StableMermaid => {
+                // the quotes would end the label, Mermaid writes them with an entity
+                let label = label.replace('"', "#quot;");
+                self.text
+                    .push_str(&format!("\nsubgraph {}[{}]", subgraph_id, label));
+            }
+            GraphSyntax::Dot => {
+                let label = label
+                    .replace('\\', "\\\\")
+                    .replace('"', "\\\"")
+                    .replace('\n', "\\n");
+                self.text
+                    .push_str(&format!("\n    {} [label=\"{}\"];", subgraph_id, label));
+                if let Some(parent) = self.open.last() {
+                    self.text
+                        .push_str(&format!("\n    {} -> {};", parent.id, subgraph_id));
+                }
+            }
+        }
+        self.open.push(OpenSubgraph {
+            id: subgraph_id,
+            path,
+            children: 0,
+        });
+    }
+
+    /// Close the last subgraph opened with [Graph::open].
+    pub fn close(&mut self) {
+        self.open.pop();
+        if self.syntax != GraphSyntax::Dot {
+            self.text.push_str("\nend");
+        }
+    }
+}
+
+/// Trait to write a node back as Skribi code, for the tools like a formatter. The code is written
+/// in a canonical form : one space between the tokens, and one statement per line in the blocks,
+/// indented with 4 spaces. The comments are not in the AST, so they are lost.
+pub(crate) trait ToSource {
+    fn to_source(&self) -> String;
+}
+
+/// Trait to compute the operations between numeric literals once, after the parsing, instead of at
+/// each evaluation : `2 + 3 * 4` is replaced by the literal `14`. An operation that fails, like an
+/// overflow or a division by zero, is kept so the error happens at runtime. The nodes only forward
+/// the call to their children, the folding itself is done by
+/// [TakePriorityN](operations::TakePriorityN).
+pub trait FoldConstants {
+    fn fold_constants(&mut self);
+}
+
+/// Write the members of a block between curly brackets, each one on its own indented lines.
+pub(crate) fn block_source<'a, T: ToSource + 'a>(members: impl Iterator<Item = &'a T>) -> String {
+    let mut source = String::from("{");
+    for member in members {
+        for line in member.to_source().lines() {
+            source.push_str("\n    ");
+            source.push_str(line);
+        }
+    }
+    source.push_str("\n}");
+    source
+}
+
+/// Write the nodes separated by `separator`.
+pub(crate) fn join_source<'a, T: ToSource + 'a>(
+    nodes: impl Iterator<Item = &'a T>,
+    separator: &str,
+) -> String {
+    nodes
+        .map(|node| node.to_source())
+        .collect::<Vec<_>>()
+        .join(separator)
+}
+
+/// Remove the separators before the next statement : the new lines and the `;`.
+///
+/// `<sep> ::= "\n" | T_SEMICOLON`
+pub(crate) fn skip_separators(tokens: &mut VecDeque<TokenContainer>) {
+    while let some_token!(Token::Space(SpaceTypes::NewLine) | Token::Semicolon) = tokens.front() {
+        tokens.pop_front();
     }
 }
 
@@ -83,13 +258,20 @@ macro_rules! token_m {
 }
 
 pub trait Parsable {
-    fn parse(tokens: &mut VecDeque<TokenContainer>) -> ResultOption<Self>
+    fn parse(
+        tokens: &mut VecDeque<TokenContainer>,
+        context: &mut ParseContext,
+    ) -> ResultOption<Self>
     where
         Self: Sized;
 }
 
 pub trait ParsableWithLevel {
-    fn parse(tokens: &mut VecDeque<TokenContainer>, level: u8) -> ResultOption<Self>
+    fn parse(
+        tokens: &mut VecDeque<TokenContainer>,
+        context: &mut ParseContext,
+        level: u8,
+    ) -> ResultOption<Self>
     where
         Self: Sized;
 }
@@ -1,8 +1,14 @@
 use std::collections::VecDeque;
 
+use serde::Serialize;
+
+use crate::execute::variables::VariableType;
+use crate::execute::{Evaluate, OperationContext, OperationIO};
+use crate::parse::context::ParseContext;
 use crate::parse::nodes::expressions::StaL;
-use crate::parse::nodes::GraphDisplay;
-use crate::skr_errors::{CustomError, ResultOption};
+use crate::parse::nodes::visitor::{Visitable, Visitor};
+use crate::parse::nodes::{FoldConstants, Graph, GraphDisplay, ToSource};
+use crate::skr_errors::{CustomError, ResultOption, ShortResult};
 use crate::tokens::{Token, TokenContainer};
 use crate::{impl_debug, some_token};
 
@@ -25,23 +31,33 @@ use crate::{impl_debug, some_token};
 // --- KName ---
 // -------------
 
-#[derive(PartialEq)]
+#[derive(PartialEq, Serialize)]
 pub struct KName {
     name: String,
 }
 
 impl GraphDisplay for KName {
-    fn graph_display(&self, graph: &mut String, id: &mut usize) {
-        graph.push_str(&format!(
-            "\nsubgraph KName_{}[KName {}]\nend",
-            id, self.name
-        ));
+    fn graph_display(&self, graph: &mut Graph, id: &mut usize) {
+        graph.open("KName", &format!("KName {}", self.name), *id);
         *id += 1;
+        graph.close();
     }
 }
 
 impl_debug!(KName);
 
+impl ToSource for KName {
+    fn to_source(&self) -> String {
+        self.name.clone()
+    }
+}
+
+impl Visitable for KName {
+    fn accept<V: Visitor>(&self, visitor: &mut V) {
+        visitor.visit_k_name(self);
+    }
+}
+
 impl KName {
     pub fn new(name: String) -> Self {
         Self { name }
@@ -53,8 +69,9 @@ impl KName {
             if let some_token!(Token::Identifier(name)) = tokens.pop_front() {
                 Ok(Some(KName::new(name)))
             } else {
-                Err(CustomError::UnexpectedToken(
-                    "Expected an identifier".to_string(),
+                Err(CustomError::unexpected_token(
+                    "Expected an identifier",
+                    tokens.front(),
                 ))
             }
         } else {
@@ -75,79 +92,152 @@ impl KName {
 // --- KStart ---
 // --------------
 
-#[derive(PartialEq)]
+#[derive(PartialEq, Serialize)]
 pub struct KStart {
     name: Option<KName>,
     sta_l: StaL,
 }
 
 impl GraphDisplay for KStart {
-    fn graph_display(&self, graph: &mut String, id: &mut usize) {
-        graph.push_str(&format!("\nsubgraph KStart_{}[KStart]", id));
+    fn graph_display(&self, graph: &mut Graph, id: &mut usize) {
+        graph.open("KStart", "KStart", *id);
         *id += 1;
         if let Some(name) = &self.name {
             name.graph_display(graph, id);
         }
         self.sta_l.graph_display(graph, id);
-        graph.push_str("\nend");
+        graph.close();
     }
 }
 
 impl_debug!(KStart);
 
+impl ToSource for KStart {
+    fn to_source(&self) -> String {
+        match &self.name {
+            Some(name) => format!("{} {}", name.to_source(), self.sta_l.to_source()),
+            None => self.sta_l.to_source(),
+        }
+    }
+}
+
+impl FoldConstants for KStart {
+    fn fold_constants(&mut self) {
+        self.sta_l.fold_constants();
+    }
+}
+
+impl Visitable for KStart {
+    fn accept<V: Visitor>(&self, visitor: &mut V) {
+        visitor.visit_k_start(self);
+        if let Some(name) = &self.name {
+            name.accept(visitor);
+        }
+        self.sta_l.accept(visitor);
+    }
+}
+
 impl KStart {
     pub fn new(name: Option<KName>, sta_l: StaL) -> Self {
         Self { name, sta_l }
     }
 
-    pub fn parse(tokens: &mut VecDeque<TokenContainer>) -> ResultOption<Self> {
+    pub fn parse(
+        tokens: &mut VecDeque<TokenContainer>,
+        context: &mut ParseContext,
+    ) -> ResultOption<Self> {
         // <k_start> ::= <sta_l> | <k_name> <sta_l>
-        if let Some(sta_l) = StaL::parse(tokens)? {
+        if let Some(sta_l) = StaL::parse(tokens, context)? {
             Ok(Some(KStart::new(None, sta_l)))
         } else if let Some(name) = KName::parse(tokens)? {
-            if let Some(sta_l) = StaL::parse(tokens)? {
+            if let Some(sta_l) = StaL::parse(tokens, context)? {
                 Ok(Some(KStart::new(Some(name), sta_l)))
             } else {
-                Err(CustomError::UnexpectedToken("Expected a sta_l".to_string()))
+                Err(CustomError::unexpected_token(
+                    "Expected a sta_l",
+                    tokens.front(),
+                ))
             }
         } else {
-            Err(CustomError::UnexpectedToken(
-                "Expected a sta_l or a k_name".to_string(),
+            Err(CustomError::unexpected_token(
+                "Expected a sta_l or a k_name",
+                tokens.front(),
             ))
         }
     }
+
+    /// See [StaL::has_trailing_exp].
+    pub fn has_trailing_exp(&self) -> bool {
+        self.sta_l.has_trailing_exp()
+    }
+}
+
+impl Evaluate for KStart {
+    fn evaluate(&self, operation_context: &OperationContext) -> ShortResult<OperationIO> {
+        self.sta_l.evaluate(operation_context)
+    }
 }
 
 // ------------
 // --- Kodi ---
 // ------------
 
-#[derive(PartialEq)]
+/// `Kodi` represents a simple scope introduced by `kodi`. It is a plain block : the statements
+/// are executed in order and nothing is returned. In strict mode, a block producing a value is an
+/// error, because the value would be lost.
+#[derive(PartialEq, Serialize)]
 pub struct Kodi {
     start: KStart,
 }
 
 impl GraphDisplay for Kodi {
-    fn graph_display(&self, graph: &mut String, id: &mut usize) {
-        graph.push_str(&format!("\nsubgraph Kodi_{}[Kodi]", id));
+    fn graph_display(&self, graph: &mut Graph, id: &mut usize) {
+        graph.open("Kodi", "Kodi", *id);
         *id += 1;
         self.start.graph_display(graph, id);
-        graph.push_str("\nend");
+        graph.close();
     }
 }
 
 impl_debug!(Kodi);
 
+impl ToSource for Kodi {
+    fn to_source(&self) -> String {
+        format!("kodi {}", self.start.to_source())
+    }
+}
+
+impl FoldConstants for Kodi {
+    fn fold_constants(&mut self) {
+        self.start.fold_constants();
+    }
+}
+
+impl Visitable for Kodi {
+    fn accept<V: Visitor>(&self, visitor: &mut V) {
+        visitor.visit_kodi(self);
+        self.start.accept(visitor);
+    }
+}
+
 impl Kodi {
-    pub fn parse(tokens: &mut VecDeque<TokenContainer>) -> ResultOption<Self> {
+    pub fn new(start: KStart) -> Self {
+        Self { start }
+    }
+
+    pub fn parse(
+        tokens: &mut VecDeque<TokenContainer>,
+        context: &mut ParseContext,
+    ) -> ResultOption<Self> {
         // <kodi> ::= kodi <k_start>
         if let some_token!(Token::KeywordSimpleScope) = tokens.front() {
             tokens.pop_front();
-            if let Some(start) = KStart::parse(tokens)? {
-                Ok(Some(Kodi { start }))
+            if let Some(start) = KStart::parse(tokens, context)? {
+                Ok(Some(Kodi::new(start)))
             } else {
-                Err(CustomError::UnexpectedToken(
-                    "Expected a k_start".to_string(),
+                Err(CustomError::unexpected_token(
+                    "Expected a k_start",
+                    tokens.front(),
                 ))
             }
         } else {
@@ -156,36 +246,79 @@ impl Kodi {
     }
 }
 
+impl Evaluate for Kodi {
+    fn evaluate(&self, operation_context: &OperationContext) -> ShortResult<OperationIO> {
+        let value = self.start.evaluate(operation_context)?;
+        if operation_context.strict && value != VariableType::Null {
+            return Err(CustomError::StrictMode(format!(
+                "The kodi scope produces a {} that is lost, use biuli to return it",
+                value.type_name()
+            )));
+        }
+        Ok(VariableType::Null)
+    }
+}
+
 // -------------
 // --- Biuli ---
 // -------------
 
-#[derive(PartialEq)]
+/// `Biuli` represents a bubble scope introduced by `biuli`. The value of the block bubbles up :
+/// the scope evaluates to the value of its trailing expression, or null when the last statement is
+/// a declaration.
+#[derive(PartialEq, Serialize)]
 pub struct Biuli {
     start: KStart,
 }
 
 impl GraphDisplay for Biuli {
-    fn graph_display(&self, graph: &mut String, id: &mut usize) {
-        graph.push_str(&format!("\nsubgraph Biuli_{}[Biuli]", id));
+    fn graph_display(&self, graph: &mut Graph, id: &mut usize) {
+        graph.open("Biuli", "Biuli", *id);
         *id += 1;
         self.start.graph_display(graph, id);
-        graph.push_str("\nend");
+        graph.close();
     }
 }
 
 impl_debug!(Biuli);
 
+impl ToSource for Biuli {
+    fn to_source(&self) -> String {
+        format!("biuli {}", self.start.to_source())
+    }
+}
+
+impl FoldConstants for Biuli {
+    fn fold_constants(&mut self) {
+        self.start.fold_constants();
+    }
+}
+
+impl Visitable for Biuli {
+    fn accept<V: Visitor>(&self, visitor: &mut V) {
+        visitor.visit_biuli(self);
+        self.start.accept(visitor);
+    }
+}
+
 impl Biuli {
-    pub fn parse(tokens: &mut VecDeque<TokenContainer>) -> ResultOption<Self> {
+    pub fn new(start: KStart) -> Self {
+        Self { start }
+    }
+
+    pub fn parse(
+        tokens: &mut VecDeque<TokenContainer>,
+        context: &mut ParseContext,
+    ) -> ResultOption<Self> {
         // <biuli> ::= biuli <k_start>
         if let some_token!(Token::KeywordBubbleScope) = tokens.front() {
             tokens.pop_front();
-            if let Some(start) = KStart::parse(tokens)? {
-                Ok(Some(Biuli { start }))
+            if let Some(start) = KStart::parse(tokens, context)? {
+                Ok(Some(Biuli::new(start)))
             } else {
-                Err(CustomError::UnexpectedToken(
-                    "Expected a k_start".to_string(),
+                Err(CustomError::unexpected_token(
+                    "Expected a k_start",
+                    tokens.front(),
                 ))
             }
         } else {
@@ -194,36 +327,76 @@ impl Biuli {
     }
 }
 
+impl Evaluate for Biuli {
+    fn evaluate(&self, operation_context: &OperationContext) -> ShortResult<OperationIO> {
+        let value = self.start.evaluate(operation_context)?;
+        if self.start.has_trailing_exp() || operation_context.is_returning() {
+            Ok(value)
+        } else {
+            Ok(VariableType::Null)
+        }
+    }
+}
+
 // -------------
 // --- Spoki ---
 // -------------
 
-#[derive(PartialEq)]
+/// `Spoki` represents an unused scope introduced by `spoki`. The statements are executed but the
+/// result of the block is discarded.
+#[derive(PartialEq, Serialize)]
 pub struct Spoki {
     start: KStart,
 }
 
 impl GraphDisplay for Spoki {
-    fn graph_display(&self, graph: &mut String, id: &mut usize) {
-        graph.push_str(&format!("\nsubgraph Spoki_{}[Spoki]", id));
+    fn graph_display(&self, graph: &mut Graph, id: &mut usize) {
+        graph.open("Spoki", "Spoki", *id);
         *id += 1;
         self.start.graph_display(graph, id);
-        graph.push_str("\nend");
+        graph.close();
     }
 }
 
 impl_debug!(Spoki);
 
+impl ToSource for Spoki {
+    fn to_source(&self) -> String {
+        format!("spoki {}", self.start.to_source())
+    }
+}
+
+impl FoldConstants for Spoki {
+    fn fold_constants(&mut self) {
+        self.start.fold_constants();
+    }
+}
+
+impl Visitable for Spoki {
+    fn accept<V: Visitor>(&self, visitor: &mut V) {
+        visitor.visit_spoki(self);
+        self.start.accept(visitor);
+    }
+}
+
 impl Spoki {
-    pub fn parse(tokens: &mut VecDeque<TokenContainer>) -> ResultOption<Self> {
+    pub fn new(start: KStart) -> Self {
+        Self { start }
+    }
+
+    pub fn parse(
+        tokens: &mut VecDeque<TokenContainer>,
+        context: &mut ParseContext,
+    ) -> ResultOption<Self> {
         // <spoki> ::= spoki <k_start>
         if let some_token!(Token::KeywordUnusedScope) = tokens.front() {
             tokens.pop_front();
-            if let Some(start) = KStart::parse(tokens)? {
-                Ok(Some(Spoki { start }))
+            if let Some(start) = KStart::parse(tokens, context)? {
+                Ok(Some(Spoki::new(start)))
             } else {
-                Err(CustomError::UnexpectedToken(
-                    "Expected a k_start".to_string(),
+                Err(CustomError::unexpected_token(
+                    "Expected a k_start",
+                    tokens.front(),
                 ))
             }
         } else {
@@ -232,11 +405,18 @@ impl Spoki {
     }
 }
 
+impl Evaluate for Spoki {
+    fn evaluate(&self, operation_context: &OperationContext) -> ShortResult<OperationIO> {
+        self.start.evaluate(operation_context)?;
+        Ok(VariableType::Null)
+    }
+}
+
 // -----------------
 // --- ScopeBase ---
 // -----------------
 
-#[derive(PartialEq)]
+#[derive(PartialEq, Serialize)]
 pub enum ScopeBase {
     StaL(StaL),
     Kodi(Kodi),
@@ -245,8 +425,8 @@ pub enum ScopeBase {
 }
 
 impl GraphDisplay for ScopeBase {
-    fn graph_display(&self, graph: &mut String, id: &mut usize) {
-        graph.push_str(&format!("\nsubgraph ScopeBase_{}[ScopeBase]", id));
+    fn graph_display(&self, graph: &mut Graph, id: &mut usize) {
+        graph.open("ScopeBase", "ScopeBase", *id);
         *id += 1;
         match self {
             ScopeBase::StaL(sta_l) => sta_l.graph_display(graph, id),
@@ -254,22 +434,59 @@ impl GraphDisplay for ScopeBase {
             ScopeBase::Spoki(spoki) => spoki.graph_display(graph, id),
             ScopeBase::Biuli(biuli) => biuli.graph_display(graph, id),
         }
-        graph.push_str("\nend");
+        graph.close();
     }
 }
 
 impl_debug!(ScopeBase);
 
+impl ToSource for ScopeBase {
+    fn to_source(&self) -> String {
+        match self {
+            ScopeBase::StaL(sta_l) => sta_l.to_source(),
+            ScopeBase::Kodi(kodi) => kodi.to_source(),
+            ScopeBase::Spoki(spoki) => spoki.to_source(),
+            ScopeBase::Biuli(biuli) => biuli.to_source(),
+        }
+    }
+}
+
+impl FoldConstants for ScopeBase {
+    fn fold_constants(&mut self) {
+        match self {
+            ScopeBase::StaL(sta_l) => sta_l.fold_constants(),
+            ScopeBase::Kodi(kodi) => kodi.fold_constants(),
+            ScopeBase::Spoki(spoki) => spoki.fold_constants(),
+            ScopeBase::Biuli(biuli) => biuli.fold_constants(),
+        }
+    }
+}
+
+impl Visitable for ScopeBase {
+    fn accept<V: Visitor>(&self, visitor: &mut V) {
+        visitor.visit_scope_base(self);
+        match self {
+            ScopeBase::StaL(sta_l) => sta_l.accept(visitor),
+            ScopeBase::Kodi(kodi) => kodi.accept(visitor),
+            ScopeBase::Spoki(spoki) => spoki.accept(visitor),
+            ScopeBase::Biuli(biuli) => biuli.accept(visitor),
+        }
+    }
+}
+
 impl ScopeBase {
-    pub fn parse(tokens: &mut VecDeque<TokenContainer>) -> ResultOption<Self> {
+    pub fn parse(
+        tokens: &mut VecDeque<TokenContainer>,
+        context: &mut ParseContext,
+    ) -> ResultOption<Self> {
         // <scope_base> ::= <sta_l> | <kodi> | <spoki> | <biuli>
-        if let Some(sta_l) = StaL::parse(tokens)? {
+        if let Some(sta_l) = StaL::parse(tokens, context)? {
             Ok(Some(ScopeBase::StaL(sta_l)))
-        } else if let Some(kodi) = Kodi::parse(tokens)? {
+        } else if let Some(kodi) = Kodi::parse(tokens, context)? {
             Ok(Some(ScopeBase::Kodi(kodi)))
-        } else if let Some(spoki) = Spoki::parse(tokens)? {
+        } else if let Some(spoki) = Spoki::parse(tokens, context)? {
             Ok(Some(ScopeBase::Spoki(spoki)))
-        } else if let Some(biuli) = Biuli::parse(tokens)? {
+        } else if let Some(biuli) = Biuli::parse(tokens, context)? {
             Ok(Some(ScopeBase::Biuli(biuli)))
         } else {
             Ok(None)
@@ -277,39 +494,90 @@ impl ScopeBase {
     }
 }
 
+impl Evaluate for ScopeBase {
+    fn evaluate(&self, operation_context: &OperationContext) -> ShortResult<OperationIO> {
+        match self {
+            ScopeBase::StaL(sta_l) => sta_l.evaluate(operation_context),
+            ScopeBase::Kodi(kodi) => kodi.evaluate(operation_context),
+            ScopeBase::Spoki(spoki) => spoki.evaluate(operation_context),
+            ScopeBase::Biuli(biuli) => biuli.evaluate(operation_context),
+        }
+    }
+}
+
 // -------------
 // --- Scope ---
 // -------------
 
-#[derive(PartialEq)]
-pub(crate) enum Scope {
+#[derive(PartialEq, Serialize)]
+pub enum Scope {
     ScopeBase(ScopeBase),
     Sta(StaL),
 }
 
 impl GraphDisplay for Scope {
-    fn graph_display(&self, graph: &mut String, id: &mut usize) {
-        graph.push_str(&format!("\nsubgraph Scope_{}[Scope]", id));
+    fn graph_display(&self, graph: &mut Graph, id: &mut usize) {
+        graph.open("Scope", "Scope", *id);
         *id += 1;
         match self {
             Scope::ScopeBase(scope_base) => scope_base.graph_display(graph, id),
             Scope::Sta(sta_l) => sta_l.graph_display(graph, id),
         }
-        graph.push_str("\nend");
+        graph.close();
     }
 }
 
 impl_debug!(Scope);
 
+impl ToSource for Scope {
+    fn to_source(&self) -> String {
+        match self {
+            Scope::ScopeBase(scope_base) => scope_base.to_source(),
+            Scope::Sta(sta_l) => sta_l.to_source(),
+        }
+    }
+}
+
+impl FoldConstants for Scope {
+    fn fold_constants(&mut self) {
+        match self {
+            Scope::ScopeBase(scope_base) => scope_base.fold_constants(),
+            Scope::Sta(sta_l) => sta_l.fold_constants(),
+        }
+    }
+}
+
+impl Visitable for Scope {
+    fn accept<V: Visitor>(&self, visitor: &mut V) {
+        visitor.visit_scope(self);
+        match self {
+            Scope::ScopeBase(scope_base) => scope_base.accept(visitor),
+            Scope::Sta(sta_l) => sta_l.accept(visitor),
+        }
+    }
+}
+
 impl Scope {
-    pub fn parse(tokens: &mut VecDeque<TokenContainer>) -> ResultOption<Self> {
+    pub fn parse(
+        tokens: &mut VecDeque<TokenContainer>,
+        context: &mut ParseContext,
+    ) -> ResultOption<Self> {
         // <scope> ::= <scope_base> | <sta>
-        if let Some(scope_base) = ScopeBase::parse(tokens)? {
+        if let Some(scope_base) = ScopeBase::parse(tokens, context)? {
             Ok(Some(Scope::ScopeBase(scope_base)))
-        } else if let Some(sta_l) = StaL::parse(tokens)? {
+        } else if let Some(sta_l) = StaL::parse(tokens, context)? {
             Ok(Some(Scope::Sta(sta_l)))
         } else {
             Ok(None)
         }
     }
 }
+
+impl Evaluate for Scope {
+    fn evaluate(&self, operation_context: &OperationContext) -> ShortResult<OperationIO> {
+        match self {
+            Scope::ScopeBase(scope_base) => scope_base.evaluate(operation_context),
+            Scope::Sta(sta_l) => sta_l.evaluate(operation_context),
+        }
+    }
+}
@@ -1,33 +1,365 @@
-use crate::impl_debug;
-use crate::parse::nodes::blocs::Scope;
-use crate::parse::nodes::GraphDisplay;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::rc::Rc;
+
+use serde::Serialize;
+
+use crate::execute::variables::{Object, VariableType};
+use crate::execute::{Evaluate, OperationContext, OperationIO};
+use crate::parse::context::ParseContext;
+use crate::parse::nodes::functions::FctDec;
+use crate::parse::nodes::vars::VarDec;
+use crate::parse::nodes::visitor::{Visitable, Visitor};
+use crate::parse::nodes::{
+    block_source, skip_separators, FoldConstants, Graph, GraphDisplay, ToSource,
+};
+use crate::skr_errors::{CustomError, ResultOption, ShortResult};
+use crate::tokens::{Token, TokenContainer};
+use crate::{impl_debug, some_token};
 
 // Grammar of this file :
-// <class_dec> ::= kat T_IDENTIFIER <scope>
+// <class_member> ::= <var_dec> | <fct_dec> | <class_dec>
+// <class_body> ::= T_LEFT_E {<class_member>} T_RIGHT_E
+// <class_dec> ::= kat T_IDENTIFIER (<class_body> |)
+
+// -------------------
+// --- ClassMember ---
+// -------------------
+
+/// `ClassMember` represents an element declared in the body of a class : a field with a [VarDec],
+/// a method with a [FctDec] or a nested class with a [ClassDec].
+#[derive(PartialEq, Serialize)]
+pub enum ClassMember {
+    Field(VarDec),
+    Method(FctDec),
+    Class(ClassDec),
+}
+
+impl GraphDisplay for ClassMember {
+    fn graph_display(&self, graph: &mut Graph, id: &mut usize) {
+        match self {
+            ClassMember::Field(var_dec) => var_dec.graph_display(graph, id),
+            ClassMember::Method(fct_dec) => fct_dec.graph_display(graph, id),
+            ClassMember::Class(class_dec) => class_dec.graph_display(graph, id),
+        }
+    }
+}
+
+impl_debug!(ClassMember);
+
+impl ToSource for ClassMember {
+    fn to_source(&self) -> String {
+        match self {
+            ClassMember::Field(var_dec) => var_dec.to_source(),
+            ClassMember::Method(fct_dec) => fct_dec.to_source(),
+            ClassMember::Class(class_dec) => class_dec.to_source(),
+        }
+    }
+}
+
+impl FoldConstants for ClassMember {
+    fn fold_constants(&mut self) {
+        match self {
+            ClassMember::Field(var_dec) => var_dec.fold_constants(),
+            ClassMember::Method(fct_dec) => fct_dec.fold_constants(),
+            ClassMember::Class(class_dec) => class_dec.fold_constants(),
+        }
+    }
+}
+
+impl Visitable for ClassMember {
+    fn accept<V: Visitor>(&self, visitor: &mut V) {
+        visitor.visit_class_member(self);
+        match self {
+            ClassMember::Field(var_dec) => var_dec.accept(visitor),
+            ClassMember::Method(fct_dec) => fct_dec.accept(visitor),
+            ClassMember::Class(class_dec) => class_dec.accept(visitor),
+        }
+    }
+}
+
+impl ClassMember {
+    pub fn parse(
+        tokens: &mut VecDeque<TokenContainer>,
+        context: &mut ParseContext,
+    ) -> ResultOption<Self> {
+        // <class_member> ::= <var_dec> | <fct_dec> | <class_dec>
+        if let Some(var_dec) = VarDec::parse(tokens, context)? {
+            Ok(Some(ClassMember::Field(var_dec)))
+        } else if let Some(fct_dec) = FctDec::parse(tokens, context)? {
+            Ok(Some(ClassMember::Method(fct_dec)))
+        } else if let Some(class_dec) = ClassDec::parse(tokens, context)? {
+            Ok(Some(ClassMember::Class(class_dec)))
+        } else {
+            Ok(None)
+        }
+    }
+}
 
-// TODO - implement Debug for ClassDec
+// ----------------
+// --- ClassDec ---
+// ----------------
 
-/// `ClassDec` represents a class declaration. It is not yet implemented. Will be implemented in a
-/// future pull request.
-#[derive(PartialEq)]
+/// `ClassDec` represents a class declaration. It contains the name of the class and the members
+/// declared in its body. The body is optional : `kat Foo` declares an empty class.
+///
+/// The name of the class is registered in the [ClassManager] as soon as it is parsed, so it can be
+/// used as a type in the body of the class and in the rest of the code. A class declared in the
+/// body of another class is registered with its qualified name, read from the right like the other
+/// `:` chains : `Inner:Outer`. The nested classes cannot be instantiated yet.
+///
+/// # Grammar
+///
+/// `<class_dec> ::= kat T_IDENTIFIER (<class_body> |)`
+///
+/// See also [ClassMember].
+#[derive(PartialEq, Serialize)]
 pub struct ClassDec {
     identifier: String,
-    scope: Box<Scope>,
+    members: Vec<ClassMember>,
 }
 
 impl GraphDisplay for ClassDec {
-    fn graph_display(&self, graph: &mut String, id: &mut usize) {
-        graph.push_str(&format!(
-            "\nsubgraph ClassDec_{}[ClassDec {}]\nend",
-            id, self.identifier
-        ));
+    fn graph_display(&self, graph: &mut Graph, id: &mut usize) {
+        graph.open("ClassDec", &format!("ClassDec {}", self.identifier), *id);
         *id += 1;
+        for member in &self.members {
+            member.graph_display(graph, id);
+        }
+        graph.close();
     }
 }
 
 impl_debug!(ClassDec);
 
-pub fn is_type_def(identifier: &str) -> bool {
-    // TODO: implement this function with complex types
-    matches!(identifier, "int" | "dar" | "ioi" | "skr")
+impl ToSource for ClassDec {
+    fn to_source(&self) -> String {
+        if self.members.is_empty() {
+            format!("kat {}", self.identifier)
+        } else {
+            format!(
+                "kat {} {}",
+                self.identifier,
+                block_source(self.members.iter())
+            )
+        }
+    }
+}
+
+impl FoldConstants for ClassDec {
+    fn fold_constants(&mut self) {
+        for member in &mut self.members {
+            member.fold_constants();
+        }
+    }
+}
+
+impl Visitable for ClassDec {
+    fn accept<V: Visitor>(&self, visitor: &mut V) {
+        visitor.visit_class_dec(self);
+        for member in &self.members {
+            member.accept(visitor);
+        }
+        visitor.leave_class_dec(self);
+    }
+}
+
+impl ClassDec {
+    pub fn new(identifier: String, members: Vec<ClassMember>) -> Self {
+        Self {
+            identifier,
+            members,
+        }
+    }
+
+    pub fn parse(
+        tokens: &mut VecDeque<TokenContainer>,
+        context: &mut ParseContext,
+    ) -> ResultOption<Self> {
+        // <class_dec> ::= kat T_IDENTIFIER (<class_body> |)
+        if let some_token!(Token::KeywordClass) = tokens.front() {
+            tokens.pop_front();
+            match tokens.pop_front() {
+                some_token!(Token::Identifier(identifier)) => {
+                    context.classes.register(&identifier);
+                    context.classes.enter(&identifier);
+                    let members = parse_class_body(tokens, context);
+                    context.classes.leave();
+                    Ok(Some(ClassDec::new(
+                        identifier,
+                        members?.unwrap_or_default(),
+                    )))
+                }
+                token => Err(CustomError::unexpected_token(
+                    "Expected the name of the class",
+                    token.as_ref(),
+                )),
+            }
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+/// A class is a value stored in a variable, like a function. Calling it creates an [Object] with the
+/// fields declared in the class. The fields are evaluated like the body of a function, with only
+/// the global variables visible.
+impl ClassDec {
+    pub fn identifier(&self) -> &str {
+        &self.identifier
+    }
+
+    /// Declare the class in the current scope.
+    pub(crate) fn declare(
+        self: &Rc<Self>,
+        operation_context: &OperationContext,
+    ) -> ShortResult<OperationIO> {
+        let class = VariableType::Class(Rc::clone(self));
+        operation_context
+            .variables
+            .borrow_mut()
+            .declare(&self.identifier, class.clone())?;
+        Ok(class)
+    }
+
+    /// Create an instance of the class. The methods are not part of the instance yet.
+    pub(crate) fn instantiate(
+        &self,
+        operation_context: &OperationContext,
+        args: Vec<OperationIO>,
+        line: usize,
+    ) -> ShortResult<OperationIO> {
+        if !args.is_empty() {
+            return Err(CustomError::InvalidCall(
+                format!(
+                    "The class {} expects no arguments, got {}",
+                    self.identifier,
+                    args.len()
+                ),
+                line,
+            ));
+        }
+        let caller_scopes = operation_context
+            .variables
+            .borrow_mut()
+            .push_frame(operation_context.max_call_depth)?;
+        let fields = self.evaluate_fields(operation_context);
+        operation_context
+            .variables
+            .borrow_mut()
+            .pop_frame(caller_scopes);
+        Ok(VariableType::Object(Object {
+            class: self.identifier.clone(),
+            fields: fields?,
+        }))
+    }
+
+    fn evaluate_fields(
+        &self,
+        operation_context: &OperationContext,
+    ) -> ShortResult<HashMap<String, VariableType>> {
+        let mut fields = HashMap::new();
+        for member in &self.members {
+            if let ClassMember::Field(var_dec) = member {
+                let value = var_dec.evaluate(operation_context)?;
+                fields.insert(var_dec.identifier().to_string(), value);
+            }
+        }
+        Ok(fields)
+    }
+}
+
+fn parse_class_body(
+    tokens: &mut VecDeque<TokenContainer>,
+    context: &mut ParseContext,
+) -> ResultOption<Vec<ClassMember>> {
+    // <class_body> ::= T_LEFT_E {<class_member>} T_RIGHT_E
+    if let some_token!(Token::LeftBrace) = tokens.front() {
+        tokens.pop_front();
+        let mut members = Vec::new();
+        loop {
+            // the members are usually written on different lines
+            skip_separators(tokens);
+            match ClassMember::parse(tokens, context)? {
+                Some(member) => members.push(member),
+                None => break,
+            }
+        }
+
+        if let some_token!(Token::RightBrace) = tokens.front() {
+            tokens.pop_front();
+            Ok(Some(members))
+        } else {
+            Err(CustomError::unexpected_token(
+                "Expected a field, a method or a right curly bracket in the class",
+                tokens.front(),
+            ))
+        }
+    } else {
+        Ok(None)
+    }
+}
+
+// --------------------
+// --- ClassManager ---
+// --------------------
+
+/// Types that exist without being declared : `int`, `dar` (float), `ioi` (bool), `skr` (string)
+/// and `null`.
+const BUILT_IN_TYPES: [&str; 5] = ["int", "dar", "ioi", "skr", "null"];
+
+/// `ClassManager` knows every type that can be used in the code. It is in the [ParseContext] given
+/// to the parse functions, and [ClassDec] registers the name of the classes it parses.
+///
+/// # Lookup
+///
+/// The built-in types and the classes declared with `kat` before this point of the code are stored
+/// in the same set, so [ClassManager::is_type_def] is a single hash lookup. It is called for every
+/// identifier that may start a declaration, so it must stay cheap on large files. An identifier
+/// that is not in the set is not a type.
+///
+/// The classes declared in the body of another class are stored with their qualified name, like
+/// `Inner:Outer`. The classes being parsed are kept in `enclosing` to build this name.
+pub struct ClassManager {
+    types: HashSet<String>,
+    enclosing: Vec<String>,
+}
+
+impl Default for ClassManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ClassManager {
+    pub fn new() -> Self {
+        Self {
+            types: BUILT_IN_TYPES.iter().map(|name| name.to_string()).collect(),
+            enclosing: Vec::new(),
+        }
+    }
+
+    /// Register a class name, so it can be used as a type in the rest of the code. In the body of
+    /// a class, the name is qualified by the enclosing classes.
+    pub fn register(&mut self, identifier: &str) {
+        let qualified = std::iter::once(identifier)
+            .chain(self.enclosing.iter().rev().map(String::as_str))
+            .collect::<Vec<_>>()
+            .join(":");
+        self.types.insert(qualified);
+    }
+
+    /// Start the body of a class, the classes registered until [ClassManager::leave] are nested in
+    /// it.
+    pub fn enter(&mut self, identifier: &str) {
+        self.enclosing.push(identifier.to_string());
+    }
+
+    /// End the body of the last class given to [ClassManager::enter].
+    pub fn leave(&mut self) {
+        self.enclosing.pop();
+    }
+
+    pub fn is_type_def(&self, identifier: &str) -> bool {
+        self.types.contains(identifier)
+    }
 }
@@ -1,19 +1,34 @@
 use std::collections::VecDeque;
+use std::rc::Rc;
 
+use serde::Serialize;
+
+use crate::execute::variables::VariableType;
+use crate::execute::{Evaluate, EvaluateFromInput, OperationContext, OperationIO};
+
+use crate::parse::context::ParseContext;
 use crate::parse::nodes::blocs::ScopeBase;
+use crate::parse::nodes::classes::ClassDec;
 use crate::parse::nodes::functions::FctDec;
 use crate::parse::nodes::id_nodes::{parse_op_in, OpIn, TupleNode};
 use crate::parse::nodes::if_else::Cond;
-use crate::parse::nodes::operations::{NoValueN, TakePriorityLast};
-use crate::parse::nodes::vars::{VarDec, VarMod};
-use crate::parse::nodes::{GraphDisplay, Parsable};
-use crate::skr_errors::{CustomError, ResultOption};
+use crate::parse::nodes::loops::Luba;
+use crate::parse::nodes::matches::Kasu;
+use crate::parse::nodes::operations::{NoValueN, TakePriorityLast, ValueBase};
+use crate::parse::nodes::vars::{resolve_type_path, VarDec, VarMod};
+use crate::parse::nodes::visitor::{Visitable, Visitor};
+use crate::parse::nodes::{
+    block_source, skip_separators, FoldConstants, Graph, GraphDisplay, Parsable, ToSource,
+};
+use crate::skr_errors::{CustomError, NotYetImplementedType, ResultOption, ShortResult};
 use crate::tokens::{SpaceTypes, Token, TokenContainer};
 use crate::{impl_debug, some_token};
 
 // Grammar of this file :
-// <nat_call_in> ::= T_IDENTIFIER ("\n" | <nat_call_in>)
-// <nat_call> ::= T_NAT_CALL <nat_call_in>
+// <nat_call> ::= T_NAT_CALL (
+//     T_IDENTIFIER <tuple>
+//     | T_LEFT_P T_STRING {T_COMMA <exp>} T_RIGHT_P
+//   )
 // <id_use> ::= T_IDENTIFIER (
 //     <tuple> <op_in>
 //     | <op_in> <var_mod>
@@ -27,128 +42,162 @@ use crate::{impl_debug, some_token};
 //   <id_use>
 //   | <var_dec>
 //   | <cond>
+//   | <luba>
+//   | <kasu>
 //   | <scope_base>
 //   | <fct_dec>
+//   | <class_dec>
+//   | <nat_call>
 //   | T_LEFT_P <exp> T_RIGHT_P
 // <exp_tp> ::=
-//   <exp_base>
-//   | <id_use_v>
-// <exp> ::=
-//   <exp_tp>
-//   | <tp_last>
-// <return> ::= ei <exp>
+//   <id_use_v>
+//   | <exp_base>
+// <exp> ::= (
+//     <exp_tp>
+//     | <tp_last>
+//   ) (<ternary> |)
+// <ternary> ::= T_QUESTION <exp> sula <exp>
+// <return> ::= ei (<exp> |)
 // <sta> ::= <return> | <exp>
-// <sta_l> ::= T_LEFT_E {<sta>} T_RIGHT_E
+// <sta_l> ::= T_LEFT_E {<sep>} {<sta> {<sep>}} T_RIGHT_E
+
+/// Whether the identifier at the start of the tokens is a type that starts a declaration. A type is
+/// only used alone when calling a constructor. An unknown qualified type is left to the
+/// declaration, which reports it.
+pub(crate) fn starts_declaration(
+    tokens: &VecDeque<TokenContainer>,
+    context: &ParseContext,
+) -> bool {
+    match resolve_type_path(tokens, context) {
+        Ok(Some((_, length))) => !matches!(tokens.get(length), some_token!(Token::LeftParenthesis)),
+        Ok(None) => false,
+        Err(_) => true,
+    }
+}
 
-// -----------------
-// --- NatCallIn ---
-// -----------------
+// ---------------
+// --- NatCall ---
+// ---------------
 
-/// `NatCallIn` represents an argument of a native call. It contains an identifier and an optional
-/// [NatCallIn] to represent the next argument.
+/// `NatCall` represents a call to a native function of the interpreter, with `skr_app`. It contains
+/// the name of the native function and the [TupleNode] of its arguments.
 ///
-/// The identifier is the name of the variable that will be passed to the native function. The
-/// [NatCallIn] is the next argument to pass to the native function, this is the tail of the list of
-/// arguments.
-#[derive(PartialEq)]
-struct NatCallIn {
-    identifier: String,
-    nat_call_in: Option<Box<NatCallIn>>,
+/// # Grammar
+///
+/// `<nat_call> ::= T_NAT_CALL (T_IDENTIFIER <tuple> | T_LEFT_P T_STRING {T_COMMA <exp>} T_RIGHT_P)`
+///
+/// The name is written after `skr_app` (`skr_app print(x)`) or as the first argument of the tuple
+/// (`skr_app("print", x)`), in which case it must be a string literal.
+#[derive(PartialEq, Serialize)]
+pub struct NatCall {
+    name: String,
+    args: TupleNode,
 }
 
-impl GraphDisplay for NatCallIn {
-    fn graph_display(&self, graph: &mut String, id: &mut usize) {
-        graph.push_str(&format!(
-            "\nsubgraph NatCallIn_{}[NatCallIn {}]",
-            id, self.identifier
-        ));
+impl GraphDisplay for NatCall {
+    fn graph_display(&self, graph: &mut Graph, id: &mut usize) {
+        graph.open("NatCall", &format!("NatCall {}", self.name), *id);
         *id += 1;
-        if let Some(nat_call_in) = &self.nat_call_in {
-            nat_call_in.graph_display(graph, id);
-        }
-        graph.push_str("\nend");
+        self.args.graph_display(graph, id);
+        graph.close();
     }
 }
 
-impl_debug!(NatCallIn);
-
-impl NatCallIn {
-    fn new(identifier: String, nat_call_in: Option<NatCallIn>) -> Self {
-        Self {
-            identifier,
-            nat_call_in: nat_call_in.map(Box::new),
-        }
-    }
+impl_debug!(NatCall);
 
-    pub fn parse(tokens: &mut VecDeque<TokenContainer>) -> ResultOption<NatCallIn> {
-        // <nat_call_in> ::= T_IDENTIFIER ("\n" | <nat_call_in>)
-        if let some_token!(Token::Identifier(_)) = tokens.front() {
-            if let some_token!(Token::Identifier(identifier)) = tokens.pop_front() {
-                if let some_token!(Token::Space(SpaceTypes::NewLine)) = tokens.front() {
-                    tokens.pop_front();
-                    Ok(Some(NatCallIn::new(identifier, None)))
-                } else {
-                    let nat_call_in = NatCallIn::parse(tokens)?;
-                    match nat_call_in {
-                        Some(nat_call_in) => {
-                            Ok(Some(NatCallIn::new(identifier, Some(nat_call_in))))
-                        }
-                        None => Err(CustomError::UnexpectedToken(
-                            "Expected a new line or a nat_call_in".to_string(),
-                        )),
-                    }
-                }
-            } else {
-                Err(CustomError::UnexpectedToken(
-                    "Had an identifier, but couldn't get it".to_string(),
-                ))
-            }
-        } else {
-            Ok(None)
-        }
+impl ToSource for NatCall {
+    fn to_source(&self) -> String {
+        format!("skr_app {}{}", self.name, self.args.to_source())
     }
 }
 
-// ---------------
-// --- NatCall ---
-// ---------------
-
-/// `NatCall` represents a native call. It contains a [NatCallIn] to represent the first argument
-/// and the chain of arguments. The first argument is the name of the native function to call.
-#[derive(PartialEq)]
-struct NatCall {
-    nat_call_in: NatCallIn,
+impl FoldConstants for NatCall {
+    fn fold_constants(&mut self) {
+        self.args.fold_constants();
+    }
 }
 
-impl GraphDisplay for NatCall {
-    fn graph_display(&self, graph: &mut String, id: &mut usize) {
-        graph.push_str(&format!("\nsubgraph NatCall_{}[NatCall]", id));
-        *id += 1;
-        self.nat_call_in.graph_display(graph, id);
-        graph.push_str("\nend");
+impl Visitable for NatCall {
+    fn accept<V: Visitor>(&self, visitor: &mut V) {
+        visitor.visit_nat_call(self);
+        self.args.accept(visitor);
     }
 }
 
-impl_debug!(NatCall);
-
 impl NatCall {
-    fn new(nat_call_in: NatCallIn) -> Self {
-        Self { nat_call_in }
+    pub fn new(name: String, args: TupleNode) -> Self {
+        Self { name, args }
     }
 
-    pub fn parse(tokens: &mut VecDeque<TokenContainer>) -> ResultOption<NatCall> {
-        // <nat_call> ::= T_NAT_CALL <nat_call_in>
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn parse(
+        tokens: &mut VecDeque<TokenContainer>,
+        context: &mut ParseContext,
+    ) -> ResultOption<NatCall> {
+        // <nat_call> ::= T_NAT_CALL (
+        //     T_IDENTIFIER <tuple>
+        //     | T_LEFT_P T_STRING {T_COMMA <exp>} T_RIGHT_P
+        //   )
         if let some_token!(Token::NatCall) = tokens.front() {
             tokens.pop_front();
-            if let Some(nat_call_in) = NatCallIn::parse(tokens)? {
-                Ok(Some(NatCall::new(nat_call_in)))
-            } else {
-                Err(CustomError::UnexpectedToken(
-                    "Expected a nat_call_in".to_string(),
+        } else {
+            return Ok(None);
+        }
+        let name = match tokens.front() {
+            some_token!(Token::Identifier(name)) => {
+                let name = name.clone();
+                tokens.pop_front();
+                name
+            }
+            some_token!(Token::LeftParenthesis) => Self::take_name_argument(tokens)?,
+            token => {
+                return Err(CustomError::unexpected_token(
+                    "Expected the name of the native function",
+                    token,
                 ))
             }
-        } else {
-            Ok(None)
+        };
+        match TupleNode::parse(tokens, context)? {
+            Some(args) => Ok(Some(NatCall::new(name, args))),
+            None => Err(CustomError::unexpected_token(
+                "Expected the arguments of the native function",
+                tokens.front(),
+            )),
+        }
+    }
+
+    /// Remove the string literal at the start of the tuple, and the comma after it, so the rest can
+    /// be parsed as a [TupleNode]. The left parenthesis is kept.
+    fn take_name_argument(tokens: &mut VecDeque<TokenContainer>) -> ShortResult<String> {
+        let name = match tokens.get(1) {
+            some_token!(Token::String(name)) => name.clone(),
+            token => {
+                return Err(CustomError::unexpected_token(
+                    "Expected the name of the native function as a string",
+                    token,
+                ))
+            }
+        };
+        tokens.remove(1);
+        if let some_token!(Token::Comma) = tokens.get(1) {
+            tokens.remove(1);
+        }
+        Ok(name)
+    }
+}
+
+impl Evaluate for NatCall {
+    fn evaluate(&self, operation_context: &OperationContext) -> ShortResult<OperationIO> {
+        let args = self.args.evaluate_all(operation_context)?;
+        match operation_context.natives.get(&self.name) {
+            Some(native) => native(&args),
+            None => Err(CustomError::InvalidCall(
+                format!("Unknown native function {}", self.name),
+                self.args.line(),
+            )),
         }
     }
 }
@@ -159,7 +208,7 @@ impl NatCall {
 
 /// `InsideIdUse` represents the possible values that can be inside an [IdUse]. It can be a
 /// [TupleNode], a [VarMod], or nothing.
-#[derive(PartialEq)]
+#[derive(PartialEq, Serialize)]
 pub(crate) enum InsideIdUse {
     Tuple(TupleNode),
     VarMod(VarMod),
@@ -174,7 +223,7 @@ pub(crate) enum InsideIdUse {
 /// `<id_use> ::= T_IDENTIFIER (<tuple> <op_in> | <op_in> <var_mod> | <op_in>)`
 ///
 /// See also [TupleNode], [OpIn] and [VarMod].
-#[derive(PartialEq)]
+#[derive(PartialEq, Serialize)]
 pub struct IdUse {
     identifier: String,
     op_in: OpIn,
@@ -182,11 +231,8 @@ pub struct IdUse {
 }
 
 impl GraphDisplay for IdUse {
-    fn graph_display(&self, graph: &mut String, id: &mut usize) {
-        graph.push_str(&format!(
-            "\nsubgraph IdUse_{}[IdUse {}]",
-            id, self.identifier
-        ));
+    fn graph_display(&self, graph: &mut Graph, id: &mut usize) {
+        graph.open("IdUse", &format!("IdUse {}", self.identifier), *id);
         *id += 1;
         self.op_in.graph_display(graph, id);
         match &*self.inside_id_use {
@@ -194,12 +240,56 @@ impl GraphDisplay for IdUse {
             InsideIdUse::VarMod(var_mod) => var_mod.graph_display(graph, id),
             InsideIdUse::Empty => {}
         }
-        graph.push_str("\nend");
+        graph.close();
     }
 }
 
 impl_debug!(IdUse);
 
+impl ToSource for IdUse {
+    fn to_source(&self) -> String {
+        let op_in = self.op_in.to_source();
+        match &*self.inside_id_use {
+            InsideIdUse::Tuple(tuple) => {
+                format!("{}{}{}", self.identifier, tuple.to_source(), op_in)
+            }
+            InsideIdUse::VarMod(var_mod) => {
+                format!(
+                    "{}{}{}{}",
+                    self.identifier,
+                    op_in,
+                    var_mod.separator(),
+                    var_mod.to_source()
+                )
+            }
+            InsideIdUse::Empty => format!("{}{}", self.identifier, op_in),
+        }
+    }
+}
+
+impl FoldConstants for IdUse {
+    fn fold_constants(&mut self) {
+        self.op_in.fold_constants();
+        match &mut *self.inside_id_use {
+            InsideIdUse::Tuple(tuple) => tuple.fold_constants(),
+            InsideIdUse::VarMod(var_mod) => var_mod.fold_constants(),
+            InsideIdUse::Empty => {}
+        }
+    }
+}
+
+impl Visitable for IdUse {
+    fn accept<V: Visitor>(&self, visitor: &mut V) {
+        visitor.visit_id_use(self);
+        self.op_in.accept(visitor);
+        match &*self.inside_id_use {
+            InsideIdUse::Tuple(tuple) => tuple.accept(visitor),
+            InsideIdUse::VarMod(var_mod) => var_mod.accept(visitor),
+            InsideIdUse::Empty => {}
+        }
+    }
+}
+
 impl IdUse {
     pub(crate) fn new(identifier: String, op_in: OpIn, inside_id_use: InsideIdUse) -> Self {
         Self {
@@ -209,24 +299,41 @@ impl IdUse {
         }
     }
 
-    pub fn parse(tokens: &mut VecDeque<TokenContainer>) -> ResultOption<IdUse> {
+    /// The variable changed by this node and its change, when it changes a variable and not a
+    /// field.
+    pub(crate) fn modification(&self) -> Option<(&str, &VarMod)> {
+        match &*self.inside_id_use {
+            InsideIdUse::VarMod(var_mod) if self.op_in == OpIn::Empty => {
+                Some((&self.identifier, var_mod))
+            }
+            _ => None,
+        }
+    }
+
+    pub fn parse(
+        tokens: &mut VecDeque<TokenContainer>,
+        context: &mut ParseContext,
+    ) -> ResultOption<IdUse> {
         // <id_use> ::= T_IDENTIFIER (
         //     <tuple> <op_in>
         //     | <op_in> <var_mod>
         //     | <op_in>
         //   )
         if let some_token!(Token::Identifier(_)) = tokens.front() {
+            if starts_declaration(tokens, context) {
+                return Ok(None);
+            }
             if let some_token!(Token::Identifier(identifier)) = tokens.pop_front() {
-                if let Some(tuple) = TupleNode::parse(tokens)? {
-                    let op_in = parse_op_in(tokens)?;
+                if let Some(tuple) = TupleNode::parse(tokens, context)? {
+                    let op_in = parse_op_in(tokens, context)?;
                     Ok(Some(IdUse::new(
                         identifier,
                         op_in,
                         InsideIdUse::Tuple(tuple),
                     )))
                 } else {
-                    let op_in = parse_op_in(tokens)?;
-                    if let Some(var_mod) = VarMod::parse(tokens)? {
+                    let op_in = parse_op_in(tokens, context)?;
+                    if let Some(var_mod) = VarMod::parse(tokens, context)? {
                         Ok(Some(IdUse::new(
                             identifier,
                             op_in,
@@ -237,8 +344,9 @@ impl IdUse {
                     }
                 }
             } else {
-                Err(CustomError::UnexpectedToken(
-                    "Expected an identifier".to_string(),
+                Err(CustomError::unexpected_token(
+                    "Expected an identifier",
+                    tokens.front(),
                 ))
             }
         } else {
@@ -247,13 +355,81 @@ impl IdUse {
     }
 }
 
+impl Evaluate for IdUse {
+    fn evaluate(&self, operation_context: &OperationContext) -> ShortResult<OperationIO> {
+        match &*self.inside_id_use {
+            InsideIdUse::Empty => get_in(operation_context, &self.identifier, &self.op_in),
+            _ if self.op_in != OpIn::Empty => Err(not_yet_implemented("the fields")),
+            InsideIdUse::Tuple(tuple) => call(operation_context, &self.identifier, tuple),
+            InsideIdUse::VarMod(var_mod) => var_mod.apply(operation_context, &self.identifier),
+        }
+    }
+}
+
+/// Get the value of `identifier` in the chain `identifier:op_in`. The chain is read from the right :
+/// in `value:inner:o`, `o` is a variable, `inner` a field of `o` and `value` a field of `inner`.
+/// With an index, `arr:0` is the first element of the variable `arr`.
+fn get_in(
+    operation_context: &OperationContext,
+    identifier: &str,
+    op_in: &OpIn,
+) -> ShortResult<OperationIO> {
+    match op_in {
+        OpIn::Empty => operation_context.variables.borrow().get(identifier),
+        OpIn::Index(index) => operation_context
+            .variables
+            .borrow()
+            .get(identifier)?
+            .index(*index),
+        OpIn::IdGet(id_get) if id_get.tuple.is_none() => {
+            get_in(operation_context, &id_get.identifier, &id_get.op_in)?.field(identifier)
+        }
+        OpIn::IdGet(_) => Err(not_yet_implemented("the methods")),
+        OpIn::Field(field_access) => {
+            let value = operation_context.variables.borrow().get(identifier)?;
+            field_access.get_from(value)
+        }
+        OpIn::CGet(_) => Err(not_yet_implemented("the static fields")),
+    }
+}
+
+/// Call the function stored in the variable `identifier` with the values of the `tuple`.
+fn call(
+    operation_context: &OperationContext,
+    identifier: &str,
+    tuple: &TupleNode,
+) -> ShortResult<OperationIO> {
+    let value = operation_context.variables.borrow().get(identifier);
+    let function = match value {
+        Ok(VariableType::Function(function)) => function,
+        Ok(VariableType::Class(class)) => {
+            let args = tuple.evaluate_all(operation_context)?;
+            return class.instantiate(operation_context, args, tuple.line());
+        }
+        Ok(value) => {
+            return Err(CustomError::InvalidCall(
+                format!("{} is a {}, not a function", identifier, value.type_name()),
+                tuple.line(),
+            ))
+        }
+        Err(_) => {
+            return Err(CustomError::InvalidCall(
+                format!("Unknown function {}", identifier),
+                tuple.line(),
+            ))
+        }
+    };
+    let args = tuple.evaluate_all(operation_context)?;
+    function.call(operation_context, args, tuple.line())
+}
+
 // --------------
 // --- IdUseV ---
 // --------------
 
 /// `InsideIdUseV` represents the possible values that can be inside an [IdUseV]. It can be a
 /// [TupleNode] (with an optional [NoValue]), a [VarMod], a [NoValue], or nothing.
-#[derive(PartialEq)]
+#[derive(PartialEq, Serialize)]
 pub(crate) enum InsideIdUseV {
     Tuple {
         tuple: TupleNode,
@@ -281,7 +457,7 @@ pub(crate) enum InsideIdUseV {
 ///
 /// See the test `test_simple_exp_id_use_v` in `src/tests/parse_tests/expressions_tests.rs` for an
 /// example of parsing.
-#[derive(PartialEq)]
+#[derive(PartialEq, Serialize)]
 pub struct IdUseV {
     identifier: String,
     op_in: OpIn,
@@ -289,11 +465,8 @@ pub struct IdUseV {
 }
 
 impl GraphDisplay for IdUseV {
-    fn graph_display(&self, graph: &mut String, id: &mut usize) {
-        graph.push_str(&format!(
-            "\nsubgraph IdUseV_{}[IdUseV {}]",
-            id, self.identifier
-        ));
+    fn graph_display(&self, graph: &mut Graph, id: &mut usize) {
+        graph.open("IdUseV", &format!("IdUseV {}", self.identifier), *id);
         *id += 1;
         self.op_in.graph_display(graph, id);
         match &*self.inside_id_use_v {
@@ -307,12 +480,75 @@ impl GraphDisplay for IdUseV {
             InsideIdUseV::VarMod(var_mod) => var_mod.graph_display(graph, id),
             InsideIdUseV::Empty => {}
         }
-        graph.push_str("\nend");
+        graph.close();
     }
 }
 
 impl_debug!(IdUseV);
 
+impl ToSource for IdUseV {
+    fn to_source(&self) -> String {
+        let op_in = self.op_in.to_source();
+        match &*self.inside_id_use_v {
+            InsideIdUseV::Tuple { tuple, no_value } => {
+                let call = format!("{}{}{}", self.identifier, tuple.to_source(), op_in);
+                match no_value {
+                    Some(no_value) => format!("{} {}", call, no_value.to_source()),
+                    None => call,
+                }
+            }
+            InsideIdUseV::NoValue(no_value) => {
+                format!("{}{} {}", self.identifier, op_in, no_value.to_source())
+            }
+            InsideIdUseV::VarMod(var_mod) => {
+                format!(
+                    "{}{}{}{}",
+                    self.identifier,
+                    op_in,
+                    var_mod.separator(),
+                    var_mod.to_source()
+                )
+            }
+            InsideIdUseV::Empty => format!("{}{}", self.identifier, op_in),
+        }
+    }
+}
+
+impl FoldConstants for IdUseV {
+    fn fold_constants(&mut self) {
+        self.op_in.fold_constants();
+        match &mut *self.inside_id_use_v {
+            InsideIdUseV::Tuple { tuple, no_value } => {
+                tuple.fold_constants();
+                if let Some(no_value) = no_value {
+                    no_value.fold_constants();
+                }
+            }
+            InsideIdUseV::NoValue(no_value) => no_value.fold_constants(),
+            InsideIdUseV::VarMod(var_mod) => var_mod.fold_constants(),
+            InsideIdUseV::Empty => {}
+        }
+    }
+}
+
+impl Visitable for IdUseV {
+    fn accept<V: Visitor>(&self, visitor: &mut V) {
+        visitor.visit_id_use_v(self);
+        self.op_in.accept(visitor);
+        match &*self.inside_id_use_v {
+            InsideIdUseV::Tuple { tuple, no_value } => {
+                tuple.accept(visitor);
+                if let Some(no_value) = no_value {
+                    no_value.accept(visitor);
+                }
+            }
+            InsideIdUseV::NoValue(no_value) => no_value.accept(visitor),
+            InsideIdUseV::VarMod(var_mod) => var_mod.accept(visitor),
+            InsideIdUseV::Empty => {}
+        }
+    }
+}
+
 impl IdUseV {
     pub(crate) fn new(identifier: String, op_in: OpIn, inside_id_use_v: InsideIdUseV) -> Self {
         Self {
@@ -322,32 +558,54 @@ impl IdUseV {
         }
     }
 
-    pub fn parse(tokens: &mut VecDeque<TokenContainer>) -> ResultOption<IdUseV> {
+    /// The variable changed by this node and its change, when it changes a variable and not a
+    /// field.
+    pub(crate) fn modification(&self) -> Option<(&str, &VarMod)> {
+        match &*self.inside_id_use_v {
+            InsideIdUseV::VarMod(var_mod) if self.op_in == OpIn::Empty => {
+                Some((&self.identifier, var_mod))
+            }
+            _ => None,
+        }
+    }
+
+    /// Whether the node changes a variable or a field.
+    pub(crate) fn is_modification(&self) -> bool {
+        matches!(*self.inside_id_use_v, InsideIdUseV::VarMod(_))
+    }
+
+    pub fn parse(
+        tokens: &mut VecDeque<TokenContainer>,
+        context: &mut ParseContext,
+    ) -> ResultOption<IdUseV> {
         // <id_use_v> ::= T_IDENTIFIER (
         //     <tuple> <op_in> (<no_value> |)
         //     | <op_in> (<no_value> | <var_mod> |)
         //   )
         if let some_token!(Token::Identifier(_)) = tokens.front() {
+            if starts_declaration(tokens, context) {
+                return Ok(None);
+            }
             if let some_token!(Token::Identifier(identifier)) = tokens.pop_front() {
-                if let Some(tuple) = TupleNode::parse(tokens)? {
-                    let op_in = parse_op_in(tokens)?;
+                if let Some(tuple) = TupleNode::parse(tokens, context)? {
+                    let op_in = parse_op_in(tokens, context)?;
                     Ok(Some(IdUseV::new(
                         identifier,
                         op_in,
                         InsideIdUseV::Tuple {
                             tuple,
-                            no_value: NoValueN::parse(tokens)?,
+                            no_value: NoValueN::parse(tokens, context)?,
                         },
                     )))
                 } else {
-                    let op_in = parse_op_in(tokens)?;
-                    if let Some(no_value) = NoValueN::parse(tokens)? {
+                    let op_in = parse_op_in(tokens, context)?;
+                    if let Some(no_value) = NoValueN::parse(tokens, context)? {
                         Ok(Some(IdUseV::new(
                             identifier,
                             op_in,
                             InsideIdUseV::NoValue(no_value),
                         )))
-                    } else if let Some(var_mod) = VarMod::parse(tokens)? {
+                    } else if let Some(var_mod) = VarMod::parse(tokens, context)? {
                         Ok(Some(IdUseV::new(
                             identifier,
                             op_in,
@@ -358,8 +616,9 @@ impl IdUseV {
                     }
                 }
             } else {
-                Err(CustomError::UnexpectedToken(
-                    "Expected an identifier".to_string(),
+                Err(CustomError::unexpected_token(
+                    "Expected an identifier",
+                    tokens.front(),
                 ))
             }
         } else {
@@ -368,78 +627,179 @@ impl IdUseV {
     }
 }
 
+impl Evaluate for IdUseV {
+    fn evaluate(&self, operation_context: &OperationContext) -> ShortResult<OperationIO> {
+        match &*self.inside_id_use_v {
+            InsideIdUseV::NoValue(no_value) => {
+                let value = get_in(operation_context, &self.identifier, &self.op_in)?;
+                no_value.evaluate_from_input(operation_context, value)
+            }
+            InsideIdUseV::Empty => get_in(operation_context, &self.identifier, &self.op_in),
+            _ if self.op_in != OpIn::Empty => Err(not_yet_implemented("the fields")),
+            InsideIdUseV::Tuple { tuple, no_value } => {
+                let value = call(operation_context, &self.identifier, tuple)?;
+                match no_value {
+                    Some(no_value) => no_value.evaluate_from_input(operation_context, value),
+                    None => Ok(value),
+                }
+            }
+            InsideIdUseV::VarMod(var_mod) => var_mod.apply(operation_context, &self.identifier),
+        }
+    }
+}
+
 // ---------------
 // --- ExpBase ---
 // ---------------
 
 /// `ExpBase` represents any expression node that has the priority over many grammar rules with high
 /// priority, like operations.
-#[derive(PartialEq)]
+#[derive(PartialEq, Serialize)]
 pub enum ExpBase {
     IdUse(Box<IdUse>),
     VarDec(Box<VarDec>),
     Cond(Box<Cond>),
+    Luba(Box<Luba>),
+    Kasu(Box<Kasu>),
     ScopeBase(Box<ScopeBase>),
-    FctDec(Box<FctDec>),
+    FctDec(Rc<FctDec>),
+    ClassDec(Rc<ClassDec>),
+    NatCall(Box<NatCall>),
     LeftP(Box<Exp>),
     RightP(Box<Exp>),
 }
 
 impl GraphDisplay for ExpBase {
-    fn graph_display(&self, graph: &mut String, id: &mut usize) {
-        graph.push_str(&format!("\nsubgraph ExpBase_{}[ExpBase]", id));
+    fn graph_display(&self, graph: &mut Graph, id: &mut usize) {
+        graph.open("ExpBase", "ExpBase", *id);
         *id += 1;
         match self {
             ExpBase::IdUse(id_use) => id_use.graph_display(graph, id),
             ExpBase::VarDec(var_dec) => var_dec.graph_display(graph, id),
             ExpBase::Cond(cond) => cond.graph_display(graph, id),
+            ExpBase::Luba(luba) => luba.graph_display(graph, id),
+            ExpBase::Kasu(kasu) => kasu.graph_display(graph, id),
             ExpBase::ScopeBase(scope_base) => scope_base.graph_display(graph, id),
             ExpBase::FctDec(fct_dec) => fct_dec.graph_display(graph, id),
+            ExpBase::ClassDec(class_dec) => class_dec.graph_display(graph, id),
+            ExpBase::NatCall(nat_call) => nat_call.graph_display(graph, id),
             ExpBase::LeftP(exp) => exp.graph_display(graph, id),
             ExpBase::RightP(exp) => exp.graph_display(graph, id),
         }
-        graph.push_str("\nend");
+        graph.close();
     }
 }
 
 impl_debug!(ExpBase);
 
+impl ToSource for ExpBase {
+    fn to_source(&self) -> String {
+        match self {
+            ExpBase::IdUse(id_use) => id_use.to_source(),
+            ExpBase::VarDec(var_dec) => var_dec.to_source(),
+            ExpBase::Cond(cond) => cond.to_source(),
+            ExpBase::Luba(luba) => luba.to_source(),
+            ExpBase::Kasu(kasu) => kasu.to_source(),
+            ExpBase::ScopeBase(scope_base) => scope_base.to_source(),
+            ExpBase::FctDec(fct_dec) => fct_dec.to_source(),
+            ExpBase::ClassDec(class_dec) => class_dec.to_source(),
+            ExpBase::NatCall(nat_call) => nat_call.to_source(),
+            ExpBase::LeftP(exp) | ExpBase::RightP(exp) => format!("({})", exp.to_source()),
+        }
+    }
+}
+
+impl FoldConstants for ExpBase {
+    fn fold_constants(&mut self) {
+        match self {
+            ExpBase::IdUse(id_use) => id_use.fold_constants(),
+            ExpBase::VarDec(var_dec) => var_dec.fold_constants(),
+            ExpBase::Cond(cond) => cond.fold_constants(),
+            ExpBase::Luba(luba) => luba.fold_constants(),
+            ExpBase::Kasu(kasu) => kasu.fold_constants(),
+            ExpBase::ScopeBase(scope_base) => scope_base.fold_constants(),
+            // the declarations are only shared once they are evaluated
+            ExpBase::FctDec(fct_dec) => {
+                if let Some(fct_dec) = Rc::get_mut(fct_dec) {
+                    fct_dec.fold_constants();
+                }
+            }
+            ExpBase::ClassDec(class_dec) => {
+                if let Some(class_dec) = Rc::get_mut(class_dec) {
+                    class_dec.fold_constants();
+                }
+            }
+            ExpBase::NatCall(nat_call) => nat_call.fold_constants(),
+            ExpBase::LeftP(exp) | ExpBase::RightP(exp) => exp.fold_constants(),
+        }
+    }
+}
+
+impl Visitable for ExpBase {
+    fn accept<V: Visitor>(&self, visitor: &mut V) {
+        visitor.visit_exp_base(self);
+        match self {
+            ExpBase::IdUse(id_use) => id_use.accept(visitor),
+            ExpBase::VarDec(var_dec) => var_dec.accept(visitor),
+            ExpBase::Cond(cond) => cond.accept(visitor),
+            ExpBase::Luba(luba) => luba.accept(visitor),
+            ExpBase::Kasu(kasu) => kasu.accept(visitor),
+            ExpBase::ScopeBase(scope_base) => scope_base.accept(visitor),
+            ExpBase::FctDec(fct_dec) => fct_dec.accept(visitor),
+            ExpBase::ClassDec(class_dec) => class_dec.accept(visitor),
+            ExpBase::NatCall(nat_call) => nat_call.accept(visitor),
+            ExpBase::LeftP(exp) | ExpBase::RightP(exp) => exp.accept(visitor),
+        }
+    }
+}
+
 impl ExpBase {
     fn new(id_use: IdUse) -> Self {
         Self::IdUse(Box::new(id_use))
     }
 
-    pub fn parse(tokens: &mut VecDeque<TokenContainer>) -> ResultOption<ExpBase> {
+    pub fn parse(
+        tokens: &mut VecDeque<TokenContainer>,
+        context: &mut ParseContext,
+    ) -> ResultOption<ExpBase> {
         // <exp_base> ::=
         //   <id_use>
         //   | <var_dec>
         //   | <cond>
+        //   | <luba>
+        //   | <kasu>
         //   | <scope_base>
         //   | <fct_dec>
+        //   | <class_dec>
+        //   | <nat_call>
         //   | T_LEFT_P <exp> T_RIGHT_P
-        if let Some(id_use) = IdUse::parse(tokens)? {
+        if let Some(id_use) = IdUse::parse(tokens, context)? {
             Ok(Some(ExpBase::new(id_use)))
-        } else if let Some(var_dec) = VarDec::parse(tokens)? {
+        } else if let Some(var_dec) = VarDec::parse(tokens, context)? {
             Ok(Some(ExpBase::VarDec(Box::new(var_dec))))
-        } else if let Some(cond) = Cond::parse(tokens)? {
+        } else if let Some(cond) = Cond::parse(tokens, context)? {
             Ok(Some(ExpBase::Cond(Box::new(cond))))
-        } else if let Some(scope_base) = ScopeBase::parse(tokens)? {
+        } else if let Some(luba) = Luba::parse(tokens, context)? {
+            Ok(Some(ExpBase::Luba(Box::new(luba))))
+        } else if let Some(kasu) = Kasu::parse(tokens, context)? {
+            Ok(Some(ExpBase::Kasu(Box::new(kasu))))
+        } else if let Some(scope_base) = ScopeBase::parse(tokens, context)? {
             Ok(Some(ExpBase::ScopeBase(Box::new(scope_base))))
-        } else if let Some(fct_dec) = FctDec::parse(tokens)? {
-            Ok(Some(ExpBase::FctDec(Box::new(fct_dec))))
+        } else if let Some(fct_dec) = FctDec::parse(tokens, context)? {
+            Ok(Some(ExpBase::FctDec(Rc::new(fct_dec))))
+        } else if let Some(class_dec) = ClassDec::parse(tokens, context)? {
+            Ok(Some(ExpBase::ClassDec(Rc::new(class_dec))))
+        } else if let Some(nat_call) = NatCall::parse(tokens, context)? {
+            Ok(Some(ExpBase::NatCall(Box::new(nat_call))))
         } else if let some_token!(Token::LeftParenthesis) = tokens.front() {
-            tokens.pop_front();
-            if let Some(exp) = Exp::parse(tokens)? {
-                if let some_token!(Token::RightParenthesis) = tokens.pop_front() {
-                    Ok(Some(ExpBase::RightP(Box::new(exp))))
-                } else {
-                    Err(CustomError::UnexpectedToken(
-                        "Expected a right parenthesis".to_string(),
-                    ))
-                }
+            let opening = tokens.pop_front().expect("the front token exists");
+            if let Some(exp) = Exp::parse(tokens, context)? {
+                parse_right_parenthesis(tokens, &opening)?;
+                Ok(Some(ExpBase::RightP(Box::new(exp))))
             } else {
-                Err(CustomError::UnexpectedToken(
-                    "Expected an expression".to_string(),
+                Err(CustomError::unexpected_token(
+                    "Expected an expression",
+                    tokens.front(),
                 ))
             }
         } else {
@@ -448,164 +808,588 @@ impl ExpBase {
     }
 }
 
+impl Evaluate for ExpBase {
+    fn evaluate(&self, operation_context: &OperationContext) -> ShortResult<OperationIO> {
+        match self {
+            ExpBase::VarDec(var_dec) => var_dec.evaluate(operation_context),
+            ExpBase::Luba(luba) => luba.evaluate(operation_context),
+            ExpBase::Kasu(kasu) => kasu.evaluate(operation_context),
+            ExpBase::ScopeBase(scope_base) => scope_base.evaluate(operation_context),
+            ExpBase::LeftP(exp) | ExpBase::RightP(exp) => exp.evaluate(operation_context),
+            ExpBase::IdUse(id_use) => id_use.evaluate(operation_context),
+            ExpBase::Cond(cond) => cond.evaluate(operation_context),
+            ExpBase::FctDec(fct_dec) => fct_dec.declare(operation_context),
+            ExpBase::ClassDec(class_dec) => class_dec.declare(operation_context),
+            ExpBase::NatCall(nat_call) => nat_call.evaluate(operation_context),
+        }
+    }
+}
+
 // -------------
 // --- ExpTp ---
 // -------------
 
 /// `ExpTp` represents the second level of high priority expressions. This contains [ExpBase] and
 /// [IdUseV]. For now, it is only used to represent the [IdUseV].
-#[derive(PartialEq)]
+#[derive(PartialEq, Serialize)]
 pub enum ExpTp {
     ExpBase(ExpBase),
     IdUseV(IdUseV),
 }
 
 impl GraphDisplay for ExpTp {
-    fn graph_display(&self, graph: &mut String, id: &mut usize) {
-        graph.push_str(&format!("\nsubgraph ExpTp_{}[ExpTp]", id));
+    fn graph_display(&self, graph: &mut Graph, id: &mut usize) {
+        graph.open("ExpTp", "ExpTp", *id);
         *id += 1;
         match self {
             ExpTp::ExpBase(exp_base) => exp_base.graph_display(graph, id),
             ExpTp::IdUseV(id_use_v) => id_use_v.graph_display(graph, id),
         }
-        graph.push_str("\nend");
+        graph.close();
     }
 }
 
 impl_debug!(ExpTp);
 
+impl ToSource for ExpTp {
+    fn to_source(&self) -> String {
+        match self {
+            ExpTp::ExpBase(exp_base) => exp_base.to_source(),
+            ExpTp::IdUseV(id_use_v) => id_use_v.to_source(),
+        }
+    }
+}
+
+impl FoldConstants for ExpTp {
+    fn fold_constants(&mut self) {
+        match self {
+            ExpTp::ExpBase(exp_base) => exp_base.fold_constants(),
+            ExpTp::IdUseV(id_use_v) => id_use_v.fold_constants(),
+        }
+    }
+}
+
+impl Visitable for ExpTp {
+    fn accept<V: Visitor>(&self, visitor: &mut V) {
+        visitor.visit_exp_tp(self);
+        match self {
+            ExpTp::ExpBase(exp_base) => exp_base.accept(visitor),
+            ExpTp::IdUseV(id_use_v) => id_use_v.accept(visitor),
+        }
+    }
+}
+
 impl ExpTp {
     fn new(exp_base: ExpBase) -> Self {
         Self::ExpBase(exp_base)
     }
 
-    pub fn parse(tokens: &mut VecDeque<TokenContainer>) -> ResultOption<ExpTp> {
+    pub fn parse(
+        tokens: &mut VecDeque<TokenContainer>,
+        context: &mut ParseContext,
+    ) -> ResultOption<ExpTp> {
         // <exp_tp> ::=
-        //   <exp_base>
-        //   | <id_use_v>
-        if let Some(exp_base) = ExpBase::parse(tokens)? {
-            Ok(Some(ExpTp::new(exp_base)))
-        } else if let Some(id_use_v) = IdUseV::parse(tokens)? {
+        //   <id_use_v>
+        //   | <exp_base>
+        // an identifier followed by an operation is an IdUseV, so it is tried first
+        if let Some(id_use_v) = IdUseV::parse(tokens, context)? {
             Ok(Some(ExpTp::IdUseV(id_use_v)))
+        } else if let Some(exp_base) = ExpBase::parse(tokens, context)? {
+            Ok(Some(ExpTp::new(exp_base)))
         } else {
             Ok(None)
         }
     }
 }
 
+impl Evaluate for ExpTp {
+    fn evaluate(&self, operation_context: &OperationContext) -> ShortResult<OperationIO> {
+        match self {
+            ExpTp::ExpBase(exp_base) => exp_base.evaluate(operation_context),
+            ExpTp::IdUseV(id_use_v) => id_use_v.evaluate(operation_context),
+        }
+    }
+}
+
 // -----------
 // --- Exp ---
 // -----------
 
 /// `Exp` represents any expression with low priority. It might be between parentheses to work. It
 /// contains [ExpTp] or [TPLast]. [TPLast] represents any chain of operations, and [ExpTp] a high
-/// priority expression.
-#[derive(PartialEq)]
+/// priority expression. Any of them can be the condition of a [Ternary].
+#[derive(PartialEq, Serialize)]
 pub enum Exp {
     ExpTp(ExpTp),
     TPLast(TakePriorityLast),
+    Ternary(Box<Ternary>),
 }
 
 impl GraphDisplay for Exp {
-    fn graph_display(&self, graph: &mut String, id: &mut usize) {
-        graph.push_str(&format!("\nsubgraph Exp_{}[Exp]", id));
+    fn graph_display(&self, graph: &mut Graph, id: &mut usize) {
+        graph.open("Exp", "Exp", *id);
         *id += 1;
         match self {
             Exp::ExpTp(exp_tp) => exp_tp.graph_display(graph, id),
             Exp::TPLast(tp_last) => tp_last.graph_display(graph, id),
+            Exp::Ternary(ternary) => ternary.graph_display(graph, id),
         }
-        graph.push_str("\nend");
+        graph.close();
     }
 }
 
 impl_debug!(Exp);
 
+impl ToSource for Exp {
+    fn to_source(&self) -> String {
+        match self {
+            Exp::ExpTp(exp_tp) => exp_tp.to_source(),
+            Exp::TPLast(tp_last) => tp_last.to_source(),
+            Exp::Ternary(ternary) => ternary.to_source(),
+        }
+    }
+}
+
+impl FoldConstants for Exp {
+    fn fold_constants(&mut self) {
+        match self {
+            Exp::ExpTp(exp_tp) => exp_tp.fold_constants(),
+            Exp::TPLast(tp_last) => tp_last.fold_constants(),
+            Exp::Ternary(ternary) => ternary.fold_constants(),
+        }
+    }
+}
+
+impl Visitable for Exp {
+    fn accept<V: Visitor>(&self, visitor: &mut V) {
+        visitor.visit_exp(self);
+        match self {
+            Exp::ExpTp(exp_tp) => exp_tp.accept(visitor),
+            Exp::TPLast(tp_last) => tp_last.accept(visitor),
+            Exp::Ternary(ternary) => ternary.accept(visitor),
+        }
+    }
+}
+
 impl Exp {
-    pub fn parse(tokens: &mut VecDeque<TokenContainer>) -> ResultOption<Exp> {
-        // <exp> ::=
-        //   <exp_tp>
-        //   | <tp_last>
-        if let Some(exp_tp) = ExpTp::parse(tokens)? {
-            Ok(Some(Exp::ExpTp(exp_tp)))
-        } else if let Some(tp_last) = TakePriorityLast::parse(tokens)? {
-            Ok(Some(Exp::TPLast(tp_last)))
+    pub fn parse(
+        tokens: &mut VecDeque<TokenContainer>,
+        context: &mut ParseContext,
+    ) -> ResultOption<Exp> {
+        // <exp> ::= (
+        //     <exp_tp>
+        //     | <tp_last>
+        //   ) (<ternary> |)
+        let mut context = context.nest(tokens.front())?;
+        Self::parse_nested(tokens, &mut context)
+    }
+
+    fn parse_nested(
+        tokens: &mut VecDeque<TokenContainer>,
+        context: &mut ParseContext,
+    ) -> ResultOption<Exp> {
+        let exp = if let some_token!(Token::LeftParenthesis) = tokens.front() {
+            // The parentheses can be followed by operations, only [TakePriorityLast] can parse them
+            TakePriorityLast::parse(tokens, context)?.map(Exp::TPLast)
+        } else if let Some(exp_tp) = ExpTp::parse(tokens, context)? {
+            Some(Exp::ExpTp(exp_tp))
         } else {
-            Ok(None)
+            TakePriorityLast::parse(tokens, context)?.map(Exp::TPLast)
+        };
+        match exp {
+            Some(condition) => Ternary::parse(condition, tokens, context).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Whether the expression declares a variable, a function or a class.
+    pub fn is_declaration(&self) -> bool {
+        matches!(
+            self,
+            Exp::ExpTp(ExpTp::ExpBase(
+                ExpBase::VarDec(_) | ExpBase::FctDec(_) | ExpBase::ClassDec(_)
+            ))
+        )
+    }
+
+    /// The value of the expression when it is a single literal, like `1` or `"text"`. The constant
+    /// operations are folded into a literal by [FoldConstants].
+    pub(crate) fn literal(&self) -> Option<VariableType> {
+        match self {
+            Exp::TPLast(tp_last) => tp_last.literal().map(ValueBase::value),
+            _ => None,
+        }
+    }
+}
+
+impl Evaluate for Exp {
+    fn evaluate(&self, operation_context: &OperationContext) -> ShortResult<OperationIO> {
+        match self {
+            Exp::ExpTp(exp_tp) => exp_tp.evaluate(operation_context),
+            Exp::TPLast(tp_last) => tp_last.evaluate(operation_context),
+            Exp::Ternary(ternary) => ternary.evaluate(operation_context),
+        }
+    }
+}
+
+// ---------------
+// --- Ternary ---
+// ---------------
+
+/// `Ternary` is the inline form of an `ij` / `sula` : `condition ? exp_true sula exp_false`. The
+/// colon is not used as a separator because it is already the [OpIn] operator.
+///
+/// # Grammar
+///
+/// `<ternary> ::= T_QUESTION <exp> sula <exp>`
+///
+/// The expression after `sula` can be another ternary : `a ? 1 sula b ? 2 sula 3`.
+#[derive(PartialEq, Serialize)]
+pub struct Ternary {
+    condition: Exp,
+    exp_true: Exp,
+    exp_false: Exp,
+}
+
+impl GraphDisplay for Ternary {
+    fn graph_display(&self, graph: &mut Graph, id: &mut usize) {
+        graph.open("Ternary", "Ternary", *id);
+        *id += 1;
+        self.condition.graph_display(graph, id);
+        self.exp_true.graph_display(graph, id);
+        self.exp_false.graph_display(graph, id);
+        graph.close();
+    }
+}
+
+impl_debug!(Ternary);
+
+impl ToSource for Ternary {
+    fn to_source(&self) -> String {
+        format!(
+            "{} ? {} sula {}",
+            self.condition.to_source(),
+            self.exp_true.to_source(),
+            self.exp_false.to_source()
+        )
+    }
+}
+
+impl FoldConstants for Ternary {
+    fn fold_constants(&mut self) {
+        self.condition.fold_constants();
+        self.exp_true.fold_constants();
+        self.exp_false.fold_constants();
+    }
+}
+
+impl Visitable for Ternary {
+    fn accept<V: Visitor>(&self, visitor: &mut V) {
+        visitor.visit_ternary(self);
+        self.condition.accept(visitor);
+        self.exp_true.accept(visitor);
+        self.exp_false.accept(visitor);
+    }
+}
+
+impl Ternary {
+    pub fn new(condition: Exp, exp_true: Exp, exp_false: Exp) -> Self {
+        Self {
+            condition,
+            exp_true,
+            exp_false,
+        }
+    }
+
+    /// Parse the rest of a ternary after its `condition`. The `condition` is given back unchanged
+    /// when there is no `?`.
+    pub fn parse(
+        condition: Exp,
+        tokens: &mut VecDeque<TokenContainer>,
+        context: &mut ParseContext,
+    ) -> ShortResult<Exp> {
+        // <ternary> ::= T_QUESTION <exp> sula <exp>
+        if let some_token!(Token::Question) = tokens.front() {
+            tokens.pop_front();
+        } else {
+            return Ok(condition);
+        }
+        let exp_true = match Exp::parse(tokens, context)? {
+            Some(exp) => exp,
+            None => {
+                return Err(CustomError::unexpected_token(
+                    "Expected an expression after ?",
+                    tokens.front(),
+                ))
+            }
+        };
+        if let some_token!(Token::KeywordElse) = tokens.front() {
+            tokens.pop_front();
+        } else {
+            return Err(CustomError::unexpected_token(
+                "Expected sula after the first expression of the ternary",
+                tokens.front(),
+            ));
+        }
+        match Exp::parse(tokens, context)? {
+            Some(exp_false) => Ok(Exp::Ternary(Box::new(Ternary::new(
+                condition, exp_true, exp_false,
+            )))),
+            None => Err(CustomError::unexpected_token(
+                "Expected an expression after sula",
+                tokens.front(),
+            )),
         }
     }
 }
 
+/// Only the expression selected by the condition is evaluated, like the scopes of an `ij`. Both
+/// expressions must have the same type : the value is compared to the other expression when its
+/// type is known without evaluating it, so `c ? 1 sula "x"` is an error whatever `c` is.
+impl Evaluate for Ternary {
+    fn evaluate(&self, operation_context: &OperationContext) -> ShortResult<OperationIO> {
+        let condition = self.condition.evaluate(operation_context)?;
+        let (selected, other) = match condition {
+            VariableType::Boolean(true) => (&self.exp_true, &self.exp_false),
+            VariableType::Boolean(false) => (&self.exp_false, &self.exp_true),
+            value => {
+                return Err(CustomError::InvalidOperation(format!(
+                    "The condition of a ternary must be a bool, not a {}",
+                    value.type_name()
+                )))
+            }
+        };
+        let value = selected.evaluate(operation_context)?;
+        if let Some(other_value) = other.literal() {
+            if value.type_name() != other_value.type_name() {
+                let (type_true, type_false) = if condition == VariableType::Boolean(true) {
+                    (value.type_name(), other_value.type_name())
+                } else {
+                    (other_value.type_name(), value.type_name())
+                };
+                return Err(CustomError::TypeMismatch(format!(
+                    "The expressions of a ternary must have the same type, not a {} and a {}",
+                    type_true, type_false
+                )));
+            }
+        }
+        Ok(value)
+    }
+}
+
+/// Parse the expressions of a list separated by commas, like the arguments of a call, until the
+/// `closing` token (`)` or `]`) that is also consumed. The opening token must already be consumed.
+///
+/// The list can be empty, but a trailing comma is rejected : `(1, 2,)` is an error.
+pub(crate) fn parse_exp_list(
+    tokens: &mut VecDeque<TokenContainer>,
+    context: &mut ParseContext,
+    closing: Token,
+    closing_name: &str,
+) -> ShortResult<Vec<Exp>> {
+    // <exp_list> ::= (<exp> {T_COMMA <exp>} |) <closing>
+    let mut exps = Vec::new();
+    if let Some(exp) = Exp::parse(tokens, context)? {
+        exps.push(exp);
+        while let some_token!(Token::Comma) = tokens.front() {
+            tokens.pop_front();
+            match Exp::parse(tokens, context)? {
+                Some(exp) => exps.push(exp),
+                None => {
+                    return Err(CustomError::unexpected_token(
+                        "Expected an expression after the comma",
+                        tokens.front(),
+                    ))
+                }
+            }
+        }
+    }
+    match tokens.front() {
+        Some(token) if token.token == closing => {
+            tokens.pop_front();
+            Ok(exps)
+        }
+        token => Err(CustomError::unexpected_token(
+            &format!("Expected a comma or a {}", closing_name),
+            token,
+        )),
+    }
+}
+
+/// Consume the `)` closing the parenthesis `opening`. When the line or the file ends before it,
+/// the error points to the opening parenthesis instead of the end of the line.
+pub(crate) fn parse_right_parenthesis(
+    tokens: &mut VecDeque<TokenContainer>,
+    opening: &TokenContainer,
+) -> ShortResult<()> {
+    match tokens.front() {
+        some_token!(Token::RightParenthesis) => {
+            tokens.pop_front();
+            Ok(())
+        }
+        None | some_token!(Token::Space(SpaceTypes::NewLine)) => Err(
+            CustomError::UnclosedParenthesis(opening.line, opening.column),
+        ),
+        token => Err(CustomError::unexpected_token(
+            "Expected a right parenthesis",
+            token,
+        )),
+    }
+}
+
+fn not_yet_implemented(feature: &str) -> CustomError {
+    CustomError::NotYetImplemented(NotYetImplementedType::InProgress(format!(
+        "the evaluation of {}",
+        feature
+    )))
+}
+
 // --------------
 // --- Return ---
 // --------------
 
-/// `Return` represents a return statement. It contains an [Exp] that will be returned by the
-/// function.
-#[derive(PartialEq)]
+/// `Return` represents a return statement. It contains the [Exp] that will be returned by the
+/// function, or nothing for a bare `ei` that returns null.
+///
+/// A `Return` outside of a function is not rejected by the parser.
+#[derive(PartialEq, Serialize)]
 pub struct Return {
-    exp: Exp,
+    exp: Option<Exp>,
 }
 
 impl GraphDisplay for Return {
-    fn graph_display(&self, graph: &mut String, id: &mut usize) {
-        graph.push_str(&format!("\nsubgraph Return_{}[Return]", id));
+    fn graph_display(&self, graph: &mut Graph, id: &mut usize) {
+        graph.open("Return", "Return", *id);
         *id += 1;
-        self.exp.graph_display(graph, id);
-        graph.push_str("\nend");
+        if let Some(exp) = &self.exp {
+            exp.graph_display(graph, id);
+        }
+        graph.close();
     }
 }
 
 impl_debug!(Return);
 
+impl ToSource for Return {
+    fn to_source(&self) -> String {
+        match &self.exp {
+            Some(exp) => format!("ei {}", exp.to_source()),
+            None => String::from("ei"),
+        }
+    }
+}
+
+impl FoldConstants for Return {
+    fn fold_constants(&mut self) {
+        if let Some(exp) = &mut self.exp {
+            exp.fold_constants();
+        }
+    }
+}
+
+impl Visitable for Return {
+    fn accept<V: Visitor>(&self, visitor: &mut V) {
+        visitor.visit_return(self);
+        if let Some(exp) = &self.exp {
+            exp.accept(visitor);
+        }
+    }
+}
+
 impl Return {
-    pub fn parse(tokens: &mut VecDeque<TokenContainer>) -> ResultOption<Return> {
-        // <return> ::= ei <exp>
+    pub fn new(exp: Option<Exp>) -> Self {
+        Self { exp }
+    }
+
+    pub fn parse(
+        tokens: &mut VecDeque<TokenContainer>,
+        context: &mut ParseContext,
+    ) -> ResultOption<Return> {
+        // <return> ::= ei (<exp> |)
         if let some_token!(Token::KeywordReturn) = tokens.front() {
             tokens.pop_front();
-            if let Some(exp) = Exp::parse(tokens)? {
-                Ok(Some(Return { exp }))
-            } else {
-                Err(CustomError::UnexpectedToken(
-                    "Expected an expression".to_string(),
-                ))
-            }
+            let exp = Exp::parse(tokens, context)?;
+            Ok(Some(Return::new(exp)))
         } else {
             Ok(None)
         }
     }
 }
 
+/// The value is kept in the [OperationContext] until the end of the function.
+impl Evaluate for Return {
+    fn evaluate(&self, operation_context: &OperationContext) -> ShortResult<OperationIO> {
+        let value = match &self.exp {
+            Some(exp) => exp.evaluate(operation_context)?,
+            None => VariableType::Null,
+        };
+        *operation_context.return_value.borrow_mut() = Some(value.clone());
+        Ok(value)
+    }
+}
+
 // -----------
 // --- Sta ---
 // -----------
 
 /// `Sta` represents a statement. It can be a [Return] or an [Exp].
-#[derive(PartialEq)]
+#[derive(PartialEq, Serialize)]
 pub enum Sta {
     Return(Return),
     Exp(Exp),
 }
 
 impl GraphDisplay for Sta {
-    fn graph_display(&self, graph: &mut String, id: &mut usize) {
-        graph.push_str(&format!("\nsubgraph Sta_{}[Sta]", id));
+    fn graph_display(&self, graph: &mut Graph, id: &mut usize) {
+        graph.open("Sta", "Sta", *id);
         *id += 1;
         match self {
             Sta::Return(return_node) => return_node.graph_display(graph, id),
             Sta::Exp(exp) => exp.graph_display(graph, id),
         }
-        graph.push_str("\nend");
+        graph.close();
     }
 }
 
 impl_debug!(Sta);
 
+impl ToSource for Sta {
+    fn to_source(&self) -> String {
+        match self {
+            Sta::Return(return_node) => return_node.to_source(),
+            Sta::Exp(exp) => exp.to_source(),
+        }
+    }
+}
+
+impl FoldConstants for Sta {
+    fn fold_constants(&mut self) {
+        match self {
+            Sta::Return(ret) => ret.fold_constants(),
+            Sta::Exp(exp) => exp.fold_constants(),
+        }
+    }
+}
+
+impl Visitable for Sta {
+    fn accept<V: Visitor>(&self, visitor: &mut V) {
+        visitor.visit_sta(self);
+        match self {
+            Sta::Return(ret) => ret.accept(visitor),
+            Sta::Exp(exp) => exp.accept(visitor),
+        }
+    }
+}
+
 impl Sta {
-    pub fn parse(tokens: &mut VecDeque<TokenContainer>) -> ResultOption<Sta> {
+    pub fn parse(
+        tokens: &mut VecDeque<TokenContainer>,
+        context: &mut ParseContext,
+    ) -> ResultOption<Sta> {
         // <sta> ::= <return> | <exp>
-        if let Some(return_node) = Return::parse(tokens)? {
+        if let Some(return_node) = Return::parse(tokens, context)? {
             Ok(Some(Sta::Return(return_node)))
-        } else if let Some(exp) = Exp::parse(tokens)? {
+        } else if let Some(exp) = Exp::parse(tokens, context)? {
             Ok(Some(Sta::Exp(exp)))
         } else {
             Ok(None)
@@ -613,20 +1397,29 @@ impl Sta {
     }
 }
 
+impl Evaluate for Sta {
+    fn evaluate(&self, operation_context: &OperationContext) -> ShortResult<OperationIO> {
+        match self {
+            Sta::Return(return_node) => return_node.evaluate(operation_context),
+            Sta::Exp(exp) => exp.evaluate(operation_context),
+        }
+    }
+}
+
 // ------------
 // --- StaL ---
 // ------------
 
 /// `StaL` is the base of a scope. It contains a list of [Sta] that will be executed in
 /// order.
-#[derive(PartialEq)]
+#[derive(PartialEq, Serialize)]
 pub struct StaL {
     sta_l: Vec<Sta>,
 }
 
 impl GraphDisplay for StaL {
-    fn graph_display(&self, graph: &mut String, id: &mut usize) {
-        graph.push_str(&format!("\nsubgraph StaL_{}[StaL]", id));
+    fn graph_display(&self, graph: &mut Graph, id: &mut usize) {
+        graph.open("StaL", "StaL", *id);
         *id += 1;
         for sta in &self.sta_l {
             match sta {
@@ -634,36 +1427,71 @@ impl GraphDisplay for StaL {
                 Sta::Exp(exp) => exp.graph_display(graph, id),
             }
         }
-        graph.push_str("\nend");
+        graph.close();
     }
 }
 
 impl_debug!(StaL);
 
+impl ToSource for StaL {
+    fn to_source(&self) -> String {
+        block_source(self.sta_l.iter())
+    }
+}
+
+impl FoldConstants for StaL {
+    fn fold_constants(&mut self) {
+        for sta in &mut self.sta_l {
+            sta.fold_constants();
+        }
+    }
+}
+
+impl Visitable for StaL {
+    fn accept<V: Visitor>(&self, visitor: &mut V) {
+        visitor.visit_sta_l(self);
+        for sta in &self.sta_l {
+            sta.accept(visitor);
+        }
+        visitor.leave_sta_l(self);
+    }
+}
+
 impl StaL {
     pub fn new(sta_l: Vec<Sta>) -> Self {
         Self { sta_l }
     }
 
-    pub fn parse(tokens: &mut VecDeque<TokenContainer>) -> ResultOption<StaL> {
-        // <sta_l> ::= T_LEFT_E {<sta>} T_RIGHT_E
+    /// Whether the last statement is an expression that gives its value to the block, that is not
+    /// a declaration or a `ei`.
+    pub fn has_trailing_exp(&self) -> bool {
+        matches!(self.sta_l.last(), Some(Sta::Exp(exp)) if !exp.is_declaration())
+    }
+
+    pub fn parse(
+        tokens: &mut VecDeque<TokenContainer>,
+        context: &mut ParseContext,
+    ) -> ResultOption<StaL> {
+        // <sta_l> ::= T_LEFT_E {<sep>} {<sta> {<sep>}} T_RIGHT_E
         if let some_token!(Token::LeftBrace) = tokens.front() {
             tokens.pop_front();
             let mut sta_l = Vec::new();
 
-            while let Some(sta) = Sta::parse(tokens)? {
-                sta_l.push(sta);
+            loop {
+                skip_separators(tokens);
+                match Sta::parse(tokens, context)? {
+                    Some(sta) => sta_l.push(sta),
+                    None => break,
+                }
             }
 
-            if let Some(TokenContainer {
-                token: Token::RightBrace,
-                ..
-            }) = tokens.pop_front()
-            {
+            if let some_token!(Token::RightBrace) = tokens.front() {
+                tokens.pop_front();
                 Ok(Some(StaL::new(sta_l)))
             } else {
-                Err(CustomError::UnexpectedToken(
-                    "Expected a right curly bracket".to_string(),
+                Err(CustomError::unexpected_token(
+                    "Expected a right curly bracket",
+                    tokens.front(),
                 ))
             }
         } else {
@@ -671,3 +1499,65 @@ impl StaL {
         }
     }
 }
+
+/// The value of a [StaL] is the value of its last statement. The variables declared inside are
+/// dropped at the end of the [StaL]. The statements after an executed [Return] are skipped.
+impl Evaluate for StaL {
+    fn evaluate(&self, operation_context: &OperationContext) -> ShortResult<OperationIO> {
+        operation_context.variables.borrow_mut().push_scope()?;
+        let value = evaluate_statements(operation_context, self.sta_l.iter());
+        operation_context.variables.borrow_mut().pop_scope();
+        value
+    }
+}
+
+/// A node that can be a statement of a block or of a file.
+pub(crate) trait StatementNode: Evaluate {
+    /// Whether the node is a `biuli` scope, whose value must be used.
+    fn is_bubble_scope(&self) -> bool;
+}
+
+impl StatementNode for Exp {
+    fn is_bubble_scope(&self) -> bool {
+        match self {
+            Exp::ExpTp(ExpTp::ExpBase(ExpBase::ScopeBase(scope_base))) => {
+                matches!(**scope_base, ScopeBase::Biuli(_))
+            }
+            _ => false,
+        }
+    }
+}
+
+impl StatementNode for Sta {
+    fn is_bubble_scope(&self) -> bool {
+        match self {
+            Sta::Exp(exp) => exp.is_bubble_scope(),
+            Sta::Return(_) => false,
+        }
+    }
+}
+
+/// Evaluate the nodes in order until the end or until a [Return] is executed. The value is the
+/// value of the last node evaluated, or null.
+///
+/// In strict mode, a `biuli` scope that is not the last node is an error : its value bubbles up
+/// but nothing uses it.
+pub(crate) fn evaluate_statements<'a, T: StatementNode + 'a>(
+    operation_context: &OperationContext,
+    nodes: impl Iterator<Item = &'a T>,
+) -> ShortResult<OperationIO> {
+    let mut value = VariableType::Null;
+    let mut nodes = nodes.peekable();
+    while let Some(node) = nodes.next() {
+        if operation_context.strict && node.is_bubble_scope() && nodes.peek().is_some() {
+            return Err(CustomError::StrictMode(String::from(
+                "The value of a biuli scope is never used, use kodi instead",
+            )));
+        }
+        value = node.evaluate(operation_context)?;
+        if operation_context.is_returning() {
+            break;
+        }
+    }
+    Ok(value)
+}
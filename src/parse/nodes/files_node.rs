@@ -1,40 +1,156 @@
 use std::collections::VecDeque;
 
+use serde::Serialize;
+
+use crate::execute::{Evaluate, OperationContext, OperationIO};
 use crate::impl_debug;
-use crate::parse::nodes::expressions::Exp;
-use crate::parse::nodes::GraphDisplay;
-use crate::skr_errors::ResultOption;
+use crate::parse::context::ParseContext;
+use crate::parse::nodes::expressions::evaluate_statements;
+use crate::parse::nodes::statements::Statement;
+use crate::parse::nodes::visitor::{Visitable, Visitor};
+use crate::parse::nodes::{
+    join_source, skip_separators, FoldConstants, Graph, GraphDisplay, ToSource,
+};
+use crate::skr_errors::{CustomError, ResultOption, ShortResult};
 use crate::tokens::TokenContainer;
 
-/// Node representing a file. This is the root node of the AST.
-#[derive(PartialEq)]
+/// Node representing a file. This is the root node of the AST, see [crate::parse::Program].
+///
+/// The top-level [Statement]s are the declarations of variables (`fu int a 1`), functions (`ums`)
+/// and classes (`kat`), the modifications of a variable, or any other expression.
+#[derive(PartialEq, Serialize)]
 pub struct FileNode {
-    exps: Vec<Exp>,
+    statements: Vec<Statement>,
 }
 
 impl GraphDisplay for FileNode {
-    fn graph_display(&self, graph: &mut String, id: &mut usize) {
-        graph.push_str(&format!("\nsubgraph File_{}[File]", id));
+    fn graph_display(&self, graph: &mut Graph, id: &mut usize) {
+        graph.open("File", "File", *id);
         *id += 1;
-        for exp in &self.exps {
-            exp.graph_display(graph, id);
+        for statement in &self.statements {
+            statement.graph_display(graph, id);
         }
-        graph.push_str("\nend");
+        graph.close();
     }
 }
 
 impl_debug!(FileNode);
 
+impl ToSource for FileNode {
+    fn to_source(&self) -> String {
+        join_source(self.statements.iter(), "\n")
+    }
+}
+
+impl FoldConstants for FileNode {
+    fn fold_constants(&mut self) {
+        for statement in &mut self.statements {
+            statement.fold_constants();
+        }
+    }
+}
+
+impl Visitable for FileNode {
+    fn accept<V: Visitor>(&self, visitor: &mut V) {
+        visitor.visit_file(self);
+        for statement in &self.statements {
+            statement.accept(visitor);
+        }
+    }
+}
+
 impl FileNode {
-    pub fn new(exps: Vec<Exp>) -> Self {
-        Self { exps }
+    pub fn new(statements: Vec<Statement>) -> Self {
+        Self { statements }
+    }
+
+    /// The top-level statements, in the order of the file.
+    pub fn statements(&self) -> &[Statement] {
+        &self.statements
     }
 
-    pub fn parse(tokens: &mut VecDeque<TokenContainer>) -> ResultOption<Self> {
-        let mut exps = Vec::new();
-        while let Some(exp) = Exp::parse(tokens)? {
-            exps.push(exp);
+    pub fn parse(
+        tokens: &mut VecDeque<TokenContainer>,
+        context: &mut ParseContext,
+    ) -> ResultOption<Self> {
+        // <file> ::= {<sep>} {<statement> {<sep>}}
+        let mut statements = Vec::new();
+        while let Some(statement) = Self::parse_statement(tokens, context)? {
+            statements.push(statement);
         }
-        Ok(Some(FileNode { exps }))
+        // Every line of the file must be parsed, including the last one
+        if !tokens.is_empty() {
+            return Err(CustomError::unexpected_token(
+                "Expected a statement",
+                tokens.front(),
+            ));
+        }
+        Ok(Some(FileNode { statements }))
+    }
+
+    /// Parse the next top-level statement, after the empty lines. The tokens after it are left
+    /// for the next call.
+    pub fn parse_statement(
+        tokens: &mut VecDeque<TokenContainer>,
+        context: &mut ParseContext,
+    ) -> ResultOption<Statement> {
+        skip_separators(tokens);
+        Statement::parse(tokens, context)
+    }
+
+    /// Parse the file like [FileNode::parse], but continue after an error to report every error
+    /// at once. After an error, the rest of the line of the error is skipped and the parsing
+    /// continues on the next line.
+    pub fn parse_all(
+        tokens: &mut VecDeque<TokenContainer>,
+        context: &mut ParseContext,
+    ) -> Result<Self, Vec<CustomError>> {
+        let mut statements = Vec::new();
+        let mut errors = Vec::new();
+        loop {
+            skip_separators(tokens);
+            if tokens.is_empty() {
+                break;
+            }
+            let error = match Statement::parse(tokens, context) {
+                Ok(Some(statement)) => {
+                    statements.push(statement);
+                    continue;
+                }
+                Ok(None) => CustomError::unexpected_token("Expected a statement", tokens.front()),
+                Err(error) => error,
+            };
+            // the parser may have already removed the end of the line
+            match error.position() {
+                Some((line, _)) => skip_until_line(tokens, line + 1),
+                None => tokens.clear(),
+            }
+            errors.push(error);
+        }
+        if errors.is_empty() {
+            Ok(FileNode { statements })
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+/// Remove the tokens until the first token of the line `line`.
+fn skip_until_line(tokens: &mut VecDeque<TokenContainer>, line: usize) {
+    while tokens.front().is_some_and(|token| token.line < line) {
+        tokens.pop_front();
+    }
+}
+
+/// The value of a file is the value of its last statement, or of the first `ei` executed outside of
+/// a function. The variables are declared in the global scope.
+impl Evaluate for FileNode {
+    fn evaluate(&self, operation_context: &OperationContext) -> ShortResult<OperationIO> {
+        let value = evaluate_statements(operation_context, self.statements.iter())?;
+        Ok(operation_context
+            .return_value
+            .borrow_mut()
+            .take()
+            .unwrap_or(value))
     }
 }
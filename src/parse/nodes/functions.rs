@@ -1,73 +1,414 @@
 use std::collections::VecDeque;
+use std::rc::Rc;
 
+use serde::Serialize;
+
+use crate::execute::variables::VariableType;
+use crate::execute::{Evaluate, OperationContext, OperationIO};
+use crate::parse::context::ParseContext;
 use crate::parse::nodes::blocs::Scope;
-use crate::parse::nodes::id_nodes::TupleNode;
-use crate::parse::nodes::GraphDisplay;
-use crate::skr_errors::{CustomError, ResultOption};
+use crate::parse::nodes::vars::{parse_type, Type};
+use crate::parse::nodes::visitor::{Visitable, Visitor};
+use crate::parse::nodes::{join_source, FoldConstants, Graph, GraphDisplay, ToSource};
+use crate::skr_errors::{CustomError, ResultOption, ShortResult};
 use crate::tokens::{Token, TokenContainer};
 use crate::{impl_debug, some_token};
 
 // Grammar of this file :
-// <fct_dec> ::= ums T_IDENTIFIER <tuple> <scope>
+// <param> ::= <type> T_IDENTIFIER
+// <params> ::= T_LEFT_P (<param> {T_COMMA <param>} |) T_RIGHT_P
+// <fct_dec> ::= ums T_IDENTIFIER <params> (T_ARROW <type> |) <scope>
+
+// -------------
+// --- Param ---
+// -------------
+
+/// `Param` represents a parameter in a function declaration. It contains the [Type] of the
+/// parameter and its identifier.
+///
+/// # Grammar
+///
+/// `<param> ::= <type> T_IDENTIFIER`
+#[derive(PartialEq, Serialize)]
+pub struct Param {
+    #[serde(rename = "type")]
+    type_: Type,
+    identifier: String,
+}
+
+impl GraphDisplay for Param {
+    fn graph_display(&self, graph: &mut Graph, id: &mut usize) {
+        graph.open("Param", &format!("Param {}", self.identifier), *id);
+        *id += 1;
+        self.type_.graph_display(graph, id);
+        graph.close();
+    }
+}
+
+impl_debug!(Param);
+
+impl ToSource for Param {
+    fn to_source(&self) -> String {
+        format!("{} {}", self.type_.to_source(), self.identifier)
+    }
+}
+
+impl Visitable for Param {
+    fn accept<V: Visitor>(&self, visitor: &mut V) {
+        visitor.visit_param(self);
+        self.type_.accept(visitor);
+    }
+}
+
+impl Param {
+    pub fn new(type_: Type, identifier: String) -> Self {
+        Self { type_, identifier }
+    }
+
+    pub(crate) fn identifier(&self) -> &str {
+        &self.identifier
+    }
+
+    pub(crate) fn type_(&self) -> &Type {
+        &self.type_
+    }
+
+    pub fn parse(
+        tokens: &mut VecDeque<TokenContainer>,
+        context: &mut ParseContext,
+    ) -> ResultOption<Self> {
+        // <param> ::= <type> T_IDENTIFIER
+        let type_ = match parse_type(tokens, context)? {
+            Some(type_) => type_,
+            None => {
+                return if let some_token!(Token::Identifier(_)) = tokens.front() {
+                    Err(CustomError::unexpected_token(
+                        "Expected a type before the name of the parameter",
+                        tokens.front(),
+                    ))
+                } else {
+                    Ok(None)
+                }
+            }
+        };
+
+        match tokens.pop_front() {
+            some_token!(Token::Identifier(identifier)) => Ok(Some(Param::new(type_, identifier))),
+            token => Err(CustomError::unexpected_token(
+                "Expected the name of the parameter",
+                token.as_ref(),
+            )),
+        }
+    }
+}
+
+// --------------
+// --- Params ---
+// --------------
+
+/// `Params` represents the list of parameters of a function declaration, between parentheses and
+/// separated by commas. The list can be empty, but a trailing comma is rejected like in the
+/// arguments of a call : `(int a,)` is an error.
+///
+/// # Grammar
+///
+/// `<params> ::= T_LEFT_P (<param> {T_COMMA <param>} |) T_RIGHT_P`
+///
+/// See also [Param].
+#[derive(PartialEq, Serialize)]
+pub struct Params {
+    params: Vec<Param>,
+}
+
+impl GraphDisplay for Params {
+    fn graph_display(&self, graph: &mut Graph, id: &mut usize) {
+        graph.open("Params", "Params", *id);
+        *id += 1;
+        for param in &self.params {
+            param.graph_display(graph, id);
+        }
+        graph.close();
+    }
+}
+
+impl_debug!(Params);
+
+impl ToSource for Params {
+    fn to_source(&self) -> String {
+        format!("({})", join_source(self.params.iter(), ", "))
+    }
+}
+
+impl Visitable for Params {
+    fn accept<V: Visitor>(&self, visitor: &mut V) {
+        visitor.visit_params(self);
+        for param in &self.params {
+            param.accept(visitor);
+        }
+    }
+}
+
+impl Params {
+    pub fn new(params: Vec<Param>) -> Self {
+        Self { params }
+    }
+
+    pub fn parse(
+        tokens: &mut VecDeque<TokenContainer>,
+        context: &mut ParseContext,
+    ) -> ResultOption<Self> {
+        // <params> ::= T_LEFT_P (<param> {T_COMMA <param>} |) T_RIGHT_P
+        if let some_token!(Token::LeftParenthesis) = tokens.front() {
+            tokens.pop_front();
+            let mut params = Vec::new();
+
+            if let Some(param) = Param::parse(tokens, context)? {
+                params.push(param);
+                while let some_token!(Token::Comma) = tokens.front() {
+                    tokens.pop_front();
+                    match Param::parse(tokens, context)? {
+                        Some(param) => params.push(param),
+                        None => {
+                            return Err(CustomError::unexpected_token(
+                                "Expected a parameter after the comma",
+                                tokens.front(),
+                            ))
+                        }
+                    }
+                }
+            }
+
+            if let some_token!(Token::RightParenthesis) = tokens.front() {
+                tokens.pop_front();
+                Ok(Some(Params::new(params)))
+            } else {
+                Err(CustomError::unexpected_token(
+                    "Expected a right parenthesis to close the parameters",
+                    tokens.front(),
+                ))
+            }
+        } else {
+            Ok(None)
+        }
+    }
+}
 
 // --------------
 // --- FctDec ---
 // --------------
 
 /// `FctDec` represents a function declaration. It contains the identifier of the function, the
-/// tuple of arguments and the scope of the function.
+/// parameters of the function, the type it returns when it is given and the scope of the function.
 ///
 /// # Grammar
 ///
-/// `<fct_dec> ::= ums T_IDENTIFIER <tuple> <scope>`
+/// `<fct_dec> ::= ums T_IDENTIFIER <params> (T_ARROW <type> |) <scope>`
 ///
-/// See also [TupleNode] and [Scope].
-#[derive(PartialEq)]
+/// See also [Params] and [Scope].
+#[derive(PartialEq, Serialize)]
 pub struct FctDec {
     identifier: String,
-    tuple: TupleNode,
+    params: Params,
+    /// Type written after `->`, the value returned by the function must have this type
+    return_type: Option<Type>,
     scope: Scope,
 }
 
 impl GraphDisplay for FctDec {
-    fn graph_display(&self, graph: &mut String, id: &mut usize) {
-        graph.push_str(&format!(
-            "\nsubgraph FctDec_{}[FctDec {}]",
-            id, self.identifier
-        ));
+    fn graph_display(&self, graph: &mut Graph, id: &mut usize) {
+        graph.open("FctDec", &format!("FctDec {}", self.identifier), *id);
         *id += 1;
-        self.tuple.graph_display(graph, id);
+        self.params.graph_display(graph, id);
+        if let Some(return_type) = &self.return_type {
+            return_type.graph_display(graph, id);
+        }
         self.scope.graph_display(graph, id);
-        graph.push_str("\nend");
+        graph.close();
     }
 }
 
 impl_debug!(FctDec);
 
+impl ToSource for FctDec {
+    fn to_source(&self) -> String {
+        let return_type = match &self.return_type {
+            Some(return_type) => format!(" -> {}", return_type.to_source()),
+            None => String::new(),
+        };
+        format!(
+            "ums {} {}{} {}",
+            self.identifier,
+            self.params.to_source(),
+            return_type,
+            self.scope.to_source()
+        )
+    }
+}
+
+impl FoldConstants for FctDec {
+    fn fold_constants(&mut self) {
+        self.scope.fold_constants();
+    }
+}
+
+impl Visitable for FctDec {
+    fn accept<V: Visitor>(&self, visitor: &mut V) {
+        visitor.visit_fct_dec(self);
+        self.params.accept(visitor);
+        if let Some(return_type) = &self.return_type {
+            return_type.accept(visitor);
+        }
+        self.scope.accept(visitor);
+        visitor.leave_fct_dec(self);
+    }
+}
+
 impl FctDec {
-    pub fn parse(tokens: &mut VecDeque<TokenContainer>) -> ResultOption<Self> {
-        // <fct_dec> ::= ums T_IDENTIFIER <tuple> <scope>
+    pub(crate) fn new(
+        identifier: String,
+        params: Params,
+        return_type: Option<Type>,
+        scope: Scope,
+    ) -> Self {
+        Self {
+            identifier,
+            params,
+            return_type,
+            scope,
+        }
+    }
+
+    pub fn parse(
+        tokens: &mut VecDeque<TokenContainer>,
+        context: &mut ParseContext,
+    ) -> ResultOption<Self> {
+        // <fct_dec> ::= ums T_IDENTIFIER <params> (T_ARROW <type> |) <scope>
         if let some_token!(Token::KeywordFunction) = tokens.front() {
             tokens.pop_front();
-            if let some_token!(Token::Identifier(identifier)) = tokens.pop_front() {
-                match TupleNode::parse(tokens)? {
-                    Some(tuple) => match Scope::parse(tokens)? {
-                        Some(scope) => Ok(Some(FctDec {
-                            identifier,
-                            tuple,
-                            scope,
-                        })),
-                        None => Err(CustomError::UnexpectedToken("Expected a scope".to_string())),
-                    },
-                    None => Err(CustomError::UnexpectedToken("Expected a tuple".to_string())),
-                }
-            } else {
-                Err(CustomError::UnexpectedToken(
-                    "Expected an identifier".to_string(),
-                ))
+            match tokens.pop_front() {
+                some_token!(Token::Identifier(identifier)) => match Params::parse(tokens, context)?
+                {
+                    Some(params) => {
+                        let return_type = Self::parse_return_type(tokens, context)?;
+                        match Scope::parse(tokens, context)? {
+                            Some(scope) => {
+                                Ok(Some(FctDec::new(identifier, params, return_type, scope)))
+                            }
+                            None => Err(CustomError::unexpected_token(
+                                "Expected a scope",
+                                tokens.front(),
+                            )),
+                        }
+                    }
+                    None => Err(CustomError::unexpected_token(
+                        "Expected the parameters of the function",
+                        tokens.front(),
+                    )),
+                },
+                token => Err(CustomError::unexpected_token(
+                    "Expected an identifier",
+                    token.as_ref(),
+                )),
+            }
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+impl FctDec {
+    fn parse_return_type(
+        tokens: &mut VecDeque<TokenContainer>,
+        context: &mut ParseContext,
+    ) -> ShortResult<Option<Type>> {
+        if let some_token!(Token::Arrow) = tokens.front() {
+            tokens.pop_front();
+            match parse_type(tokens, context)? {
+                Some(type_) => Ok(Some(type_)),
+                None => Err(CustomError::unexpected_token(
+                    "Expected the return type after ->",
+                    tokens.front(),
+                )),
             }
         } else {
             Ok(None)
         }
     }
 }
+
+/// Calling a function evaluates its scope with only the global variables, the function itself and
+/// the parameters visible. The variables of the caller are hidden until the end of the call.
+impl FctDec {
+    pub fn identifier(&self) -> &str {
+        &self.identifier
+    }
+
+    /// Declare the function in the current scope, the function is a value like any other.
+    pub(crate) fn declare(
+        self: &Rc<Self>,
+        operation_context: &OperationContext,
+    ) -> ShortResult<OperationIO> {
+        let function = VariableType::Function(Rc::clone(self));
+        operation_context
+            .variables
+            .borrow_mut()
+            .declare(&self.identifier, function.clone())?;
+        Ok(function)
+    }
+
+    /// Call the function with the evaluated arguments. `line` is the line of the call, used in the
+    /// errors. The value is the one of the `ei` executed, or null, and must match the return type
+    /// when there is one.
+    pub(crate) fn call(
+        self: &Rc<Self>,
+        operation_context: &OperationContext,
+        args: Vec<OperationIO>,
+        line: usize,
+    ) -> ShortResult<OperationIO> {
+        if args.len() != self.params.params.len() {
+            return Err(CustomError::InvalidCall(
+                format!(
+                    "The function {} expects {} arguments, got {}",
+                    self.identifier,
+                    self.params.params.len(),
+                    args.len()
+                ),
+                line,
+            ));
+        }
+        let caller_scopes = operation_context
+            .variables
+            .borrow_mut()
+            .push_frame(operation_context.max_call_depth)?;
+        let value = self.bind_and_evaluate(operation_context, args);
+        operation_context
+            .variables
+            .borrow_mut()
+            .pop_frame(caller_scopes);
+        let returned = operation_context.return_value.borrow_mut().take();
+        let returned = value.map(|_| returned.unwrap_or(VariableType::Null))?;
+        match &self.return_type {
+            Some(return_type) => return_type.assign(returned),
+            None => Ok(returned),
+        }
+    }
+
+    fn bind_and_evaluate(
+        self: &Rc<Self>,
+        operation_context: &OperationContext,
+        args: Vec<OperationIO>,
+    ) -> ShortResult<OperationIO> {
+        // the function is visible inside itself for the recursion
+        self.declare(operation_context)?;
+        for (param, arg) in self.params.params.iter().zip(args) {
+            let value = param.type_.assign(arg)?;
+            operation_context.variables.borrow_mut().declare_typed(
+                &param.identifier,
+                value,
+                param.type_.clone(),
+                false,
+            )?;
+        }
+        self.scope.evaluate(operation_context)
+    }
+}
@@ -1,45 +1,117 @@
 use std::collections::VecDeque;
 
-use crate::parse::nodes::classes::is_type_def;
-use crate::parse::nodes::GraphDisplay;
-use crate::skr_errors::{CustomError, ResultOption};
+use serde::Serialize;
+
+use crate::execute::variables::VariableType;
+use crate::execute::{Evaluate, IntType, OperationContext, OperationIO};
+use crate::parse::context::ParseContext;
+use crate::parse::nodes::expressions::{parse_exp_list, Exp};
+use crate::parse::nodes::visitor::{Visitable, Visitor};
+use crate::parse::nodes::{join_source, FoldConstants, Graph, GraphDisplay, ToSource};
+use crate::parse::token_stream::expect;
+use crate::skr_errors::{CustomError, ResultOption, ShortResult};
 use crate::tokens::{Token, TokenContainer};
 use crate::{impl_debug, skr_errors, some_token};
 
 // Grammar of this file :
+// <tuple> ::= T_LEFT_P (<exp> {T_COMMA <exp>} |) T_RIGHT_P
 // <cget> ::= T_TYPE_DEF
-// <op_in> ::= (T_IN (<cget> | <id_get>) |)
+// <field_access> ::= T_DOT T_IDENTIFIER (<field_access> |)
+// <op_in> ::= (T_IN (<cget> | <id_get> | T_INT) | <field_access> |)
 // <id_get> ::= T_IDENTIFIER (<tuple> |) <op_in>
 
-/// `TupleNode` represents a tuple in the AST.
+/// `TupleNode` represents a tuple in the AST : a list of expressions between parentheses,
+/// separated by commas.
+///
+/// # Grammar
 ///
-/// The grammar of a tuple is not yet defined, so this class is not implemented yet.
+/// `<tuple> ::= T_LEFT_P (<exp> {T_COMMA <exp>} |) T_RIGHT_P`
 ///
 /// # Use cases
 ///
-/// Tuples will be a datatype, and they will mainly be used to store fonction arguments.
-#[derive(PartialEq)]
+/// Tuples are used to store the arguments of a function call : `fibo(n - 1)`. Because the spaces
+/// are not tokens, a parenthesis right after an identifier always starts a tuple, `x (1)` is a call
+/// and not the modification of `x`.
+#[derive(PartialEq, Serialize)]
 pub struct TupleNode {
-    // TODO: définir les champs du tuple ici
+    exps: Vec<Exp>,
+    /// Line of the opening parenthesis, used to locate the errors of a call
+    #[serde(skip)]
+    line: usize,
 }
 
 impl GraphDisplay for TupleNode {
-    fn graph_display(&self, graph: &mut String, id: &mut usize) {
-        graph.push_str(&format!("\nsubgraph TupleNode_{}[TupleNode]\nend", id));
+    fn graph_display(&self, graph: &mut Graph, id: &mut usize) {
+        graph.open("TupleNode", "TupleNode", *id);
         *id += 1;
+        for exp in &self.exps {
+            exp.graph_display(graph, id);
+        }
+        graph.close();
     }
 }
 
 impl_debug!(TupleNode);
 
+impl ToSource for TupleNode {
+    fn to_source(&self) -> String {
+        format!("({})", join_source(self.exps.iter(), ", "))
+    }
+}
+
+impl FoldConstants for TupleNode {
+    fn fold_constants(&mut self) {
+        for exp in &mut self.exps {
+            exp.fold_constants();
+        }
+    }
+}
+
+impl Visitable for TupleNode {
+    fn accept<V: Visitor>(&self, visitor: &mut V) {
+        visitor.visit_tuple(self);
+        for exp in &self.exps {
+            exp.accept(visitor);
+        }
+    }
+}
+
 impl TupleNode {
-    pub(crate) fn new() -> Self {
-        Self {}
+    pub(crate) fn new(exps: Vec<Exp>, line: usize) -> Self {
+        Self { exps, line }
+    }
+
+    pub(crate) fn line(&self) -> usize {
+        self.line
+    }
+
+    pub(crate) fn parse(
+        tokens: &mut VecDeque<TokenContainer>,
+        context: &mut ParseContext,
+    ) -> ResultOption<Self> {
+        // <tuple> ::= T_LEFT_P (<exp> {T_COMMA <exp>} |) T_RIGHT_P
+        let line = match tokens.front() {
+            some_token!(Token::LeftParenthesis) => tokens.pop_front().map_or(0, |t| t.line),
+            _ => return Ok(None),
+        };
+        let exps = parse_exp_list(
+            tokens,
+            context,
+            Token::RightParenthesis,
+            "right parenthesis",
+        )?;
+        Ok(Some(TupleNode::new(exps, line)))
     }
 
-    pub(crate) fn parse(_tokens: &mut VecDeque<TokenContainer>) -> ResultOption<Self> {
-        // TODO: implémenter cette fonction
-        Ok(None)
+    /// Evaluate every expression of the tuple, from left to right.
+    pub(crate) fn evaluate_all(
+        &self,
+        operation_context: &OperationContext,
+    ) -> ShortResult<Vec<OperationIO>> {
+        self.exps
+            .iter()
+            .map(|exp| exp.evaluate(operation_context))
+            .collect()
     }
 }
 
@@ -67,23 +139,39 @@ impl TupleNode {
 ///
 /// A, E and D can be accessed with a `CGet` node while B, B0 of D and C cannot. See the [IdGet]
 /// for further information.
-#[derive(PartialEq)]
+#[derive(PartialEq, Serialize)]
 pub struct CGet {
     pub(crate) name: String,
 }
 
 impl GraphDisplay for CGet {
-    fn graph_display(&self, graph: &mut String, id: &mut usize) {
-        graph.push_str(&format!("\nsubgraph CGet_{}[CGet {}]\nend", id, self.name));
+    fn graph_display(&self, graph: &mut Graph, id: &mut usize) {
+        graph.open("CGet", &format!("CGet {}", self.name), *id);
         *id += 1;
+        graph.close();
     }
 }
 
 impl_debug!(CGet);
 
-pub(crate) fn parse_cget(tokens: &mut VecDeque<TokenContainer>) -> Option<CGet> {
+impl ToSource for CGet {
+    fn to_source(&self) -> String {
+        self.name.clone()
+    }
+}
+
+impl Visitable for CGet {
+    fn accept<V: Visitor>(&self, visitor: &mut V) {
+        visitor.visit_c_get(self);
+    }
+}
+
+pub(crate) fn parse_cget(
+    tokens: &mut VecDeque<TokenContainer>,
+    context: &mut ParseContext,
+) -> Option<CGet> {
     if let some_token!(Token::Identifier(identifier)) = tokens.front() {
-        if is_type_def(identifier) {
+        if context.classes.is_type_def(identifier) {
             if let some_token!(Token::Identifier(identifier)) = tokens.pop_front() {
                 return Some(CGet { name: identifier });
             }
@@ -139,7 +227,7 @@ pub(crate) fn parse_cget(tokens: &mut VecDeque<TokenContainer>) -> Option<CGet>
 /// - `T1:F():D`, get the field T1 of the result of the function F with no arguments. Here, F() must
 ///   be an IdGet node, this is the only solution.
 /// - `T1:C0:D`, get the field T1 of the field C0 of the variable D. Here, C0 must also be an IdGet.
-#[derive(PartialEq)]
+#[derive(PartialEq, Serialize)]
 pub struct IdGet {
     pub identifier: String,
     pub tuple: Option<TupleNode>,
@@ -147,22 +235,45 @@ pub struct IdGet {
 }
 
 impl GraphDisplay for IdGet {
-    fn graph_display(&self, graph: &mut String, id: &mut usize) {
-        graph.push_str(&format!(
-            "\nsubgraph IdGet_{}[IdGet {}]",
-            id, self.identifier
-        ));
+    fn graph_display(&self, graph: &mut Graph, id: &mut usize) {
+        graph.open("IdGet", &format!("IdGet {}", self.identifier), *id);
         *id += 1;
         if let Some(tuple) = &self.tuple {
             tuple.graph_display(graph, id);
         }
         self.op_in.graph_display(graph, id);
-        graph.push_str("\nend");
+        graph.close();
     }
 }
 
 impl_debug!(IdGet);
 
+impl ToSource for IdGet {
+    fn to_source(&self) -> String {
+        let tuple = self.tuple.as_ref().map_or(String::new(), |t| t.to_source());
+        format!("{}{}{}", self.identifier, tuple, self.op_in.to_source())
+    }
+}
+
+impl FoldConstants for IdGet {
+    fn fold_constants(&mut self) {
+        if let Some(tuple) = &mut self.tuple {
+            tuple.fold_constants();
+        }
+        self.op_in.fold_constants();
+    }
+}
+
+impl Visitable for IdGet {
+    fn accept<V: Visitor>(&self, visitor: &mut V) {
+        visitor.visit_id_get(self);
+        if let Some(tuple) = &self.tuple {
+            tuple.accept(visitor);
+        }
+        self.op_in.accept(visitor);
+    }
+}
+
 impl IdGet {
     pub(crate) fn new(identifier: String, tuple: Option<TupleNode>, op_in: OpIn) -> Self {
         Self {
@@ -172,13 +283,15 @@ impl IdGet {
         }
     }
 
-    pub(crate) fn parse(tokens: &mut VecDeque<TokenContainer>) -> ResultOption<Self> {
+    pub(crate) fn parse(
+        tokens: &mut VecDeque<TokenContainer>,
+        context: &mut ParseContext,
+    ) -> ResultOption<Self> {
         // <id_get> ::= T_IDENTIFIER (<tuple> |) <op_in>
         if let some_token!(Token::Identifier(_)) = tokens.front() {
             if let some_token!(Token::Identifier(identifier)) = tokens.pop_front() {
-                let tuple_parsed = TupleNode::parse(tokens)?;
-                let tuple = tuple_parsed;
-                let op_in = parse_op_in(tokens)?;
+                let tuple = TupleNode::parse(tokens, context)?;
+                let op_in = parse_op_in(tokens, context)?;
                 Ok(Some(IdGet {
                     identifier,
                     tuple,
@@ -193,52 +306,199 @@ impl IdGet {
     }
 }
 
+// -------------------
+// --- FieldAccess ---
+// -------------------
+
+/// `FieldAccess` is a chain of fields read from the left with `.` : in `o.inner.value`, `inner` is
+/// a field of the variable `o` and `value` a field of `inner`. This is the same chain as
+/// `value:inner:o`, written in the reading order.
+///
+/// A `.` right after digits is always the point of a float : `1.5` is a float, and a field access
+/// cannot start with a number.
+///
+/// # Grammar
+///
+/// `<field_access> ::= T_DOT T_IDENTIFIER (<field_access> |)`
+#[derive(PartialEq, Serialize)]
+pub struct FieldAccess {
+    pub field: String,
+    pub next: Option<Box<FieldAccess>>,
+}
+
+impl GraphDisplay for FieldAccess {
+    fn graph_display(&self, graph: &mut Graph, id: &mut usize) {
+        graph.open("FieldAccess", &format!("FieldAccess {}", self.field), *id);
+        *id += 1;
+        if let Some(next) = &self.next {
+            next.graph_display(graph, id);
+        }
+        graph.close();
+    }
+}
+
+impl_debug!(FieldAccess);
+
+impl ToSource for FieldAccess {
+    fn to_source(&self) -> String {
+        let next = self
+            .next
+            .as_ref()
+            .map_or(String::new(), |next| next.to_source());
+        format!(".{}{}", self.field, next)
+    }
+}
+
+impl Visitable for FieldAccess {
+    fn accept<V: Visitor>(&self, visitor: &mut V) {
+        visitor.visit_field_access(self);
+        if let Some(next) = &self.next {
+            next.accept(visitor);
+        }
+    }
+}
+
+impl FieldAccess {
+    pub(crate) fn new(field: String, next: Option<FieldAccess>) -> Self {
+        Self {
+            field,
+            next: next.map(Box::new),
+        }
+    }
+
+    pub(crate) fn parse(tokens: &mut VecDeque<TokenContainer>) -> ResultOption<Self> {
+        // <field_access> ::= T_DOT T_IDENTIFIER (<field_access> |)
+        if let some_token!(Token::Dot) = tokens.front() {
+            tokens.pop_front();
+            let field = expect(tokens, |token| matches!(token, Token::Identifier(_)));
+            if let some_token!(Token::Identifier(field)) = field {
+                if let some_token!(Token::LeftParenthesis) = tokens.front() {
+                    return Err(CustomError::unexpected_token(
+                        "The methods cannot be called yet",
+                        tokens.front(),
+                    ));
+                }
+                let next = FieldAccess::parse(tokens)?;
+                Ok(Some(FieldAccess::new(field, next)))
+            } else {
+                Err(CustomError::unexpected_token(
+                    "Expected the name of a field after the .",
+                    tokens.front(),
+                ))
+            }
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Read the chain of fields from `value`.
+    pub(crate) fn get_from(&self, value: VariableType) -> ShortResult<VariableType> {
+        let value = value.field(&self.field)?;
+        match &self.next {
+            Some(next) => next.get_from(value),
+            None => Ok(value),
+        }
+    }
+}
+
 // ------------
 // --- OpIn ---
 // ------------
 
 /// `OpIn` is used by nodes that represent a part of an identifier. It contains the next part of the
 /// chain of the identifier. It can be an [IdGet] node or a [CGet] node. The `OpIn` can also be
-/// empty if this is the last part of the identifier.
+/// empty if this is the last part of the identifier, an index in an array : `arr:0`, or the
+/// [FieldAccess] of the identifier : `o.field`.
 ///
 /// It will first try to parse the [CGet] node, if it fails, it will try to parse the [IdGet] node.
 /// If both fail, it will return an empty `OpIn`. Here, "fail" means that there is no parsing error,
 /// but that the token is not the one expected for an identifier.
-#[derive(PartialEq)]
+#[derive(PartialEq, Serialize)]
 pub enum OpIn {
     IdGet(IdGet),
     CGet(CGet),
+    Index(IntType),
+    Field(FieldAccess),
     Empty,
 }
 
 impl GraphDisplay for OpIn {
-    fn graph_display(&self, graph: &mut String, id: &mut usize) {
-        graph.push_str(&format!("\nsubgraph OpIn_{}[OpIn]", id));
+    fn graph_display(&self, graph: &mut Graph, id: &mut usize) {
+        graph.open("OpIn", "OpIn", *id);
         *id += 1;
         match self {
             OpIn::IdGet(id_get) => id_get.graph_display(graph, id),
             OpIn::CGet(c_get) => c_get.graph_display(graph, id),
+            OpIn::Index(index) => {
+                graph.open("Index", &format!("Index {}", index), *id);
+                *id += 1;
+                graph.close();
+            }
+            OpIn::Field(field_access) => field_access.graph_display(graph, id),
             OpIn::Empty => {}
         }
-        graph.push_str("\nend");
+        graph.close();
     }
 }
 
 impl_debug!(OpIn);
 
-pub(crate) fn parse_op_in(tokens: &mut VecDeque<TokenContainer>) -> skr_errors::ShortResult<OpIn> {
-    // <op_in> ::= (T_IN (<id_get> | <cget>) |)
+impl ToSource for OpIn {
+    fn to_source(&self) -> String {
+        match self {
+            OpIn::IdGet(id_get) => format!(":{}", id_get.to_source()),
+            OpIn::CGet(c_get) => format!(":{}", c_get.to_source()),
+            OpIn::Index(index) => format!(":{}", index),
+            OpIn::Field(field_access) => field_access.to_source(),
+            OpIn::Empty => String::new(),
+        }
+    }
+}
+
+impl FoldConstants for OpIn {
+    fn fold_constants(&mut self) {
+        if let OpIn::IdGet(id_get) = self {
+            id_get.fold_constants();
+        }
+    }
+}
+
+impl Visitable for OpIn {
+    fn accept<V: Visitor>(&self, visitor: &mut V) {
+        visitor.visit_op_in(self);
+        match self {
+            OpIn::IdGet(id_get) => id_get.accept(visitor),
+            OpIn::CGet(c_get) => c_get.accept(visitor),
+            OpIn::Field(field_access) => field_access.accept(visitor),
+            OpIn::Index(_) | OpIn::Empty => {}
+        }
+    }
+}
+
+pub(crate) fn parse_op_in(
+    tokens: &mut VecDeque<TokenContainer>,
+    context: &mut ParseContext,
+) -> skr_errors::ShortResult<OpIn> {
+    // <op_in> ::= (T_IN (<id_get> | <cget> | T_INT) | <field_access> |)
     if let some_token!(Token::Inside) = tokens.front() {
         tokens.pop_front();
-        if let Some(c_get) = parse_cget(tokens) {
+        if let some_token!(Token::Int(_)) = tokens.front() {
+            if let some_token!(Token::Int(index)) = tokens.pop_front() {
+                return Ok(OpIn::Index(index));
+            }
+        }
+        if let Some(c_get) = parse_cget(tokens, context) {
             Ok(OpIn::CGet(c_get))
-        } else if let Some(id_get) = IdGet::parse(tokens)? {
+        } else if let Some(id_get) = IdGet::parse(tokens, context)? {
             Ok(OpIn::IdGet(id_get))
         } else {
-            Err(CustomError::UnexpectedToken(
-                "Expected id_get or cget after \"indide\" token".to_string(),
+            Err(CustomError::unexpected_token(
+                "Expected id_get, cget or an index after \"indide\" token",
+                tokens.front(),
             ))
         }
+    } else if let Some(field_access) = FieldAccess::parse(tokens)? {
+        Ok(OpIn::Field(field_access))
     } else {
         Ok(OpIn::Empty)
     }
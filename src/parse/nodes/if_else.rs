@@ -1,10 +1,16 @@
+use crate::execute::variables::VariableType;
+use crate::execute::{Evaluate, OperationContext, OperationIO};
+use crate::parse::context::ParseContext;
 use crate::parse::nodes::blocs::Scope;
 use crate::parse::nodes::expressions::Exp;
-use crate::parse::nodes::GraphDisplay;
-use crate::skr_errors::{CustomError, ResultOption};
+use crate::parse::nodes::visitor::{Visitable, Visitor};
+use crate::parse::nodes::{FoldConstants, Graph, GraphDisplay, ToSource};
+use crate::skr_errors::{CustomError, ResultOption, ShortResult};
 use crate::tokens::{Token, TokenContainer};
 use crate::{impl_debug, some_token};
 use std::collections::VecDeque;
+
+use serde::Serialize;
 // Grammar for this file:
 // <sula> ::= sula (<ij> (<sula> |) | <scope>)
 // <ij> ::= ij <exp> <scope>
@@ -29,15 +35,15 @@ use std::collections::VecDeque;
 /// `<sula> ::= sula (<ij> (<sula> |) | <scope>)`
 ///
 /// See also [Ij] and [Scope].
-#[derive(PartialEq)]
+#[derive(PartialEq, Serialize)]
 pub enum Sula {
     Ij { ij: Ij, sula: Option<Box<Sula>> },
     Scope(Scope),
 }
 
 impl GraphDisplay for Sula {
-    fn graph_display(&self, graph: &mut String, id: &mut usize) {
-        graph.push_str(&format!("\nsubgraph Sula_{}[Sula]", id));
+    fn graph_display(&self, graph: &mut Graph, id: &mut usize) {
+        graph.open("Sula", "Sula", *id);
         *id += 1;
         match self {
             Sula::Ij { ij, sula } => {
@@ -50,12 +56,56 @@ impl GraphDisplay for Sula {
                 scope.graph_display(graph, id);
             }
         }
-        graph.push_str("\nend");
+        graph.close();
     }
 }
 
 impl_debug!(Sula);
 
+impl ToSource for Sula {
+    fn to_source(&self) -> String {
+        match self {
+            Sula::Ij {
+                ij,
+                sula: Some(sula),
+            } => {
+                format!("sula {} {}", ij.to_source(), sula.to_source())
+            }
+            Sula::Ij { ij, sula: None } => format!("sula {}", ij.to_source()),
+            Sula::Scope(scope) => format!("sula {}", scope.to_source()),
+        }
+    }
+}
+
+impl FoldConstants for Sula {
+    fn fold_constants(&mut self) {
+        match self {
+            Sula::Ij { ij, sula } => {
+                ij.fold_constants();
+                if let Some(sula) = sula {
+                    sula.fold_constants();
+                }
+            }
+            Sula::Scope(scope) => scope.fold_constants(),
+        }
+    }
+}
+
+impl Visitable for Sula {
+    fn accept<V: Visitor>(&self, visitor: &mut V) {
+        visitor.visit_sula(self);
+        match self {
+            Sula::Ij { ij, sula } => {
+                ij.accept(visitor);
+                if let Some(sula) = sula {
+                    sula.accept(visitor);
+                }
+            }
+            Sula::Scope(scope) => scope.accept(visitor),
+        }
+    }
+}
+
 impl Sula {
     pub fn new(ij: Ij, sula: Option<Sula>) -> Self {
         Self::Ij {
@@ -64,24 +114,31 @@ impl Sula {
         }
     }
 
-    pub fn parse(tokens: &mut VecDeque<TokenContainer>) -> ResultOption<Self> {
+    pub fn parse(
+        tokens: &mut VecDeque<TokenContainer>,
+        context: &mut ParseContext,
+    ) -> ResultOption<Self> {
         // <sula> ::= sula (<ij> (<sula> |) | <scope>)
         if let some_token!(Token::KeywordElse) = tokens.front() {
             tokens.pop_front();
-            if let Some(ij) = Ij::parse(tokens)? {
-                if let Some(sula) = Sula::parse(tokens)? {
+            if let Some(ij) = Ij::parse(tokens, context)? {
+                if let Some(sula) = Sula::parse(tokens, context)? {
                     Ok(Some(Sula::Ij {
                         ij,
                         sula: Some(Box::new(sula)),
                     }))
                 } else {
-                    Err(CustomError::UnexpectedToken("Expected a sula".to_string()))
+                    Err(CustomError::unexpected_token(
+                        "Expected a sula",
+                        tokens.front(),
+                    ))
                 }
-            } else if let Some(scope) = Scope::parse(tokens)? {
+            } else if let Some(scope) = Scope::parse(tokens, context)? {
                 Ok(Some(Sula::Scope(scope)))
             } else {
-                Err(CustomError::UnexpectedToken(
-                    "Expected an ij or a scope".to_string(),
+                Err(CustomError::unexpected_token(
+                    "Expected an ij or a scope",
+                    tokens.front(),
                 ))
             }
         } else {
@@ -103,40 +160,68 @@ impl Sula {
 /// `<ij> ::= ij <exp> <scope>`
 ///
 /// See also [Exp] and [Scope].
-#[derive(PartialEq)]
+#[derive(PartialEq, Serialize)]
 pub struct Ij {
     exp: Exp,
     scope: Scope,
 }
 
 impl GraphDisplay for Ij {
-    fn graph_display(&self, graph: &mut String, id: &mut usize) {
-        graph.push_str(&format!("\nsubgraph Ij_{}[Ij]", id));
+    fn graph_display(&self, graph: &mut Graph, id: &mut usize) {
+        graph.open("Ij", "Ij", *id);
         *id += 1;
         self.exp.graph_display(graph, id);
         self.scope.graph_display(graph, id);
-        graph.push_str("\nend");
+        graph.close();
     }
 }
 
 impl_debug!(Ij);
 
+impl ToSource for Ij {
+    fn to_source(&self) -> String {
+        format!("ij {} {}", self.exp.to_source(), self.scope.to_source())
+    }
+}
+
+impl FoldConstants for Ij {
+    fn fold_constants(&mut self) {
+        self.exp.fold_constants();
+        self.scope.fold_constants();
+    }
+}
+
+impl Visitable for Ij {
+    fn accept<V: Visitor>(&self, visitor: &mut V) {
+        visitor.visit_ij(self);
+        self.exp.accept(visitor);
+        self.scope.accept(visitor);
+    }
+}
+
 impl Ij {
     pub fn new(exp: Exp, scope: Scope) -> Self {
         Self { exp, scope }
     }
 
-    pub fn parse(tokens: &mut VecDeque<TokenContainer>) -> ResultOption<Self> {
+    pub fn parse(
+        tokens: &mut VecDeque<TokenContainer>,
+        context: &mut ParseContext,
+    ) -> ResultOption<Self> {
         // <ij> ::= ij <exp> <scope>
         if let some_token!(Token::KeywordIf) = tokens.front() {
             tokens.pop_front();
-            match Exp::parse(tokens)? {
-                Some(exp) => match Scope::parse(tokens)? {
+            match Exp::parse(tokens, context)? {
+                Some(exp) => match Scope::parse(tokens, context)? {
                     Some(scope) => Ok(Some(Ij::new(exp, scope))),
-                    None => Err(CustomError::UnexpectedToken("Expected a scope".to_string())),
+                    None => Err(CustomError::unexpected_token(
+                        "Expected a scope",
+                        tokens.front(),
+                    )),
                 },
-                None => Err(CustomError::UnexpectedToken(
-                    "Expected an expression".to_string(),
+                None => Err(CustomError::unexpected_token(
+                    "Expected an expression",
+                    tokens.front(),
                 )),
             }
         } else {
@@ -158,26 +243,54 @@ impl Ij {
 /// `<cond> ::= <ij> (<sula> |)`
 ///
 /// See also [Ij] and [Sula].
-#[derive(PartialEq)]
+#[derive(PartialEq, Serialize)]
 pub struct Cond {
     ij: Ij,
     sula: Option<Box<Sula>>,
 }
 
 impl GraphDisplay for Cond {
-    fn graph_display(&self, graph: &mut String, id: &mut usize) {
-        graph.push_str(&format!("\nsubgraph Cond_{}[Cond]", id));
+    fn graph_display(&self, graph: &mut Graph, id: &mut usize) {
+        graph.open("Cond", "Cond", *id);
         *id += 1;
         self.ij.graph_display(graph, id);
         if let Some(sula) = &self.sula {
             sula.graph_display(graph, id);
         }
-        graph.push_str("\nend");
+        graph.close();
     }
 }
 
 impl_debug!(Cond);
 
+impl ToSource for Cond {
+    fn to_source(&self) -> String {
+        match &self.sula {
+            Some(sula) => format!("{} {}", self.ij.to_source(), sula.to_source()),
+            None => self.ij.to_source(),
+        }
+    }
+}
+
+impl FoldConstants for Cond {
+    fn fold_constants(&mut self) {
+        self.ij.fold_constants();
+        if let Some(sula) = &mut self.sula {
+            sula.fold_constants();
+        }
+    }
+}
+
+impl Visitable for Cond {
+    fn accept<V: Visitor>(&self, visitor: &mut V) {
+        visitor.visit_cond(self);
+        self.ij.accept(visitor);
+        if let Some(sula) = &self.sula {
+            sula.accept(visitor);
+        }
+    }
+}
+
 impl Cond {
     pub fn new(ij: Ij, sula: Option<Sula>) -> Self {
         Self {
@@ -186,16 +299,68 @@ impl Cond {
         }
     }
 
-    pub fn parse(tokens: &mut VecDeque<TokenContainer>) -> ResultOption<Self> {
+    pub fn parse(
+        tokens: &mut VecDeque<TokenContainer>,
+        context: &mut ParseContext,
+    ) -> ResultOption<Self> {
         // <cond> ::= <ij> (<sula> |)
-        if let Some(ij) = Ij::parse(tokens)? {
-            if let Some(sula) = Sula::parse(tokens)? {
+        if let Some(ij) = Ij::parse(tokens, context)? {
+            if let Some(sula) = Sula::parse(tokens, context)? {
                 Ok(Some(Cond::new(ij, Some(sula))))
             } else {
                 Ok(Some(Cond::new(ij, None)))
             }
+        } else if let some_token!(Token::KeywordElse) = tokens.front() {
+            // a sula can only be parsed after an ij, in the same Cond
+            Err(CustomError::unexpected_token(
+                "Expected an ij before a sula",
+                tokens.front(),
+            ))
         } else {
             Ok(None)
         }
     }
 }
+
+impl Sula {
+    fn evaluate(&self, operation_context: &OperationContext) -> ShortResult<OperationIO> {
+        match self {
+            Sula::Ij { ij, sula } => match ij.evaluate(operation_context)? {
+                Some(value) => Ok(value),
+                None => match sula {
+                    Some(sula) => sula.evaluate(operation_context),
+                    None => Ok(VariableType::Null),
+                },
+            },
+            Sula::Scope(scope) => scope.evaluate(operation_context),
+        }
+    }
+}
+
+impl Ij {
+    /// Evaluate the scope if the condition is true. The value is `None` when the condition is
+    /// false, the next [Sula] must then be evaluated.
+    fn evaluate(&self, operation_context: &OperationContext) -> ShortResult<Option<OperationIO>> {
+        match self.exp.evaluate(operation_context)? {
+            VariableType::Boolean(true) => Ok(Some(self.scope.evaluate(operation_context)?)),
+            VariableType::Boolean(false) => Ok(None),
+            value => Err(CustomError::InvalidOperation(format!(
+                "The condition of an ij must be a bool, not a {}",
+                value.type_name()
+            ))),
+        }
+    }
+}
+
+/// The value of a [Cond] is the value of the scope evaluated, or null when no condition is true.
+impl Evaluate for Cond {
+    fn evaluate(&self, operation_context: &OperationContext) -> ShortResult<OperationIO> {
+        match self.ij.evaluate(operation_context)? {
+            Some(value) => Ok(value),
+            None => match &self.sula {
+                Some(sula) => sula.evaluate(operation_context),
+                None => Ok(VariableType::Null),
+            },
+        }
+    }
+}
@@ -0,0 +1,128 @@
+use std::collections::VecDeque;
+
+use serde::Serialize;
+
+use crate::execute::variables::VariableType;
+use crate::execute::{Evaluate, OperationContext, OperationIO};
+use crate::parse::context::ParseContext;
+use crate::parse::nodes::blocs::Scope;
+use crate::parse::nodes::expressions::Exp;
+use crate::parse::nodes::visitor::{Visitable, Visitor};
+use crate::parse::nodes::{FoldConstants, Graph, GraphDisplay, ToSource};
+use crate::skr_errors::{CustomError, ResultOption, ShortResult};
+use crate::tokens::{Token, TokenContainer};
+use crate::{impl_debug, some_token};
+
+// Grammar of this file :
+// <luba> ::= luba <exp> <scope>
+
+// ------------
+// --- Luba ---
+// ------------
+
+/// `Luba` represents a loop in the AST. The [Scope] is executed while the condition [Exp] is true,
+/// the condition being evaluated again before each iteration.
+///
+/// # Grammar
+///
+/// `<luba> ::= luba <exp> <scope>`
+///
+/// See also [Exp] and [Scope].
+#[derive(PartialEq, Serialize)]
+pub struct Luba {
+    exp: Exp,
+    scope: Scope,
+}
+
+impl GraphDisplay for Luba {
+    fn graph_display(&self, graph: &mut Graph, id: &mut usize) {
+        graph.open("Luba", "Luba", *id);
+        *id += 1;
+        self.exp.graph_display(graph, id);
+        self.scope.graph_display(graph, id);
+        graph.close();
+    }
+}
+
+impl_debug!(Luba);
+
+impl ToSource for Luba {
+    fn to_source(&self) -> String {
+        format!("luba {} {}", self.exp.to_source(), self.scope.to_source())
+    }
+}
+
+impl FoldConstants for Luba {
+    fn fold_constants(&mut self) {
+        self.exp.fold_constants();
+        self.scope.fold_constants();
+    }
+}
+
+impl Visitable for Luba {
+    fn accept<V: Visitor>(&self, visitor: &mut V) {
+        visitor.visit_luba(self);
+        self.exp.accept(visitor);
+        self.scope.accept(visitor);
+    }
+}
+
+impl Luba {
+    pub fn new(exp: Exp, scope: Scope) -> Self {
+        Self { exp, scope }
+    }
+
+    pub fn parse(
+        tokens: &mut VecDeque<TokenContainer>,
+        context: &mut ParseContext,
+    ) -> ResultOption<Self> {
+        // <luba> ::= luba <exp> <scope>
+        if let some_token!(Token::KeywordLoop) = tokens.front() {
+            tokens.pop_front();
+            match Exp::parse(tokens, context)? {
+                Some(exp) => match Scope::parse(tokens, context)? {
+                    Some(scope) => Ok(Some(Luba::new(exp, scope))),
+                    None => Err(CustomError::unexpected_token(
+                        "Expected a scope",
+                        tokens.front(),
+                    )),
+                },
+                None => Err(CustomError::unexpected_token(
+                    "Expected an expression",
+                    tokens.front(),
+                )),
+            }
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+impl Evaluate for Luba {
+    fn evaluate(&self, operation_context: &OperationContext) -> ShortResult<OperationIO> {
+        let mut iterations = 0;
+        loop {
+            match self.exp.evaluate(operation_context)? {
+                VariableType::Boolean(true) => {}
+                VariableType::Boolean(false) => return Ok(VariableType::Null),
+                value => {
+                    return Err(CustomError::InvalidOperation(format!(
+                        "The condition of a loop must be a bool, not a {}",
+                        value.type_name()
+                    )))
+                }
+            }
+            if iterations == operation_context.max_loop_iterations {
+                return Err(CustomError::InvalidOperation(format!(
+                    "The loop did not end after {} iterations",
+                    iterations
+                )));
+            }
+            self.scope.evaluate(operation_context)?;
+            if operation_context.is_returning() {
+                return Ok(VariableType::Null);
+            }
+            iterations += 1;
+        }
+    }
+}
@@ -0,0 +1,268 @@
+use std::collections::VecDeque;
+
+use serde::Serialize;
+
+use crate::execute::variables::VariableType;
+use crate::execute::{Evaluate, OperationContext, OperationIO};
+use crate::parse::context::ParseContext;
+use crate::parse::nodes::blocs::Scope;
+use crate::parse::nodes::expressions::Exp;
+use crate::parse::nodes::operations::ValueBase;
+use crate::parse::nodes::visitor::{Visitable, Visitor};
+use crate::parse::nodes::{skip_separators, FoldConstants, Graph, GraphDisplay, ToSource};
+use crate::skr_errors::{CustomError, ResultOption, ShortResult};
+use crate::tokens::{Token, TokenContainer};
+use crate::{impl_debug, some_token};
+
+// Grammar of this file :
+// <kasu_arm> ::= <value_base> <scope>
+// <kasu> ::= kasu <exp> T_LEFT_E {<sep>} {<kasu_arm> {<sep>}} (sula <scope> {<sep>} |) T_RIGHT_E
+
+// ---------------
+// --- KasuArm ---
+// ---------------
+
+/// `KasuArm` is one branch of a [Kasu] : the [Scope] is executed when the value of the matched
+/// expression is equal to the literal [ValueBase].
+///
+/// # Grammar
+///
+/// `<kasu_arm> ::= <value_base> <scope>`
+#[derive(PartialEq, Serialize)]
+pub struct KasuArm {
+    value: ValueBase,
+    scope: Scope,
+}
+
+impl GraphDisplay for KasuArm {
+    fn graph_display(&self, graph: &mut Graph, id: &mut usize) {
+        graph.open("KasuArm", "KasuArm", *id);
+        *id += 1;
+        self.value.graph_display(graph, id);
+        self.scope.graph_display(graph, id);
+        graph.close();
+    }
+}
+
+impl_debug!(KasuArm);
+
+impl ToSource for KasuArm {
+    fn to_source(&self) -> String {
+        format!("{} {}", self.value.to_source(), self.scope.to_source())
+    }
+}
+
+impl FoldConstants for KasuArm {
+    fn fold_constants(&mut self) {
+        self.scope.fold_constants();
+    }
+}
+
+impl Visitable for KasuArm {
+    fn accept<V: Visitor>(&self, visitor: &mut V) {
+        visitor.visit_kasu_arm(self);
+        self.value.accept(visitor);
+        self.scope.accept(visitor);
+    }
+}
+
+impl KasuArm {
+    pub fn new(value: ValueBase, scope: Scope) -> Self {
+        Self { value, scope }
+    }
+
+    pub fn parse(
+        tokens: &mut VecDeque<TokenContainer>,
+        context: &mut ParseContext,
+    ) -> ResultOption<Self> {
+        // <kasu_arm> ::= <value_base> <scope>
+        match ValueBase::parse(tokens) {
+            Some(value) => match Scope::parse(tokens, context)? {
+                Some(scope) => Ok(Some(KasuArm::new(value, scope))),
+                None => Err(CustomError::unexpected_token(
+                    "Expected a scope",
+                    tokens.front(),
+                )),
+            },
+            None => Ok(None),
+        }
+    }
+}
+
+// ------------
+// --- Kasu ---
+// ------------
+
+/// `Kasu` represents a match in the AST. The [Exp] is evaluated once, then the [Scope] of the first
+/// [KasuArm] with the same value is executed. The `sula` scope is executed when no arm matches.
+///
+/// # Grammar
+///
+/// `<kasu> ::= kasu <exp> T_LEFT_E {<sep>} {<kasu_arm> {<sep>}} (sula <scope> {<sep>} |) T_RIGHT_E`
+///
+/// See also [KasuArm] and [Scope].
+#[derive(PartialEq, Serialize)]
+pub struct Kasu {
+    exp: Exp,
+    arms: Vec<KasuArm>,
+    sula: Option<Scope>,
+}
+
+impl GraphDisplay for Kasu {
+    fn graph_display(&self, graph: &mut Graph, id: &mut usize) {
+        graph.open("Kasu", "Kasu", *id);
+        *id += 1;
+        self.exp.graph_display(graph, id);
+        for arm in &self.arms {
+            arm.graph_display(graph, id);
+        }
+        if let Some(sula) = &self.sula {
+            sula.graph_display(graph, id);
+        }
+        graph.close();
+    }
+}
+
+impl_debug!(Kasu);
+
+impl ToSource for Kasu {
+    fn to_source(&self) -> String {
+        let mut branches: Vec<String> = self.arms.iter().map(|arm| arm.to_source()).collect();
+        if let Some(sula) = &self.sula {
+            branches.push(format!("sula {}", sula.to_source()));
+        }
+        let mut source = format!("kasu {} {{", self.exp.to_source());
+        for line in branches.iter().flat_map(|branch| branch.lines()) {
+            source.push_str("\n    ");
+            source.push_str(line);
+        }
+        source.push_str("\n}");
+        source
+    }
+}
+
+impl FoldConstants for Kasu {
+    fn fold_constants(&mut self) {
+        self.exp.fold_constants();
+        for arm in &mut self.arms {
+            arm.fold_constants();
+        }
+        if let Some(sula) = &mut self.sula {
+            sula.fold_constants();
+        }
+    }
+}
+
+impl Visitable for Kasu {
+    fn accept<V: Visitor>(&self, visitor: &mut V) {
+        visitor.visit_kasu(self);
+        self.exp.accept(visitor);
+        for arm in &self.arms {
+            arm.accept(visitor);
+        }
+        if let Some(sula) = &self.sula {
+            sula.accept(visitor);
+        }
+    }
+}
+
+impl Kasu {
+    pub fn new(exp: Exp, arms: Vec<KasuArm>, sula: Option<Scope>) -> Self {
+        Self { exp, arms, sula }
+    }
+
+    pub fn parse(
+        tokens: &mut VecDeque<TokenContainer>,
+        context: &mut ParseContext,
+    ) -> ResultOption<Self> {
+        // <kasu> ::= kasu <exp> T_LEFT_E {<sep>} {<kasu_arm> {<sep>}} (sula <scope> {<sep>} |) T_RIGHT_E
+        if let some_token!(Token::KeywordMatch) = tokens.front() {
+            tokens.pop_front();
+            let exp = match Exp::parse(tokens, context)? {
+                Some(exp) => exp,
+                None => {
+                    return Err(CustomError::unexpected_token(
+                        "Expected an expression",
+                        tokens.front(),
+                    ))
+                }
+            };
+            if let some_token!(Token::LeftBrace) = tokens.front() {
+                tokens.pop_front();
+            } else {
+                return Err(CustomError::unexpected_token(
+                    "Expected a left curly bracket",
+                    tokens.front(),
+                ));
+            }
+
+            let mut arms = Vec::new();
+            skip_separators(tokens);
+            while let Some(arm) = KasuArm::parse(tokens, context)? {
+                arms.push(arm);
+                skip_separators(tokens);
+            }
+
+            let sula = if let some_token!(Token::KeywordElse) = tokens.front() {
+                tokens.pop_front();
+                match Scope::parse(tokens, context)? {
+                    Some(scope) => {
+                        skip_separators(tokens);
+                        Some(scope)
+                    }
+                    None => {
+                        return Err(CustomError::unexpected_token(
+                            "Expected a scope",
+                            tokens.front(),
+                        ))
+                    }
+                }
+            } else {
+                None
+            };
+
+            if let some_token!(Token::RightBrace) = tokens.front() {
+                tokens.pop_front();
+                Ok(Some(Kasu::new(exp, arms, sula)))
+            } else {
+                Err(CustomError::unexpected_token(
+                    "Expected a value, a sula or a right curly bracket",
+                    tokens.front(),
+                ))
+            }
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+/// The value of a [Kasu] is the value of the scope evaluated, or null when no arm matches and
+/// there is no `sula`. Without `sula`, a bool must be matched by both `io` and `no`.
+impl Evaluate for Kasu {
+    fn evaluate(&self, operation_context: &OperationContext) -> ShortResult<OperationIO> {
+        let value = self.exp.evaluate(operation_context)?;
+        if let (VariableType::Boolean(_), None) = (&value, &self.sula) {
+            for expected in [true, false] {
+                if !self
+                    .arms
+                    .iter()
+                    .any(|arm| arm.value == ValueBase::Bool(expected))
+                {
+                    return Err(CustomError::InvalidOperation(format!(
+                        "The kasu on a bool has no arm for {}, add it or add a sula",
+                        ValueBase::Bool(expected).to_source()
+                    )));
+                }
+            }
+        }
+        for arm in &self.arms {
+            if arm.value.evaluate(operation_context)? == value {
+                return arm.scope.evaluate(operation_context);
+            }
+        }
+        match &self.sula {
+            Some(sula) => sula.evaluate(operation_context),
+            None => Ok(VariableType::Null),
+        }
+    }
+}
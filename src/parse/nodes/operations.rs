@@ -1,18 +1,30 @@
-use crate::execute::{Evaluate, EvaluateFromInput, IntType, OperationContext, OperationIO};
-use crate::parse::nodes::expressions::{Exp, ExpBase};
-use crate::parse::nodes::operations::Operations::{Add, Div, Equal, Mul, NotEqual, Sub};
-use crate::parse::nodes::{GraphDisplay, Parsable, ParsableWithLevel};
-use crate::skr_errors::CustomError::UnexpectedToken;
-use crate::skr_errors::{CustomError, ResultOption};
+use crate::execute::variables::VariableType;
+use crate::execute::{
+    Evaluate, EvaluateFromInput, FloatType, IntType, OperationContext, OperationIO,
+};
+use crate::parse::context::ParseContext;
+use crate::parse::nodes::expressions::{parse_exp_list, parse_right_parenthesis, Exp, ExpBase};
+use crate::parse::nodes::operations::Operations::{
+    Add, Div, Equal, Greater, GreaterEqual, Less, LessEqual, Mod, Mul, NotEqual, Pow, Sub,
+};
+use crate::parse::nodes::visitor::{Visitable, Visitor};
+use crate::parse::nodes::{
+    join_source, FoldConstants, Graph, GraphDisplay, Parsable, ParsableWithLevel, ToSource,
+};
+use crate::skr_errors::{CustomError, ResultOption, ShortResult};
 use crate::tokens::{Token, TokenContainer};
 use crate::{impl_debug, some_token};
 use std::collections::VecDeque;
+
+use serde::Serialize;
 // This file is pretty long
 // Start of grammar for this file :
 // ```
-// <value_base> ::= T_BOOL | T_INT | T_STRING | T_FLOAT
+// <value_base> ::= T_BOOL | T_INT | T_STRING | T_FLOAT | T_CHAR | T_NULL
+// <array> ::= T_LEFT_B (<exp> {T_COMMA <exp>} |) T_RIGHT_B
 // <value> ::=
 //   <value_base>
+//   | <array>
 //   | <exp_base>
 // <take_prio> ::=
 //   T_LEFT_P <exp> T_RIGHT_P
@@ -24,53 +36,79 @@ use std::collections::VecDeque;
 // -----------------
 
 /// `ValueBase` represents the base of a value in the AST. This is the smallest unit of a value.
-/// This node is not dependent on any other node. The value can be a boolean, an integer, a float or
-/// a string.
-#[derive(PartialEq)]
+/// This node is not dependent on any other node. The value can be a boolean, an integer, a float, a
+/// string, a char or `nul`.
+#[derive(PartialEq, Serialize)]
 pub enum ValueBase {
     Bool(bool),
     Int(IntType),
-    Float(f32),
+    Float(FloatType),
     String(String),
+    Char(char),
+    Null,
 }
 
 impl GraphDisplay for ValueBase {
-    fn graph_display(&self, graph: &mut String, id: &mut usize) {
-        match self {
-            ValueBase::Bool(value) => {
-                graph.push_str(&format!(
-                    "\nsubgraph ValueBase_{}[ValueBase Bool {}]\nend",
-                    id, value
-                ));
-            }
-            ValueBase::Int(value) => {
-                graph.push_str(&format!(
-                    "\nsubgraph ValueBase_{}[ValueBase Int {}]\nend",
-                    id, value
-                ));
-            }
-            ValueBase::Float(value) => {
-                graph.push_str(&format!(
-                    "\nsubgraph ValueBase_{}[ValueBase Float {}]\nend",
-                    id, value
-                ));
-            }
-            ValueBase::String(value) => {
-                graph.push_str(&format!(
-                    "\nsubgraph ValueBase_{}[ValueBase String {}]\nend",
-                    id, value
-                ));
-            }
-        }
+    fn graph_display(&self, graph: &mut Graph, id: &mut usize) {
+        let label = match self {
+            ValueBase::Bool(value) => format!("ValueBase Bool {}", value),
+            ValueBase::Int(value) => format!("ValueBase Int {}", value),
+            ValueBase::Float(value) => format!("ValueBase Float {}", value),
+            // the text is escaped like in the code
+            ValueBase::String(_) => format!("ValueBase String {}", self.to_source()),
+            ValueBase::Char(_) => format!("ValueBase Char {}", self.to_source()),
+            ValueBase::Null => String::from("ValueBase Null"),
+        };
+        graph.open("ValueBase", &label, *id);
         *id += 1;
+        graph.close();
     }
 }
 
 impl_debug!(ValueBase);
 
+impl ToSource for ValueBase {
+    fn to_source(&self) -> String {
+        match self {
+            ValueBase::Bool(true) => String::from("io"),
+            ValueBase::Bool(false) => String::from("no"),
+            ValueBase::Int(value) => value.to_string(),
+            // the debug format always keeps a dot or an exponent : 5.0 and not 5
+            ValueBase::Float(value) => format!("{:?}", value),
+            ValueBase::String(value) => format!("\"{}\"", escape(value)),
+            ValueBase::Char(value) => format!("'{}'", escape(&value.to_string())),
+            ValueBase::Null => String::from("nul"),
+        }
+    }
+}
+
+impl Visitable for ValueBase {
+    fn accept<V: Visitor>(&self, visitor: &mut V) {
+        visitor.visit_value_base(self);
+    }
+}
+
+/// Escape the characters that cannot be written as is in a string or a char literal.
+fn escape(text: &str) -> String {
+    let mut escaped = String::new();
+    for ch in text.chars() {
+        match ch {
+            '\\' => escaped.push_str("\\\\"),
+            '"' => escaped.push_str("\\\""),
+            '\'' => escaped.push_str("\\'"),
+            '\n' => escaped.push_str("\\n"),
+            '\t' => escaped.push_str("\\t"),
+            '\r' => escaped.push_str("\\r"),
+            '\0' => escaped.push_str("\\0"),
+            _ => escaped.push(ch),
+        }
+    }
+    escaped
+}
+
 impl ValueBase {
     pub fn parse(tokens: &mut VecDeque<TokenContainer>) -> Option<Self> {
-        // <value_base> ::= T_BOOL | T_INT | T_STRING | T_FLOAT
+        // <value_base> ::= T_BOOL | T_INT | T_STRING | T_FLOAT | T_CHAR | T_NULL
         match tokens.front() {
             some_token!(Token::Bool(_)) => {
                 if let some_token!(Token::Bool(value)) = tokens.pop_front() {
@@ -100,64 +138,205 @@ impl ValueBase {
                     None
                 }
             }
+            some_token!(Token::Char(_)) => {
+                if let some_token!(Token::Char(value)) = tokens.pop_front() {
+                    Some(Self::Char(value))
+                } else {
+                    None
+                }
+            }
+            some_token!(Token::Null) => {
+                tokens.pop_front();
+                Some(Self::Null)
+            }
             _ => None,
         }
     }
 }
 
-impl Evaluate for ValueBase {
-    fn evaluate(&self, _operation_context: &OperationContext) -> OperationIO {
+impl ValueBase {
+    /// The value of the literal, known without any context.
+    pub(crate) fn value(&self) -> VariableType {
         match self {
-            ValueBase::Int(value) => *value,
-            _ => todo!(),
+            ValueBase::Bool(value) => VariableType::Boolean(*value),
+            ValueBase::Int(value) => VariableType::Integer(*value),
+            ValueBase::Float(value) => VariableType::Float(*value),
+            ValueBase::String(value) => VariableType::String(value.clone()),
+            ValueBase::Char(value) => VariableType::Char(*value),
+            ValueBase::Null => VariableType::Null,
         }
     }
 }
 
+impl Evaluate for ValueBase {
+    fn evaluate(&self, _operation_context: &OperationContext) -> ShortResult<OperationIO> {
+        Ok(self.value())
+    }
+}
+
+// -------------
+// --- Array ---
+// -------------
+
+/// `ArrayNode` represents an array literal : a list of [Exp] separated by commas between brackets,
+/// like `[1, 2, 3]`.
+#[derive(PartialEq, Serialize)]
+pub struct ArrayNode {
+    elements: Vec<Exp>,
+}
+
+impl GraphDisplay for ArrayNode {
+    fn graph_display(&self, graph: &mut Graph, id: &mut usize) {
+        graph.open("ArrayNode", "Array", *id);
+        *id += 1;
+        for element in &self.elements {
+            element.graph_display(graph, id);
+        }
+        graph.close();
+    }
+}
+
+impl_debug!(ArrayNode);
+
+impl ToSource for ArrayNode {
+    fn to_source(&self) -> String {
+        format!("[{}]", join_source(self.elements.iter(), ", "))
+    }
+}
+
+impl FoldConstants for ArrayNode {
+    fn fold_constants(&mut self) {
+        for element in &mut self.elements {
+            element.fold_constants();
+        }
+    }
+}
+
+impl Visitable for ArrayNode {
+    fn accept<V: Visitor>(&self, visitor: &mut V) {
+        visitor.visit_array(self);
+        for element in &self.elements {
+            element.accept(visitor);
+        }
+    }
+}
+
+impl ArrayNode {
+    pub fn new(elements: Vec<Exp>) -> Self {
+        Self { elements }
+    }
+
+    pub fn parse(
+        tokens: &mut VecDeque<TokenContainer>,
+        context: &mut ParseContext,
+    ) -> ResultOption<Self> {
+        // <array> ::= T_LEFT_B (<exp> {T_COMMA <exp>} |) T_RIGHT_B
+        if let some_token!(Token::LeftBracket) = tokens.front() {
+            tokens.pop_front();
+            let elements = parse_exp_list(tokens, context, Token::RightBracket, "right bracket")?;
+            Ok(Some(ArrayNode::new(elements)))
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+impl Evaluate for ArrayNode {
+    fn evaluate(&self, operation_context: &OperationContext) -> ShortResult<OperationIO> {
+        let elements = self
+            .elements
+            .iter()
+            .map(|element| element.evaluate(operation_context))
+            .collect::<ShortResult<Vec<_>>>()?;
+        Ok(VariableType::Array(elements))
+    }
+}
+
 // -------------
 // --- Value ---
 // -------------
 
 /// `ValueNode` represents any value that has a priority over many nodes. This node cannot be
-/// mistaken with a wrong node because the syntax is clear. This node is either a [ValueBase] or an
-/// [ExpBase].
+/// mistaken with a wrong node because the syntax is clear. This node is either a [ValueBase], an
+/// [ArrayNode] or an [ExpBase].
 ///
 /// [ValueNode] and [ExpBase] have in common that all their possibles values start with a token that
 /// can only mean one thing. Example : `T_BOOL` can only be a boolean, `biuli` can only mean that
 /// this is a special scope.
-#[derive(PartialEq)]
+#[derive(PartialEq, Serialize)]
 pub enum ValueNode {
     ValueBase(ValueBase),
+    Array(ArrayNode),
     ExpBase(ExpBase),
 }
 
 impl GraphDisplay for ValueNode {
-    fn graph_display(&self, graph: &mut String, id: &mut usize) {
-        graph.push_str(&format!("\nsubgraph ValueNode_{}[ValueNode]", id));
+    fn graph_display(&self, graph: &mut Graph, id: &mut usize) {
+        graph.open("ValueNode", "ValueNode", *id);
         *id += 1;
         match self {
             ValueNode::ValueBase(value) => {
                 value.graph_display(graph, id);
             }
+            ValueNode::Array(value) => {
+                value.graph_display(graph, id);
+            }
             ValueNode::ExpBase(value) => {
                 value.graph_display(graph, id);
             }
         }
-        graph.push_str("\nend");
+        graph.close();
     }
 }
 
 impl_debug!(ValueNode);
 
+impl ToSource for ValueNode {
+    fn to_source(&self) -> String {
+        match self {
+            ValueNode::ValueBase(value_base) => value_base.to_source(),
+            ValueNode::Array(array) => array.to_source(),
+            ValueNode::ExpBase(exp_base) => exp_base.to_source(),
+        }
+    }
+}
+
+impl FoldConstants for ValueNode {
+    fn fold_constants(&mut self) {
+        match self {
+            ValueNode::ValueBase(_) => {}
+            ValueNode::Array(array) => array.fold_constants(),
+            ValueNode::ExpBase(exp_base) => exp_base.fold_constants(),
+        }
+    }
+}
+
+impl Visitable for ValueNode {
+    fn accept<V: Visitor>(&self, visitor: &mut V) {
+        visitor.visit_value_node(self);
+        match self {
+            ValueNode::ValueBase(value_base) => value_base.accept(visitor),
+            ValueNode::Array(array) => array.accept(visitor),
+            ValueNode::ExpBase(exp_base) => exp_base.accept(visitor),
+        }
+    }
+}
+
 impl ValueNode {
-    pub fn parse(tokens: &mut VecDeque<TokenContainer>) -> ResultOption<Self> {
+    pub fn parse(
+        tokens: &mut VecDeque<TokenContainer>,
+        context: &mut ParseContext,
+    ) -> ResultOption<Self> {
         // <value> ::=
         //   <value_base>
+        //   | <array>
         //   | <exp_base>
         if let Some(value_base) = ValueBase::parse(tokens) {
             Ok(Some(ValueNode::ValueBase(value_base)))
+        } else if let Some(array) = ArrayNode::parse(tokens, context)? {
+            Ok(Some(ValueNode::Array(array)))
         } else {
-            match ExpBase::parse(tokens)? {
+            match ExpBase::parse(tokens, context)? {
                 Some(exp_base) => Ok(Some(ValueNode::ExpBase(exp_base))),
                 None => Ok(None),
             }
@@ -166,10 +345,11 @@ impl ValueNode {
 }
 
 impl Evaluate for ValueNode {
-    fn evaluate(&self, _operation_context: &OperationContext) -> OperationIO {
+    fn evaluate(&self, _operation_context: &OperationContext) -> ShortResult<OperationIO> {
         match self {
             ValueNode::ValueBase(base) => base.evaluate(_operation_context),
-            ValueNode::ExpBase(_) => todo!(),
+            ValueNode::Array(array) => array.evaluate(_operation_context),
+            ValueNode::ExpBase(exp_base) => exp_base.evaluate(_operation_context),
         }
     }
 }
@@ -180,15 +360,15 @@ impl Evaluate for ValueNode {
 
 /// `TakePriority` represents either a [ValueNode] or an [Exp]. This node is used to give a priority
 /// to a value. It can detect [Exp] only between parenthesis : this takes priority over everything.
-#[derive(PartialEq)]
+#[derive(PartialEq, Serialize)]
 pub enum TakePriority {
     Exp(Box<Exp>),
     Value(ValueNode),
 }
 
 impl GraphDisplay for TakePriority {
-    fn graph_display(&self, graph: &mut String, id: &mut usize) {
-        graph.push_str(&format!("\nsubgraph TakePriority_{}[TakePriority]", id));
+    fn graph_display(&self, graph: &mut Graph, id: &mut usize) {
+        graph.open("TakePriority", "TakePriority", *id);
         *id += 1;
         match self {
             TakePriority::Exp(value) => {
@@ -198,33 +378,61 @@ impl GraphDisplay for TakePriority {
                 value.graph_display(graph, id);
             }
         }
-        graph.push_str("\nend");
+        graph.close();
     }
 }
 
 impl_debug!(TakePriority);
 
+impl ToSource for TakePriority {
+    fn to_source(&self) -> String {
+        match self {
+            TakePriority::Exp(exp) => format!("({})", exp.to_source()),
+            TakePriority::Value(value) => value.to_source(),
+        }
+    }
+}
+
+impl FoldConstants for TakePriority {
+    fn fold_constants(&mut self) {
+        match self {
+            TakePriority::Exp(exp) => exp.fold_constants(),
+            TakePriority::Value(value) => value.fold_constants(),
+        }
+    }
+}
+
+impl Visitable for TakePriority {
+    fn accept<V: Visitor>(&self, visitor: &mut V) {
+        visitor.visit_take_priority(self);
+        match self {
+            TakePriority::Exp(exp) => exp.accept(visitor),
+            TakePriority::Value(value) => value.accept(visitor),
+        }
+    }
+}
+
 impl TakePriority {
-    pub fn parse(tokens: &mut VecDeque<TokenContainer>) -> ResultOption<Self> {
+    pub fn parse(
+        tokens: &mut VecDeque<TokenContainer>,
+        context: &mut ParseContext,
+    ) -> ResultOption<Self> {
         // <take_prio> ::=
         //   T_LEFT_P <exp> T_RIGHT_P
         //   | <value>
-        let front = tokens.front();
-        if let some_token!(Token::LeftParenthesis) = front {
-            tokens.pop_front();
-            match Exp::parse(tokens)? {
+        if let some_token!(Token::LeftParenthesis) = tokens.front() {
+            let opening = tokens.pop_front().expect("the front token exists");
+            match Exp::parse(tokens, context)? {
                 Some(exp) => {
-                    if let some_token!(Token::RightParenthesis) = tokens.pop_front() {
-                        Ok(Some(TakePriority::Exp(Box::new(exp))))
-                    } else {
-                        Err(UnexpectedToken("Expected a right parenthesis".to_string()))
-                    }
+                    parse_right_parenthesis(tokens, &opening)?;
+                    Ok(Some(TakePriority::Exp(Box::new(exp))))
                 }
-                None => Err(CustomError::UnexpectedToken(
-                    "Expected an expression".to_string(),
+                None => Err(CustomError::unexpected_token(
+                    "Expected an expression",
+                    tokens.front(),
                 )),
             }
-        } else if let Some(value) = ValueNode::parse(tokens)? {
+        } else if let Some(value) = ValueNode::parse(tokens, context)? {
             Ok(Some(TakePriority::Value(value)))
         } else {
             Ok(None)
@@ -233,9 +441,9 @@ impl TakePriority {
 }
 
 impl Evaluate for TakePriority {
-    fn evaluate(&self, operation_context: &OperationContext) -> OperationIO {
+    fn evaluate(&self, operation_context: &OperationContext) -> ShortResult<OperationIO> {
         match self {
-            TakePriority::Exp(_) => todo!(),
+            TakePriority::Exp(exp) => exp.evaluate(operation_context),
             TakePriority::Value(value) => value.evaluate(operation_context),
         }
     }
@@ -248,7 +456,7 @@ impl Evaluate for TakePriority {
 /// `UnaryTP` represents a chain (0 or more elements) of unary operators before a [TakePriority].
 ///
 /// The unary operators are : `+`, `-` and `!`. Example : `+ -+ ![TakePriority]` is an [UnaryTP].
-#[derive(PartialEq)]
+#[derive(PartialEq, Serialize)]
 pub enum UnaryTP {
     Plus(Box<UnaryTP>),
     Minus(Box<UnaryTP>),
@@ -257,8 +465,14 @@ pub enum UnaryTP {
 }
 
 impl GraphDisplay for UnaryTP {
-    fn graph_display(&self, graph: &mut String, id: &mut usize) {
-        graph.push_str(&format!("\nsubgraph UnaryTP_{}[unary_tp]", id));
+    fn graph_display(&self, graph: &mut Graph, id: &mut usize) {
+        let sign = match self {
+            UnaryTP::Plus(_) => " +",
+            UnaryTP::Minus(_) => " -",
+            UnaryTP::Not(_) => " !",
+            UnaryTP::TakePriority(_) => "",
+        };
+        graph.open("UnaryTP", &format!("unary_tp{}", sign), *id);
         *id += 1;
         match self {
             UnaryTP::Plus(unary_tp) | UnaryTP::Minus(unary_tp) | UnaryTP::Not(unary_tp) => {
@@ -268,37 +482,76 @@ impl GraphDisplay for UnaryTP {
                 take_priority.graph_display(graph, id);
             }
         }
-        graph.push_str("\nend");
+        graph.close();
     }
 }
 
 impl_debug!(UnaryTP);
 
+impl ToSource for UnaryTP {
+    fn to_source(&self) -> String {
+        match self {
+            UnaryTP::Plus(unary) => format!("+{}", unary.to_source()),
+            UnaryTP::Minus(unary) => format!("-{}", unary.to_source()),
+            UnaryTP::Not(unary) => format!("!{}", unary.to_source()),
+            UnaryTP::TakePriority(take_priority) => take_priority.to_source(),
+        }
+    }
+}
+
+impl FoldConstants for UnaryTP {
+    fn fold_constants(&mut self) {
+        match self {
+            UnaryTP::Plus(unary_tp) | UnaryTP::Minus(unary_tp) | UnaryTP::Not(unary_tp) => {
+                unary_tp.fold_constants()
+            }
+            UnaryTP::TakePriority(take_priority) => take_priority.fold_constants(),
+        }
+    }
+}
+
+impl Visitable for UnaryTP {
+    fn accept<V: Visitor>(&self, visitor: &mut V) {
+        visitor.visit_unary_tp(self);
+        match self {
+            UnaryTP::Plus(unary_tp) | UnaryTP::Minus(unary_tp) | UnaryTP::Not(unary_tp) => {
+                unary_tp.accept(visitor)
+            }
+            UnaryTP::TakePriority(take_priority) => take_priority.accept(visitor),
+        }
+    }
+}
+
 macro_rules! extract_unary {
-    ($ret:path, $tokens: ident) => {{
+    ($ret:path, $tokens: ident, $context: ident) => {{
         $tokens.pop_front();
-        let unary_tp = UnaryTP::parse($tokens)?;
-        match unary_tp {
+        // each unary operator is a nested expression
+        let mut context = $context.nest($tokens.front())?;
+        match UnaryTP::parse($tokens, &mut context)? {
             Some(unary_tp) => Ok(Some($ret(Box::new(unary_tp)))),
-            None => Err(CustomError::UnexpectedToken(
-                "Expected an unary_tp".to_string(),
+            None => Err(CustomError::unexpected_token(
+                "Expected an unary_tp",
+                $tokens.front(),
             )),
         }
     }};
 }
 
 impl Parsable for UnaryTP {
-    fn parse(tokens: &mut VecDeque<TokenContainer>) -> ResultOption<Self> {
+    fn parse(
+        tokens: &mut VecDeque<TokenContainer>,
+        context: &mut ParseContext,
+    ) -> ResultOption<Self> {
         // <tp> ::=
         //   (T_PLUS | T_MINUS | T_NOT) <tp>
         //   | <take_prio>
         let front = tokens.front();
         match front {
-            some_token!(Token::Add) => extract_unary!(UnaryTP::Plus, tokens),
-            some_token!(Token::Sub) => extract_unary!(UnaryTP::Minus, tokens),
-            some_token!(Token::Not) => extract_unary!(UnaryTP::Not, tokens),
+            some_token!(Token::Add) => extract_unary!(UnaryTP::Plus, tokens, context),
+            some_token!(Token::Sub) => extract_unary!(UnaryTP::Minus, tokens, context),
+            some_token!(Token::Not) => extract_unary!(UnaryTP::Not, tokens, context),
             _ => {
-                if let Some(take_priority) = TakePriority::parse(tokens)? {
+                if let Some(take_priority) = TakePriority::parse(tokens, context)? {
                     Ok(Some(UnaryTP::TakePriority(take_priority)))
                 } else {
                     Ok(None)
@@ -308,50 +561,252 @@ impl Parsable for UnaryTP {
     }
 }
 
+impl UnaryTP {
+    /// Apply the unary operator to the `value` of the operand.
+    fn apply(&self, value: VariableType) -> ShortResult<VariableType> {
+        let symbol = match self {
+            UnaryTP::Plus(_) => "+",
+            UnaryTP::Minus(_) => "-",
+            UnaryTP::Not(_) => "!",
+            UnaryTP::TakePriority(_) => return Ok(value),
+        };
+        match (self, value) {
+            (UnaryTP::Plus(_), value @ (VariableType::Integer(_) | VariableType::Float(_))) => {
+                Ok(value)
+            }
+            (UnaryTP::Minus(_), VariableType::Integer(value)) => match value.checked_neg() {
+                Some(value) => Ok(VariableType::Integer(value)),
+                None => Err(CustomError::IntegerOverflow(format!("-({})", value))),
+            },
+            (UnaryTP::Minus(_), VariableType::Float(value)) => Ok(VariableType::Float(-value)),
+            (UnaryTP::Not(_), VariableType::Boolean(value)) => Ok(VariableType::Boolean(!value)),
+            (_, value) => Err(CustomError::InvalidOperation(format!(
+                "Cannot apply {} to a {}",
+                symbol,
+                value.type_name()
+            ))),
+        }
+    }
+}
+
 impl Evaluate for UnaryTP {
-    fn evaluate(&self, _operation_context: &OperationContext) -> OperationIO {
+    fn evaluate(&self, operation_context: &OperationContext) -> ShortResult<OperationIO> {
         match self {
-            UnaryTP::Plus(unary_tp) => unary_tp.evaluate(_operation_context),
-            UnaryTP::TakePriority(take_priority) => take_priority.evaluate(_operation_context),
-            _ => todo!(),
+            UnaryTP::Plus(unary_tp) | UnaryTP::Minus(unary_tp) | UnaryTP::Not(unary_tp) => {
+                self.apply(unary_tp.evaluate(operation_context)?)
+            }
+            UnaryTP::TakePriority(take_priority) => take_priority.evaluate(operation_context),
         }
     }
 }
 
-#[derive(PartialEq)]
+#[derive(PartialEq, Serialize)]
 pub enum Operations {
+    Pow,
     Mul,
     Div,
+    Mod,
     Add,
     Sub,
     Equal,
     NotEqual,
+    Less,
+    Greater,
+    LessEqual,
+    GreaterEqual,
     And,
     Or,
 }
 
-const HIGHEST_LEVEL: u8 = 5;
+impl Operations {
+    /// The symbol of the operation, as written in the code.
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            Pow => "**",
+            Mul => "*",
+            Div => "/",
+            Mod => "%",
+            Add => "+",
+            Sub => "-",
+            Equal => "==",
+            NotEqual => "!=",
+            Less => "<",
+            Greater => ">",
+            LessEqual => "<=",
+            GreaterEqual => ">=",
+            Operations::And => "&&",
+            Operations::Or => "||",
+        }
+    }
+
+    /// Apply the operation to two values that are already evaluated.
+    ///
+    /// An integer used with a float is promoted to a float and the result is a float, while two
+    /// integers give an integer (`7 / 2` is `3`), except for a power with a negative exponent. `==`
+    /// and `!=` compare values of the same type, while `<`, `>`, `<=` and `>=` only order the
+    /// values with [VariableType::compare]. `&&` and `||` only accept booleans and `+` also
+    /// concatenates strings. Null is only accepted by `==` and `!=` : it is only equal to itself, so a
+    /// program can test the end of the input of `read_line`.
+    pub fn apply(&self, left: VariableType, right: VariableType) -> ShortResult<VariableType> {
+        match (left, right) {
+            (left @ VariableType::Null, right) | (left, right @ VariableType::Null)
+                if matches!(self, Equal | NotEqual) =>
+            {
+                Ok(VariableType::Boolean((left == right) == (*self == Equal)))
+            }
+            (left @ VariableType::Null, right) | (left, right @ VariableType::Null) => {
+                Err(self.incompatible(left.type_name(), right.type_name()))
+            }
+            (left, right) if matches!(self, Less | Greater | LessEqual | GreaterEqual) => {
+                self.order(left, right)
+            }
+            (VariableType::Integer(left), VariableType::Integer(right)) => {
+                self.apply_int(left, right)
+            }
+            (VariableType::Float(left), VariableType::Float(right)) => {
+                self.apply_float(left, right)
+            }
+            (VariableType::Integer(left), VariableType::Float(right)) => {
+                self.apply_float(left as FloatType, right)
+            }
+            (VariableType::Float(left), VariableType::Integer(right)) => {
+                self.apply_float(left, right as FloatType)
+            }
+            (VariableType::Boolean(left), VariableType::Boolean(right)) => match self {
+                Operations::And => Ok(VariableType::Boolean(left && right)),
+                Operations::Or => Ok(VariableType::Boolean(left || right)),
+                Equal => Ok(VariableType::Boolean(left == right)),
+                NotEqual => Ok(VariableType::Boolean(left != right)),
+                _ => Err(self.incompatible("bool", "bool")),
+            },
+            (VariableType::String(left), VariableType::String(right)) if *self == Add => {
+                Ok(VariableType::String(left + &right))
+            }
+            (left, right)
+                if std::mem::discriminant(&left) == std::mem::discriminant(&right)
+                    && (*self == Equal || *self == NotEqual) =>
+            {
+                Ok(VariableType::Boolean((left == right) == (*self == Equal)))
+            }
+            (left, right) => Err(self.incompatible(left.type_name(), right.type_name())),
+        }
+    }
+
+    fn apply_int(&self, left: IntType, right: IntType) -> ShortResult<VariableType> {
+        let result = match self {
+            // 2 ** -1 is 0.5, it cannot be an int
+            Pow if right < 0 => return self.apply_float(left as FloatType, right as FloatType),
+            Pow => u32::try_from(right)
+                .ok()
+                .and_then(|right| left.checked_pow(right)),
+            Div | Mod if right == 0 => {
+                return Err(CustomError::InvalidOperation(
+                    "Division by zero".to_string(),
+                ))
+            }
+            Add => left.checked_add(right),
+            Sub => left.checked_sub(right),
+            Mul => left.checked_mul(right),
+            Div => left.checked_div(right),
+            Mod => left.checked_rem(right),
+            Equal | NotEqual => return Ok(self.equal(&left, &right)),
+            // the orderings are applied before, like for the other types
+            Less | Greater | LessEqual | GreaterEqual | Operations::And | Operations::Or => {
+                return Err(self.incompatible("int", "int"))
+            }
+        };
+        match result {
+            Some(result) => Ok(VariableType::Integer(result)),
+            None => Err(CustomError::IntegerOverflow(format!(
+                "{} {} {}",
+                left,
+                self.symbol(),
+                right
+            ))),
+        }
+    }
+
+    fn apply_float(&self, left: FloatType, right: FloatType) -> ShortResult<VariableType> {
+        match self {
+            Div | Mod if right == 0.0 => Err(CustomError::InvalidOperation(
+                "Division by zero".to_string(),
+            )),
+            Pow => Ok(VariableType::Float(left.powf(right))),
+            Add => Ok(VariableType::Float(left + right)),
+            Sub => Ok(VariableType::Float(left - right)),
+            Mul => Ok(VariableType::Float(left * right)),
+            Div => Ok(VariableType::Float(left / right)),
+            Mod => Ok(VariableType::Float(left % right)),
+            Equal | NotEqual => Ok(self.equal(&left, &right)),
+            Less | Greater | LessEqual | GreaterEqual | Operations::And | Operations::Or => {
+                Err(self.incompatible("float", "float"))
+            }
+        }
+    }
+
+    /// The result of `==` or `!=` between two numbers of the same type.
+    fn equal<T: PartialEq>(&self, left: &T, right: &T) -> VariableType {
+        VariableType::Boolean((left == right) == (*self == Equal))
+    }
+
+    /// The result of `<`, `>`, `<=` or `>=`, the values that cannot be ordered are an error.
+    fn order(&self, left: VariableType, right: VariableType) -> ShortResult<VariableType> {
+        let result = match (left.compare(&right), self) {
+            (Some(ordering), Less) => ordering.is_lt(),
+            (Some(ordering), Greater) => ordering.is_gt(),
+            (Some(ordering), LessEqual) => ordering.is_le(),
+            (Some(ordering), GreaterEqual) => ordering.is_ge(),
+            // a NaN is a number but it has no order, every comparison with it is false
+            (None, _)
+                if matches!(left, VariableType::Integer(_) | VariableType::Float(_))
+                    && matches!(right, VariableType::Integer(_) | VariableType::Float(_)) =>
+            {
+                false
+            }
+            _ => return Err(self.incompatible(left.type_name(), right.type_name())),
+        };
+        Ok(VariableType::Boolean(result))
+    }
+
+    fn incompatible(&self, left: &str, right: &str) -> CustomError {
+        CustomError::InvalidOperation(format!(
+            "Cannot apply {} to a {} and a {}",
+            self.symbol(),
+            left,
+            right
+        ))
+    }
+}
+
+const HIGHEST_LEVEL: u8 = 6;
 const LOWEST_LEVEL: u8 = 1;
 
 /// With:
-/// 1. * and /
-/// 2. + and -
-/// 3. = and !=
-/// 4. &&
-/// 5. ||
+/// 1. ** (left to right like the other levels : `2 ** 3 ** 2` is `64`)
+/// 2. *, / and %
+/// 3. + and -
+/// 4. ==, !=, <, >, <= and >=
+/// 5. &&
+/// 6. ||
 ///
 /// 0 is for unary
 impl Token {
     pub fn get_level(&self) -> Option<u8> {
         match self {
-            Token::Mul => Some(1),
-            Token::Div => Some(1),
-            Token::Add => Some(2),
-            Token::Sub => Some(2),
-            Token::Equal => Some(3),
-            Token::NotEqual => Some(3),
-            Token::And => Some(4),
-            Token::Or => Some(5),
+            Token::Pow => Some(1),
+            Token::Mul => Some(2),
+            Token::Div => Some(2),
+            Token::Mod => Some(2),
+            Token::Add => Some(3),
+            Token::Sub => Some(3),
+            Token::Equal => Some(4),
+            Token::NotEqual => Some(4),
+            Token::Less => Some(4),
+            Token::Greater => Some(4),
+            Token::LessEqual => Some(4),
+            Token::GreaterEqual => Some(4),
+            Token::And => Some(5),
+            Token::Or => Some(6),
             _ => None,
         }
     }
@@ -359,12 +814,18 @@ impl Token {
     /// [Token::get_level] should be called before
     pub fn get_operation(&self) -> Operations {
         match self {
+            Token::Pow => Pow,
             Token::Mul => Mul,
             Token::Div => Div,
+            Token::Mod => Mod,
             Token::Add => Add,
             Token::Sub => Sub,
             Token::Equal => Equal,
             Token::NotEqual => NotEqual,
+            Token::Less => Less,
+            Token::Greater => Greater,
+            Token::LessEqual => LessEqual,
+            Token::GreaterEqual => GreaterEqual,
             Token::And => Operations::And,
             Token::Or => Operations::Or,
             _ => panic!("Unexpected token found"),
@@ -374,34 +835,52 @@ impl Token {
 
 /// Grammar for [OperationN]
 /// ```grammar
-/// <op n> ::= T_OPERATIONS_N <tp n>
+/// <op n> ::= T_OPERATIONS_N <tp n-1>
 /// ```
+/// The right side only contains operations with a higher priority, the operations of the same level
+/// are chained by [TakePriorityN] to be evaluated from left to right.
+///
 /// See also [TakePriorityN] and [Operations]
-#[derive(PartialEq)]
+#[derive(PartialEq, Serialize)]
 pub struct OperationN {
     level: u8,
     operation: Operations,
     tp_nm1: Box<TakePriorityN>,
 }
 
+impl OperationN {
+    pub(crate) fn new(level: u8, operation: Operations, tp_nm1: TakePriorityN) -> Self {
+        Self {
+            level,
+            operation,
+            tp_nm1: Box::new(tp_nm1),
+        }
+    }
+}
+
 impl ParsableWithLevel for OperationN {
-    fn parse(tokens: &mut VecDeque<TokenContainer>, level: u8) -> ResultOption<Self> {
+    fn parse(
+        tokens: &mut VecDeque<TokenContainer>,
+        context: &mut ParseContext,
+        level: u8,
+    ) -> ResultOption<Self> {
         if let Some(container) = tokens.front() {
             if let Some(level_token) = container.token.get_level() {
                 if level_token != level {
                     return Ok(None);
                 }
                 let operation = tokens.pop_front().unwrap().token.get_operation();
-                if let Some(tp_nm1) = TakePriorityN::parse(tokens, level)? {
+                if let Some(tp_nm1) = TakePriorityN::parse(tokens, context, level - 1)? {
                     Ok(Some(Self {
                         level,
                         operation,
                         tp_nm1: Box::new(tp_nm1),
                     }))
                 } else {
-                    Err(UnexpectedToken(String::from(
+                    Err(CustomError::unexpected_token(
                         "Missing TakePriorityN with level",
-                    )))
+                        tokens.front(),
+                    ))
                 }
             } else {
                 Ok(None)
@@ -417,13 +896,23 @@ impl EvaluateFromInput for OperationN {
         &self,
         operation_context: &OperationContext,
         input: OperationIO,
-    ) -> OperationIO {
-        match self.operation {
-            Add => input + self.tp_nm1.evaluate(operation_context),
-            Sub => input - self.tp_nm1.evaluate(operation_context),
-            Div => input / self.tp_nm1.evaluate(operation_context),
-            Mul => input * self.tp_nm1.evaluate(operation_context),
-            _ => todo!(),
+    ) -> ShortResult<OperationIO> {
+        self.combine(input, || self.tp_nm1.evaluate(operation_context))
+    }
+}
+
+impl OperationN {
+    /// Apply the operation to the `input` and the value of the right side, computed by `right`
+    /// only when it is needed : `&&` and `||` do not use it when the left side is enough.
+    fn combine(
+        &self,
+        input: OperationIO,
+        right: impl FnOnce() -> ShortResult<OperationIO>,
+    ) -> ShortResult<OperationIO> {
+        match (&self.operation, &input) {
+            (Operations::And, VariableType::Boolean(false)) => Ok(input),
+            (Operations::Or, VariableType::Boolean(true)) => Ok(input),
+            _ => self.operation.apply(input, right()?),
         }
     }
 }
@@ -431,9 +920,11 @@ impl EvaluateFromInput for OperationN {
 /// Grammar for [TakePriorityN]
 /// ```grammar
 /// <tp0> ::= <unary_tp> | <take_prio>
-/// <tp n> ::= <tp n-1> (<op n> |)
+/// <tp n> ::= <tp n-1> {<op n>}
 /// ```
-#[derive(PartialEq)]
+/// A chain of operations of the same level is left associative : `a - b - c` is stored as
+/// `(a - b) - c`, the left operation being the `tp_nm1` of the right one.
+#[derive(PartialEq, Serialize)]
 pub enum TakePriorityN {
     ElementUnary0(Box<UnaryTP>),
     ElementSimple0(Box<TakePriority>),
@@ -445,25 +936,29 @@ pub enum TakePriorityN {
 }
 
 impl ParsableWithLevel for TakePriorityN {
-    fn parse(tokens: &mut VecDeque<TokenContainer>, level: u8) -> ResultOption<Self> {
+    fn parse(
+        tokens: &mut VecDeque<TokenContainer>,
+        context: &mut ParseContext,
+        level: u8,
+    ) -> ResultOption<Self> {
         if level + 1 == LOWEST_LEVEL {
-            if let Some(unary) = UnaryTP::parse(tokens)? {
+            if let Some(unary) = UnaryTP::parse(tokens, context)? {
                 Ok(Some(Self::ElementUnary0(Box::new(unary))))
-            } else if let Some(take_priority) = TakePriority::parse(tokens)? {
+            } else if let Some(take_priority) = TakePriority::parse(tokens, context)? {
                 Ok(Some(Self::ElementSimple0(Box::new(take_priority))))
             } else {
                 Ok(None)
             }
-        } else if let Some(take_priority_nm1) = TakePriorityN::parse(tokens, level - 1)? {
-            let optional = OperationN::parse(tokens, level)?;
-            match optional {
-                Some(op_n) => Ok(Some(Self::ElementN {
+        } else if let Some(take_priority_nm1) = TakePriorityN::parse(tokens, context, level - 1)? {
+            let mut left = take_priority_nm1;
+            while let Some(op_n) = OperationN::parse(tokens, context, level)? {
+                left = Self::ElementN {
                     level,
-                    tp_nm1: Box::new(take_priority_nm1),
+                    tp_nm1: Box::new(left),
                     op_n: Some(Box::new(op_n)),
-                })),
-                None => Ok(Some(take_priority_nm1)),
+                };
             }
+            Ok(Some(left))
         } else {
             Ok(None)
         }
@@ -471,7 +966,7 @@ impl ParsableWithLevel for TakePriorityN {
 }
 
 impl Evaluate for TakePriorityN {
-    fn evaluate(&self, operation_context: &OperationContext) -> OperationIO {
+    fn evaluate(&self, operation_context: &OperationContext) -> ShortResult<OperationIO> {
         match self {
             TakePriorityN::ElementUnary0(unary) => unary.evaluate(operation_context),
             TakePriorityN::ElementSimple0(take_priority) => {
@@ -481,7 +976,7 @@ impl Evaluate for TakePriorityN {
                 level: _,
                 tp_nm1,
                 op_n: Some(op),
-            } => op.evaluate_from_input(operation_context, tp_nm1.evaluate(operation_context)),
+            } => op.evaluate_from_input(operation_context, tp_nm1.evaluate(operation_context)?),
             TakePriorityN::ElementN {
                 level: _,
                 tp_nm1,
@@ -491,19 +986,97 @@ impl Evaluate for TakePriorityN {
     }
 }
 
+impl TakePriorityN {
+    /// The value of the node when it is only made of operations between numeric literals, so it is
+    /// known without any context. `None` when the node uses something else, like a variable.
+    fn constant(&self) -> Option<ShortResult<VariableType>> {
+        match self {
+            TakePriorityN::ElementUnary0(unary) => unary.constant(),
+            TakePriorityN::ElementSimple0(take_priority) => take_priority.constant(),
+            TakePriorityN::ElementN {
+                level: _,
+                tp_nm1,
+                op_n: Some(op),
+            } => {
+                let left = tp_nm1.constant()?;
+                let right = op.tp_nm1.constant()?;
+                Some(left.and_then(|left| op.combine(left, || right)))
+            }
+            TakePriorityN::ElementN {
+                level: _,
+                tp_nm1,
+                op_n: None,
+            } => tp_nm1.constant(),
+        }
+    }
+
+    /// The literal when the node is a single literal, as it is parsed.
+    fn literal(&self) -> Option<&ValueBase> {
+        match self {
+            TakePriorityN::ElementUnary0(unary) => match &**unary {
+                UnaryTP::TakePriority(TakePriority::Value(ValueNode::ValueBase(value))) => {
+                    Some(value)
+                }
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+}
+
+impl UnaryTP {
+    fn constant(&self) -> Option<ShortResult<VariableType>> {
+        match self {
+            UnaryTP::Plus(unary_tp) | UnaryTP::Minus(unary_tp) | UnaryTP::Not(unary_tp) => {
+                Some(unary_tp.constant()?.and_then(|value| self.apply(value)))
+            }
+            UnaryTP::TakePriority(take_priority) => take_priority.constant(),
+        }
+    }
+}
+
+impl TakePriority {
+    fn constant(&self) -> Option<ShortResult<VariableType>> {
+        match self {
+            TakePriority::Exp(exp) => match &**exp {
+                Exp::TPLast(tp_last) => tp_last.child.constant(),
+                _ => None,
+            },
+            TakePriority::Value(ValueNode::ValueBase(
+                value @ (ValueBase::Int(_) | ValueBase::Float(_)),
+            )) => Some(Ok(value.value())),
+            TakePriority::Value(_) => None,
+        }
+    }
+}
+
 /// Level is always the higher level.
 /// ```grammar
 /// <tp_last> ::= <tp max>
 /// ```
 /// See [HIGHEST_LEVEL] and [TakePriorityN]
-#[derive(PartialEq)]
+#[derive(PartialEq, Serialize)]
 pub struct TakePriorityLast {
     child: TakePriorityN,
 }
 
+impl TakePriorityLast {
+    pub(crate) fn new(child: TakePriorityN) -> Self {
+        Self { child }
+    }
+
+    /// The literal when the expression is a single literal, like `1` or `"text"`.
+    pub(crate) fn literal(&self) -> Option<&ValueBase> {
+        self.child.literal()
+    }
+}
+
 impl Parsable for TakePriorityLast {
-    fn parse(tokens: &mut VecDeque<TokenContainer>) -> ResultOption<Self> {
-        if let Some(child) = TakePriorityN::parse(tokens, HIGHEST_LEVEL)? {
+    fn parse(
+        tokens: &mut VecDeque<TokenContainer>,
+        context: &mut ParseContext,
+    ) -> ResultOption<Self> {
+        if let Some(child) = TakePriorityN::parse(tokens, context, HIGHEST_LEVEL)? {
             Ok(Some(Self { child }))
         } else {
             Ok(None)
@@ -512,15 +1085,15 @@ impl Parsable for TakePriorityLast {
 }
 
 impl Evaluate for TakePriorityLast {
-    fn evaluate(&self, operation_context: &OperationContext) -> OperationIO {
+    fn evaluate(&self, operation_context: &OperationContext) -> ShortResult<OperationIO> {
         self.child.evaluate(operation_context)
     }
 }
 
 /// Grammar for [NoValueN]
 /// ```grammar
-/// <nv0> ::= <op max>
-/// <nv n> ::= <op max-n> (<nv n-1> |) | <nv n-1>
+/// <nv1> ::= <op max> (<nv1> |)
+/// <nv n> ::= <op max+1-n> (<nv n> |) | <nv n-1>
 /// <no_value> ::= <nv max>
 /// ```
 ///
@@ -530,7 +1103,7 @@ impl Evaluate for TakePriorityLast {
 /// and we see the operator after.
 /// At least one operator is expected in this node.
 ///
-#[derive(PartialEq)]
+#[derive(PartialEq, Serialize)]
 pub enum NoValueN {
     Element0(Box<OperationN>),
     ElementOperationN {
@@ -545,22 +1118,27 @@ pub enum NoValueN {
 }
 
 impl ParsableWithLevel for NoValueN {
-    fn parse(tokens: &mut VecDeque<TokenContainer>, level: u8) -> ResultOption<Self> {
-        if level == LOWEST_LEVEL {
-            if let Some(operation) = OperationN::parse(tokens, HIGHEST_LEVEL)? {
-                Ok(Some(Self::Element0(Box::new(operation))))
-            } else {
-                Ok(None)
+    fn parse(
+        tokens: &mut VecDeque<TokenContainer>,
+        context: &mut ParseContext,
+        level: u8,
+    ) -> ResultOption<Self> {
+        if let Some(operation) = OperationN::parse(tokens, context, HIGHEST_LEVEL + 1 - level)? {
+            // the next operations of the same level are applied after this one
+            let nv_n =
+                <NoValueN as ParsableWithLevel>::parse(tokens, context, level)?.map(Box::new);
+            if level == LOWEST_LEVEL && nv_n.is_none() {
+                return Ok(Some(Self::Element0(Box::new(operation))));
             }
-        } else if let Some(operation) = OperationN::parse(tokens, HIGHEST_LEVEL - level)? {
-            let nv_mn1 = <NoValueN as ParsableWithLevel>::parse(tokens, level - 1)?.map(Box::new);
             Ok(Some(Self::ElementOperationN {
                 level,
                 operation: Box::new(operation),
-                no_value_before: nv_mn1,
+                no_value_before: nv_n,
             }))
+        } else if level == LOWEST_LEVEL {
+            Ok(None)
         } else if let Some(no_value_before) =
-            <NoValueN as ParsableWithLevel>::parse(tokens, level - 1)?
+            <NoValueN as ParsableWithLevel>::parse(tokens, context, level - 1)?
         {
             Ok(Some(Self::ElementSimpleN {
                 level,
@@ -573,11 +1151,14 @@ impl ParsableWithLevel for NoValueN {
 }
 
 impl Parsable for NoValueN {
-    fn parse(tokens: &mut VecDeque<TokenContainer>) -> ResultOption<Self>
+    fn parse(
+        tokens: &mut VecDeque<TokenContainer>,
+        context: &mut ParseContext,
+    ) -> ResultOption<Self>
     where
         Self: Sized,
     {
-        <NoValueN as ParsableWithLevel>::parse(tokens, HIGHEST_LEVEL)
+        <NoValueN as ParsableWithLevel>::parse(tokens, context, HIGHEST_LEVEL)
     }
 }
 
@@ -586,7 +1167,7 @@ impl EvaluateFromInput for NoValueN {
         &self,
         operation_context: &OperationContext,
         input: OperationIO,
-    ) -> OperationIO {
+    ) -> ShortResult<OperationIO> {
         match self {
             NoValueN::Element0(op) => op.evaluate_from_input(operation_context, input),
             NoValueN::ElementSimpleN {
@@ -599,7 +1180,7 @@ impl EvaluateFromInput for NoValueN {
                 no_value_before: Some(value_before),
             } => value_before.evaluate_from_input(
                 operation_context,
-                operation.evaluate_from_input(operation_context, input),
+                operation.evaluate_from_input(operation_context, input)?,
             ),
             NoValueN::ElementOperationN {
                 level: _,
@@ -616,117 +1197,295 @@ impl_debug!(TakePriorityLast);
 impl_debug!(NoValueN);
 impl_debug!(TakePriorityN);
 
+impl ToSource for Operations {
+    fn to_source(&self) -> String {
+        self.symbol().to_string()
+    }
+}
+
+impl ToSource for OperationN {
+    fn to_source(&self) -> String {
+        format!("{} {}", self.operation.symbol(), self.tp_nm1.to_source())
+    }
+}
+
+impl FoldConstants for OperationN {
+    fn fold_constants(&mut self) {
+        self.tp_nm1.fold_constants();
+    }
+}
+
+impl Visitable for OperationN {
+    fn accept<V: Visitor>(&self, visitor: &mut V) {
+        visitor.visit_operation_n(self);
+        self.tp_nm1.accept(visitor);
+    }
+}
+
+impl ToSource for TakePriorityLast {
+    fn to_source(&self) -> String {
+        self.child.to_source()
+    }
+}
+
+impl FoldConstants for TakePriorityLast {
+    fn fold_constants(&mut self) {
+        self.child.fold_constants();
+    }
+}
+
+impl Visitable for TakePriorityLast {
+    fn accept<V: Visitor>(&self, visitor: &mut V) {
+        visitor.visit_take_priority_last(self);
+        self.child.accept(visitor);
+    }
+}
+
+impl ToSource for NoValueN {
+    fn to_source(&self) -> String {
+        match self {
+            NoValueN::Element0(operation) => operation.to_source(),
+            NoValueN::ElementOperationN {
+                level: _,
+                operation,
+                no_value_before: Some(next),
+            } => format!("{} {}", operation.to_source(), next.to_source()),
+            NoValueN::ElementOperationN {
+                level: _,
+                operation,
+                no_value_before: None,
+            } => operation.to_source(),
+            NoValueN::ElementSimpleN {
+                level: _,
+                no_value_before,
+            } => no_value_before.to_source(),
+        }
+    }
+}
+
+impl FoldConstants for NoValueN {
+    fn fold_constants(&mut self) {
+        match self {
+            NoValueN::Element0(operation) => operation.fold_constants(),
+            NoValueN::ElementOperationN {
+                level: _,
+                operation,
+                no_value_before,
+            } => {
+                operation.fold_constants();
+                if let Some(no_value_before) = no_value_before {
+                    no_value_before.fold_constants();
+                }
+            }
+            NoValueN::ElementSimpleN {
+                level: _,
+                no_value_before,
+            } => no_value_before.fold_constants(),
+        }
+    }
+}
+
+impl Visitable for NoValueN {
+    fn accept<V: Visitor>(&self, visitor: &mut V) {
+        visitor.visit_no_value_n(self);
+        match self {
+            NoValueN::Element0(operation) => operation.accept(visitor),
+            NoValueN::ElementOperationN {
+                level: _,
+                operation,
+                no_value_before,
+            } => {
+                operation.accept(visitor);
+                if let Some(no_value_before) = no_value_before {
+                    no_value_before.accept(visitor);
+                }
+            }
+            NoValueN::ElementSimpleN {
+                level: _,
+                no_value_before,
+            } => no_value_before.accept(visitor),
+        }
+    }
+}
+
+impl ToSource for TakePriorityN {
+    fn to_source(&self) -> String {
+        match self {
+            TakePriorityN::ElementUnary0(unary) => unary.to_source(),
+            TakePriorityN::ElementSimple0(take_priority) => take_priority.to_source(),
+            TakePriorityN::ElementN {
+                level: _,
+                tp_nm1,
+                op_n: Some(op),
+            } => format!("{} {}", tp_nm1.to_source(), op.to_source()),
+            TakePriorityN::ElementN {
+                level: _,
+                tp_nm1,
+                op_n: None,
+            } => tp_nm1.to_source(),
+        }
+    }
+}
+
+impl FoldConstants for TakePriorityN {
+    fn fold_constants(&mut self) {
+        if self.literal().is_none() {
+            let literal = match self.constant() {
+                Some(Ok(VariableType::Integer(value))) => Some(ValueBase::Int(value)),
+                Some(Ok(VariableType::Float(value))) => Some(ValueBase::Float(value)),
+                Some(Ok(VariableType::Boolean(value))) => Some(ValueBase::Bool(value)),
+                // an operation that fails is kept, the error is returned by its evaluation
+                _ => None,
+            };
+            if let Some(literal) = literal {
+                *self = Self::ElementUnary0(Box::new(UnaryTP::TakePriority(TakePriority::Value(
+                    ValueNode::ValueBase(literal),
+                ))));
+                return;
+            }
+        }
+        match self {
+            TakePriorityN::ElementUnary0(unary) => unary.fold_constants(),
+            TakePriorityN::ElementSimple0(take_priority) => take_priority.fold_constants(),
+            TakePriorityN::ElementN {
+                level: _,
+                tp_nm1,
+                op_n,
+            } => {
+                tp_nm1.fold_constants();
+                if let Some(op_n) = op_n {
+                    op_n.fold_constants();
+                }
+            }
+        }
+    }
+}
+
+impl Visitable for TakePriorityN {
+    fn accept<V: Visitor>(&self, visitor: &mut V) {
+        visitor.visit_take_priority_n(self);
+        match self {
+            TakePriorityN::ElementUnary0(unary) => unary.accept(visitor),
+            TakePriorityN::ElementSimple0(take_priority) => take_priority.accept(visitor),
+            TakePriorityN::ElementN {
+                level: _,
+                tp_nm1,
+                op_n,
+            } => {
+                tp_nm1.accept(visitor);
+                if let Some(op_n) = op_n {
+                    op_n.accept(visitor);
+                }
+            }
+        }
+    }
+}
+
 impl GraphDisplay for Operations {
-    fn graph_display(&self, graph: &mut String, id: &mut usize) {
-        graph.push_str(&format!(
-            "\nsubgraph Operation_{}[Op {}]",
-            id,
-            match self {
-                Mul => "OP *",
-                Div => "OP /",
-                Add => "OP +",
-                Sub => "OP -",
-                Equal => "CO =",
-                NotEqual => "CO !=",
-                Operations::And => "LG &&",
-                Operations::Or => "LG ||",
-            }
-        ));
+    fn graph_display(&self, graph: &mut Graph, id: &mut usize) {
+        let operation = match self {
+            Pow => "OP **",
+            Mul => "OP *",
+            Div => "OP /",
+            Mod => "OP %",
+            Add => "OP +",
+            Sub => "OP -",
+            Equal => "CO =",
+            NotEqual => "CO !=",
+            Less => "CO <",
+            Greater => "CO >",
+            LessEqual => "CO <=",
+            GreaterEqual => "CO >=",
+            Operations::And => "LG &&",
+            Operations::Or => "LG ||",
+        };
+        graph.open("Operation", &format!("Op {}", operation), *id);
         *id += 1;
-        graph.push_str("\nend");
+        graph.close();
     }
 }
 
 impl GraphDisplay for OperationN {
-    fn graph_display(&self, graph: &mut String, id: &mut usize) {
-        graph.push_str(&format!(
-            "\nsubgraph OperationN_{}[OP N={}]",
-            id, self.level
-        ));
+    fn graph_display(&self, graph: &mut Graph, id: &mut usize) {
+        graph.open("OperationN", &format!("OP N={}", self.level), *id);
         *id += 1;
         self.operation.graph_display(graph, id);
         self.tp_nm1.graph_display(graph, id);
-        graph.push_str("\nend");
+        graph.close();
     }
 }
 
 impl GraphDisplay for TakePriorityN {
-    fn graph_display(&self, graph: &mut String, id: &mut usize) {
+    fn graph_display(&self, graph: &mut Graph, id: &mut usize) {
         match self {
             TakePriorityN::ElementUnary0(unary) => {
-                graph.push_str(&format!("\nsubgraph TakePriorityN_{}[TP0 UNARY]", id));
+                graph.open("TakePriorityN", "TP0 UNARY", *id);
                 *id += 1;
                 unary.graph_display(graph, id);
-                graph.push_str("\nend");
+                graph.close();
             }
             TakePriorityN::ElementSimple0(simple) => {
-                graph.push_str(&format!("\nsubgraph TakePriorityN_{}[TP0 SIMPLE]", id));
+                graph.open("TakePriorityN", "TP0 SIMPLE", *id);
                 *id += 1;
                 simple.graph_display(graph, id);
-                graph.push_str("\nend");
+                graph.close();
             }
             TakePriorityN::ElementN {
                 level,
                 tp_nm1: tp_n1,
                 op_n,
             } => {
-                graph.push_str(&format!("\nsubgraph TakePriorityN_{}[TP N={}]", id, level));
+                graph.open("TakePriorityN", &format!("TP N={}", level), *id);
                 *id += 1;
                 tp_n1.graph_display(graph, id);
                 if let Some(op_n) = op_n {
                     op_n.graph_display(graph, id);
                 }
-                graph.push_str("\nend");
+                graph.close();
             }
         }
     }
 }
 
 impl GraphDisplay for TakePriorityLast {
-    fn graph_display(&self, graph: &mut String, id: &mut usize) {
-        graph.push_str(&format!("\nsubgraph TakePriorityLst_{}[TP_LAST]", id));
+    fn graph_display(&self, graph: &mut Graph, id: &mut usize) {
+        graph.open("TakePriorityLst", "TP_LAST", *id);
         *id += 1;
         self.child.graph_display(graph, id);
-        graph.push_str("\nend");
+        graph.close();
     }
 }
 
 impl GraphDisplay for NoValueN {
-    fn graph_display(&self, graph: &mut String, id: &mut usize) {
+    fn graph_display(&self, graph: &mut Graph, id: &mut usize) {
         match self {
             NoValueN::Element0(op) => {
-                graph.push_str(&format!("\nsubgraph NoValueN_{}[NoValue0]", id));
+                graph.open("NoValueN", "NoValue0", *id);
                 *id += 1;
                 op.graph_display(graph, id);
-                graph.push_str("\nend");
+                graph.close();
             }
             NoValueN::ElementOperationN {
                 level,
                 operation,
                 no_value_before,
             } => {
-                graph.push_str(&format!(
-                    "\nsubgraph NoValueN_{}[NoValueOp N={}]",
-                    id, level
-                ));
+                graph.open("NoValueN", &format!("NoValueOp N={}", level), *id);
                 *id += 1;
                 operation.graph_display(graph, id);
                 if let Some(no_value_before) = no_value_before {
                     no_value_before.graph_display(graph, id);
                 }
-                graph.push_str("\nend");
+                graph.close();
             }
             NoValueN::ElementSimpleN {
                 level,
                 no_value_before,
             } => {
-                graph.push_str(&format!(
-                    "\nsubgraph NoValueN_{}[NoValueSimple N={}]",
-                    id, level
-                ));
+                graph.open("NoValueN", &format!("NoValueSimple N={}", level), *id);
                 *id += 1;
                 no_value_before.graph_display(graph, id);
-                graph.push_str("\nend");
+                graph.close();
             }
         }
     }
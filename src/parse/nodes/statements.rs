@@ -0,0 +1,226 @@
+use std::collections::VecDeque;
+use std::rc::Rc;
+
+use serde::Serialize;
+
+use crate::execute::{Evaluate, OperationContext, OperationIO};
+use crate::parse::context::ParseContext;
+use crate::parse::nodes::classes::ClassDec;
+use crate::parse::nodes::expressions::{
+    starts_declaration, Exp, ExpBase, ExpTp, IdUseV, NatCall, Return, StatementNode, Ternary,
+};
+use crate::parse::nodes::functions::FctDec;
+use crate::parse::nodes::if_else::Cond;
+use crate::parse::nodes::vars::VarDec;
+use crate::parse::nodes::visitor::{Visitable, Visitor};
+use crate::parse::nodes::{FoldConstants, Graph, GraphDisplay, ToSource};
+use crate::parse::token_stream::peek_kind;
+use crate::skr_errors::{ResultOption, ShortResult};
+use crate::tokens::{Token, TokenContainer};
+use crate::{impl_debug, some_token};
+
+// Grammar of this file :
+// <statement> ::=
+//   <return>
+//   | <fct_dec>
+//   | <class_dec>
+//   | <cond>
+//   | <nat_call>
+//   | <var_dec>
+//   | <exp>
+
+// -----------------
+// --- Statement ---
+// -----------------
+
+/// `Statement` is a top-level construct of a file, the node returned by
+/// [parse_statement](crate::parse::parse_statement). The declarations, the conditions, the calls to
+/// native functions and the modifications of a variable have their own variant, any other
+/// expression is a [Statement::Expr].
+///
+/// # Grammar
+///
+/// `<statement> ::= <return> | <fct_dec> | <class_dec> | <cond> | <nat_call> | <var_dec> | <exp>`
+///
+/// A statement that can be the condition of a [Ternary], like `ij c { 1 } ? 2 sula 3`, is an
+/// expression.
+#[derive(PartialEq, Serialize)]
+pub enum Statement {
+    VarDec(Box<VarDec>),
+    /// An [IdUseV] that changes a variable or a field, like `x 2` or `x++`
+    VarMod(IdUseV),
+    If(Box<Cond>),
+    FunctionDef(Rc<FctDec>),
+    ClassDef(Rc<ClassDec>),
+    Return(Return),
+    NatCall(Box<NatCall>),
+    Expr(Exp),
+}
+
+impl GraphDisplay for Statement {
+    fn graph_display(&self, graph: &mut Graph, id: &mut usize) {
+        graph.open("Statement", "Statement", *id);
+        *id += 1;
+        match self {
+            Statement::VarDec(var_dec) => var_dec.graph_display(graph, id),
+            Statement::VarMod(id_use_v) => id_use_v.graph_display(graph, id),
+            Statement::If(cond) => cond.graph_display(graph, id),
+            Statement::FunctionDef(fct_dec) => fct_dec.graph_display(graph, id),
+            Statement::ClassDef(class_dec) => class_dec.graph_display(graph, id),
+            Statement::Return(return_node) => return_node.graph_display(graph, id),
+            Statement::NatCall(nat_call) => nat_call.graph_display(graph, id),
+            Statement::Expr(exp) => exp.graph_display(graph, id),
+        }
+        graph.close();
+    }
+}
+
+impl_debug!(Statement);
+
+impl ToSource for Statement {
+    fn to_source(&self) -> String {
+        match self {
+            Statement::VarDec(var_dec) => var_dec.to_source(),
+            Statement::VarMod(id_use_v) => id_use_v.to_source(),
+            Statement::If(cond) => cond.to_source(),
+            Statement::FunctionDef(fct_dec) => fct_dec.to_source(),
+            Statement::ClassDef(class_dec) => class_dec.to_source(),
+            Statement::Return(return_node) => return_node.to_source(),
+            Statement::NatCall(nat_call) => nat_call.to_source(),
+            Statement::Expr(exp) => exp.to_source(),
+        }
+    }
+}
+
+impl FoldConstants for Statement {
+    fn fold_constants(&mut self) {
+        match self {
+            Statement::VarDec(var_dec) => var_dec.fold_constants(),
+            Statement::VarMod(id_use_v) => id_use_v.fold_constants(),
+            Statement::If(cond) => cond.fold_constants(),
+            // the declarations are only shared once they are evaluated
+            Statement::FunctionDef(fct_dec) => {
+                if let Some(fct_dec) = Rc::get_mut(fct_dec) {
+                    fct_dec.fold_constants();
+                }
+            }
+            Statement::ClassDef(class_dec) => {
+                if let Some(class_dec) = Rc::get_mut(class_dec) {
+                    class_dec.fold_constants();
+                }
+            }
+            Statement::Return(return_node) => return_node.fold_constants(),
+            Statement::NatCall(nat_call) => nat_call.fold_constants(),
+            Statement::Expr(exp) => exp.fold_constants(),
+        }
+    }
+}
+
+impl Visitable for Statement {
+    fn accept<V: Visitor>(&self, visitor: &mut V) {
+        visitor.visit_statement(self);
+        match self {
+            Statement::VarDec(var_dec) => var_dec.accept(visitor),
+            Statement::VarMod(id_use_v) => id_use_v.accept(visitor),
+            Statement::If(cond) => cond.accept(visitor),
+            Statement::FunctionDef(fct_dec) => fct_dec.accept(visitor),
+            Statement::ClassDef(class_dec) => class_dec.accept(visitor),
+            Statement::Return(return_node) => return_node.accept(visitor),
+            Statement::NatCall(nat_call) => nat_call.accept(visitor),
+            Statement::Expr(exp) => exp.accept(visitor),
+        }
+    }
+}
+
+impl Statement {
+    pub fn parse(
+        tokens: &mut VecDeque<TokenContainer>,
+        context: &mut ParseContext,
+    ) -> ResultOption<Statement> {
+        // <statement> ::=
+        //   <return>
+        //   | <fct_dec>
+        //   | <class_dec>
+        //   | <cond>
+        //   | <nat_call>
+        //   | <var_dec>
+        //   | <exp>
+        let exp_base = match peek_kind(tokens) {
+            Some(Token::KeywordReturn) => {
+                // the expression returned already contains the ternary
+                return Ok(Return::parse(tokens, context)?.map(Statement::Return));
+            }
+            Some(Token::KeywordFunction) => {
+                FctDec::parse(tokens, context)?.map(|fct_dec| ExpBase::FctDec(Rc::new(fct_dec)))
+            }
+            Some(Token::KeywordClass) => ClassDec::parse(tokens, context)?
+                .map(|class_dec| ExpBase::ClassDec(Rc::new(class_dec))),
+            Some(Token::KeywordIf) => {
+                Cond::parse(tokens, context)?.map(|cond| ExpBase::Cond(Box::new(cond)))
+            }
+            Some(Token::NatCall) => NatCall::parse(tokens, context)?
+                .map(|nat_call| ExpBase::NatCall(Box::new(nat_call))),
+            // a class followed by parentheses is a constructor call, not a declaration
+            Some(Token::Identifier(_)) if !starts_declaration(tokens, context) => {
+                return Ok(Exp::parse(tokens, context)?.map(Statement::from_exp));
+            }
+            _ => match VarDec::parse(tokens, context)? {
+                Some(var_dec) => Some(ExpBase::VarDec(Box::new(var_dec))),
+                None => return Ok(Exp::parse(tokens, context)?.map(Statement::from_exp)),
+            },
+        };
+        let exp_base = match exp_base {
+            Some(exp_base) => exp_base,
+            None => return Ok(None),
+        };
+        if let some_token!(Token::Question) = tokens.front() {
+            let condition = Exp::ExpTp(ExpTp::ExpBase(exp_base));
+            return Ok(Some(Statement::Expr(Ternary::parse(
+                condition, tokens, context,
+            )?)));
+        }
+        Ok(Some(match exp_base {
+            ExpBase::VarDec(var_dec) => Statement::VarDec(var_dec),
+            ExpBase::FctDec(fct_dec) => Statement::FunctionDef(fct_dec),
+            ExpBase::ClassDec(class_dec) => Statement::ClassDef(class_dec),
+            ExpBase::Cond(cond) => Statement::If(cond),
+            ExpBase::NatCall(nat_call) => Statement::NatCall(nat_call),
+            exp_base => Statement::Expr(Exp::ExpTp(ExpTp::ExpBase(exp_base))),
+        }))
+    }
+
+    /// The statement of an expression : an identifier that changes a variable is a
+    /// [Statement::VarMod].
+    fn from_exp(exp: Exp) -> Statement {
+        match exp {
+            Exp::ExpTp(ExpTp::IdUseV(id_use_v)) if id_use_v.is_modification() => {
+                Statement::VarMod(id_use_v)
+            }
+            exp => Statement::Expr(exp),
+        }
+    }
+}
+
+impl StatementNode for Statement {
+    fn is_bubble_scope(&self) -> bool {
+        match self {
+            Statement::Expr(exp) => exp.is_bubble_scope(),
+            _ => false,
+        }
+    }
+}
+
+impl Evaluate for Statement {
+    fn evaluate(&self, operation_context: &OperationContext) -> ShortResult<OperationIO> {
+        match self {
+            Statement::VarDec(var_dec) => var_dec.evaluate(operation_context),
+            Statement::VarMod(id_use_v) => id_use_v.evaluate(operation_context),
+            Statement::If(cond) => cond.evaluate(operation_context),
+            Statement::FunctionDef(fct_dec) => fct_dec.declare(operation_context),
+            Statement::ClassDef(class_dec) => class_dec.declare(operation_context),
+            Statement::Return(return_node) => return_node.evaluate(operation_context),
+            Statement::NatCall(nat_call) => nat_call.evaluate(operation_context),
+            Statement::Expr(exp) => exp.evaluate(operation_context),
+        }
+    }
+}
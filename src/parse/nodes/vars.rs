@@ -1,22 +1,28 @@
 use std::collections::VecDeque;
 
-use crate::parse::nodes::classes::is_type_def;
+use serde::Serialize;
+
+use crate::execute::variables::VariableType;
+use crate::execute::{Evaluate, FloatType, OperationContext, OperationIO};
+use crate::parse::context::ParseContext;
 use crate::parse::nodes::expressions::Exp;
-use crate::parse::nodes::GraphDisplay;
-use crate::skr_errors::{CustomError, ResultOption};
+use crate::parse::nodes::visitor::{Visitable, Visitor};
+use crate::parse::nodes::{FoldConstants, Graph, GraphDisplay, ToSource};
+use crate::parse::token_stream::{expect, peek_kind, peek_position};
+use crate::skr_errors::{CustomError, ResultOption, ShortResult};
 use crate::tokens::{ModifierKeyword, Token, TokenContainer};
 use crate::{impl_debug, some_token};
 
 // Grammar of this file :
 /*
-<type> ::= T_TYPE_DEF
-<vd> ::= <type> T_IDENTIFIER <exp>
+<type> ::= T_TYPE_DEF {: T_TYPE_DEF}
+<vd> ::= <type> T_IDENTIFIER (<exp> |)
 <global_var> ::= fu <vd>
 <private_var> ::= pu <vd>
 <const_var> ::= ju (<private_var> | <global_var> | <vd>)
 <var_dec> ::= <const_var> | <private_var> | <global_var> | <vd>
 
-<var_mod> ::= <exp>
+<var_mod> ::= <exp> | T_INCREMENT | T_DECREMENT
  */
 
 // ------------
@@ -25,30 +31,136 @@ use crate::{impl_debug, some_token};
 
 /// `Type` represents a defined type in the AST. This node detect any identifier and ask the class
 /// manager if this is a type or not.
-#[derive(PartialEq)]
+#[derive(Clone, PartialEq, Serialize)]
 pub struct Type {
     pub(crate) name: String,
 }
 
 impl GraphDisplay for Type {
-    fn graph_display(&self, graph: &mut String, id: &mut usize) {
-        graph.push_str(&format!("\nsubgraph CGet_{}[CGet {}]\nend", id, self.name));
+    fn graph_display(&self, graph: &mut Graph, id: &mut usize) {
+        graph.open("CGet", &format!("CGet {}", self.name), *id);
         *id += 1;
+        graph.close();
     }
 }
 
 impl_debug!(Type);
 
-pub(crate) fn parse_type(tokens: &mut VecDeque<TokenContainer>) -> Option<Type> {
-    if let some_token!(Token::Identifier(identifier)) = tokens.front() {
-        if is_type_def(identifier) {
-            if let some_token!(Token::Identifier(identifier)) = tokens.pop_front() {
-                return Some(Type { name: identifier });
+impl ToSource for Type {
+    fn to_source(&self) -> String {
+        self.name.clone()
+    }
+}
+
+impl Visitable for Type {
+    fn accept<V: Visitor>(&self, visitor: &mut V) {
+        visitor.visit_type(self);
+    }
+}
+
+impl Type {
+    /// Check that `value` can be stored in a variable of this type and return the value to store.
+    /// An int given to a `dar` is converted to a float. The values of the classes declared in the
+    /// code are not checked yet.
+    pub fn assign(&self, value: VariableType) -> ShortResult<VariableType> {
+        match (self.name.as_str(), value) {
+            ("int", value @ VariableType::Integer(_))
+            | ("dar", value @ VariableType::Float(_))
+            | ("ioi", value @ VariableType::Boolean(_))
+            | ("skr", value @ VariableType::String(_))
+            | ("null", value @ VariableType::Null) => Ok(value),
+            ("dar", VariableType::Integer(value)) => Ok(VariableType::Float(value as FloatType)),
+            ("int" | "dar" | "ioi" | "skr" | "null", value) => {
+                Err(CustomError::TypeMismatch(format!(
+                    "Cannot assign a {} to a variable of type {}",
+                    value.type_name(),
+                    self.name
+                )))
             }
+            (_, value) => Ok(value),
+        }
+    }
+
+    /// The value of a variable of this type declared without a value : `0`, `0.0`, `""`, `no`, or
+    /// null for the other types.
+    pub fn default_value(&self) -> VariableType {
+        match self.name.as_str() {
+            "int" => VariableType::Integer(0),
+            "dar" => VariableType::Float(0.0),
+            "ioi" => VariableType::Boolean(false),
+            "skr" => VariableType::String(String::new()),
+            _ => VariableType::Null,
+        }
+    }
+}
+
+/// Identifiers of the type path at the start of the tokens, without consuming them. The classes
+/// declared in a class are read from the right like the other `:` chains : `Inner:Outer` gives
+/// `["Inner", "Outer"]`.
+fn peek_type_path(tokens: &VecDeque<TokenContainer>) -> Vec<&str> {
+    let mut segments = Vec::new();
+    while let some_token!(Token::Identifier(segment)) = tokens.get(segments.len() * 2) {
+        segments.push(segment.as_str());
+        if !matches!(
+            tokens.get(segments.len() * 2 - 1),
+            some_token!(Token::Inside)
+        ) {
+            break;
+        }
+    }
+    segments
+}
+
+/// Find the type at the start of the tokens. Returns its name and the number of tokens it uses, or
+/// `None` when the tokens do not start with a type.
+///
+/// A qualified path that is followed by a name, like a declaration, is an error when the class on
+/// the right is known but one of the segments on the left is not declared inside it.
+pub(crate) fn resolve_type_path(
+    tokens: &VecDeque<TokenContainer>,
+    context: &ParseContext,
+) -> ShortResult<Option<(String, usize)>> {
+    let segments = peek_type_path(tokens);
+    if segments.is_empty() {
+        return Ok(None);
+    }
+    let name = segments.join(":");
+    let length = segments.len() * 2 - 1;
+    if context.classes.is_type_def(&name) {
+        return Ok(Some((name, length)));
+    }
+
+    // the longest known suffix of the path tells which segment is missing
+    let known = (1..segments.len())
+        .find(|&start| context.classes.is_type_def(&segments[start..].join(":")));
+    match known {
+        Some(start) if matches!(tokens.get(length), some_token!(Token::Identifier(_))) => {
+            Err(CustomError::unexpected_token(
+                &format!(
+                    "Unknown type {}, the class {} has no class {}",
+                    name,
+                    segments[start..].join(":"),
+                    segments[start - 1]
+                ),
+                tokens.front(),
+            ))
         }
+        _ => Ok(None),
     }
+}
 
-    None
+pub(crate) fn parse_type(
+    tokens: &mut VecDeque<TokenContainer>,
+    context: &mut ParseContext,
+) -> ResultOption<Type> {
+    // <type> ::= T_TYPE_DEF {: T_TYPE_DEF}
+    match resolve_type_path(tokens, context)? {
+        Some((name, length)) => {
+            tokens.drain(..length);
+            Ok(Some(Type { name }))
+        }
+        None => Ok(None),
+    }
 }
 
 // ----------
@@ -56,54 +168,192 @@ pub(crate) fn parse_type(tokens: &mut VecDeque<TokenContainer>) -> Option<Type>
 // ----------
 
 /// `Vd` represents a variable declaration in the AST. It contains a type, an identifier and an
-/// expression. The expression is not yet implemented.
-#[derive(PartialEq)]
+/// optional expression. Without expression, the variable starts with the default value of its
+/// type, see [Type::default_value].
+#[derive(PartialEq, Serialize)]
 pub struct Vd {
+    #[serde(rename = "type")]
     type_: Type,
     identifier: String,
-    exp: Box<Exp>,
+    exp: Option<Box<Exp>>,
 }
 
 impl GraphDisplay for Vd {
-    fn graph_display(&self, graph: &mut String, id: &mut usize) {
-        graph.push_str(&format!("\nsubgraph Vd_{}[Vd {}]", id, self.identifier));
+    fn graph_display(&self, graph: &mut Graph, id: &mut usize) {
+        graph.open("Vd", &format!("Vd {}", self.identifier), *id);
         *id += 1;
         self.type_.graph_display(graph, id);
-        graph.push_str("\nend")
+        graph.close();
     }
 }
 
 impl_debug!(Vd);
 
+impl ToSource for Vd {
+    fn to_source(&self) -> String {
+        match &self.exp {
+            Some(exp) => format!(
+                "{} {} {}",
+                self.type_.to_source(),
+                self.identifier,
+                exp.to_source()
+            ),
+            None => format!("{} {}", self.type_.to_source(), self.identifier),
+        }
+    }
+}
+
+impl FoldConstants for Vd {
+    fn fold_constants(&mut self) {
+        if let Some(exp) = &mut self.exp {
+            exp.fold_constants();
+        }
+    }
+}
+
+impl Visitable for Vd {
+    fn accept<V: Visitor>(&self, visitor: &mut V) {
+        visitor.visit_vd(self);
+        self.type_.accept(visitor);
+        if let Some(exp) = &self.exp {
+            exp.accept(visitor);
+        }
+    }
+}
+
 impl Vd {
-    fn new(type_: Type, identifier: String, exp: Exp) -> Self {
+    fn new(type_: Type, identifier: String, exp: Option<Exp>) -> Self {
         Self {
             type_,
             identifier,
-            exp: Box::new(exp),
+            exp: exp.map(Box::new),
         }
     }
 
-    fn parse(tokens: &mut VecDeque<TokenContainer>) -> ResultOption<Self> {
-        // <vd> ::= <type> T_IDENTIFIER <exp>
-        let type_ = match parse_type(tokens) {
+    fn parse(
+        tokens: &mut VecDeque<TokenContainer>,
+        context: &mut ParseContext,
+    ) -> ResultOption<Self> {
+        // <vd> ::= <type> T_IDENTIFIER (<exp> |)
+        let type_position = peek_position(tokens);
+        let type_ = match parse_type(tokens, context)? {
             Some(type_) => type_,
             None => return Ok(None),
         };
 
-        if let some_token!(Token::Identifier(identifier)) = tokens.pop_front() {
-            if let Some(exp0) = Exp::parse(tokens)? {
-                Ok(Some(Vd::new(type_, identifier, exp0)))
-            } else {
-                Err(CustomError::UnexpectedToken(
-                    "Expected an expression".to_string(),
-                ))
+        match tokens.pop_front() {
+            some_token!(Token::Identifier(identifier)) => {
+                let exp = Exp::parse(tokens, context)?;
+                Ok(Some(Vd::new(type_, identifier, exp)))
             }
-        } else {
-            Err(CustomError::UnexpectedToken(
-                "Expected an identifier".to_string(),
-            ))
+            token => Err(missing_part(
+                &format!(
+                    "Expected the name of the variable after the type {}",
+                    type_.name
+                ),
+                token.as_ref(),
+                type_position,
+            )),
+        }
+    }
+}
+
+impl Vd {
+    /// Declare the variable in the innermost scope of the [OperationContext], as a constant when
+    /// `constant` is true.
+    fn declare(
+        &self,
+        operation_context: &OperationContext,
+        constant: bool,
+    ) -> ShortResult<OperationIO> {
+        let value = match &self.exp {
+            Some(exp) => self.type_.assign(exp.evaluate(operation_context)?)?,
+            None => self.type_.default_value(),
+        };
+        operation_context.variables.borrow_mut().declare_typed(
+            &self.identifier,
+            value.clone(),
+            self.type_.clone(),
+            constant,
+        )?;
+        Ok(value)
+    }
+}
+
+/// The variable is declared in the innermost scope of the [OperationContext].
+impl Evaluate for Vd {
+    fn evaluate(&self, operation_context: &OperationContext) -> ShortResult<OperationIO> {
+        self.declare(operation_context, false)
+    }
+}
+
+fn modifier_word(modifier: &ModifierKeyword) -> &'static str {
+    match modifier {
+        ModifierKeyword::Global => "fu",
+        ModifierKeyword::Constant => "ju",
+        ModifierKeyword::Private => "pu",
+    }
+}
+
+/// Check the modifier following `previous`, if any. A modifier can only be written once, `fu` and
+/// `pu` cannot be used together and `ju` must be the first modifier, so a declaration has at most
+/// two modifiers.
+fn check_next_modifier(
+    previous: &ModifierKeyword,
+    tokens: &VecDeque<TokenContainer>,
+) -> ShortResult<()> {
+    let message = match (previous, peek_kind(tokens)) {
+        (_, Some(Token::KeywordModifier(modifier))) if modifier == previous => {
+            format!("Duplicate modifier {}", modifier_word(modifier))
+        }
+        (
+            ModifierKeyword::Global | ModifierKeyword::Private,
+            Some(Token::KeywordModifier(ModifierKeyword::Constant)),
+        ) => String::from("The modifier ju must be the first modifier of the declaration"),
+        (ModifierKeyword::Global | ModifierKeyword::Private, Some(Token::KeywordModifier(_))) => {
+            String::from("A variable cannot be both global (fu) and private (pu)")
         }
+        _ => return Ok(()),
+    };
+    Err(CustomError::unexpected_token(&message, tokens.front()))
+}
+
+/// Parse the [Vd] after the `modifier` keyword, when the next token is this keyword.
+fn parse_modified_vd(
+    modifier: ModifierKeyword,
+    tokens: &mut VecDeque<TokenContainer>,
+    context: &mut ParseContext,
+) -> ResultOption<Vd> {
+    if expect(
+        tokens,
+        |token| matches!(token, Token::KeywordModifier(next) if *next == modifier),
+    )
+    .is_none()
+    {
+        return Ok(None);
+    }
+    check_next_modifier(&modifier, tokens)?;
+    match Vd::parse(tokens, context)? {
+        Some(vd) => Ok(Some(vd)),
+        None => Err(CustomError::unexpected_token(
+            "Expected a variable declaration",
+            tokens.front(),
+        )),
+    }
+}
+
+/// Error for a missing part of a declaration. At the end of the file, there is no token to point
+/// at, so the error is placed on the last token read instead.
+fn missing_part(
+    message: &str,
+    next_token: Option<&TokenContainer>,
+    previous_position: Option<(usize, usize)>,
+) -> CustomError {
+    match (next_token, previous_position) {
+        (None, Some((line, column))) => {
+            CustomError::UnexpectedToken(message.to_string(), line, column)
+        }
+        (token, _) => CustomError::unexpected_token(message, token),
     }
 }
 
@@ -113,58 +363,95 @@ impl Vd {
 
 /// `GlobalVar` represents a global variable declaration in the AST. It contains a variable
 /// declaration.
-#[derive(PartialEq)]
+#[derive(PartialEq, Serialize)]
 pub struct GlobalVar {
     vd: Vd,
 }
 
 /// `PrivateVar` represents a private variable declaration in the AST. It contains a variable
 /// declaration.
-#[derive(PartialEq)]
+#[derive(PartialEq, Serialize)]
 pub struct PrivateVar {
     vd: Vd,
 }
 
 impl GraphDisplay for GlobalVar {
-    fn graph_display(&self, graph: &mut String, id: &mut usize) {
-        graph.push_str(&format!("\nsubgraph GlobalVar_{}[GlobalVar]", id));
+    fn graph_display(&self, graph: &mut Graph, id: &mut usize) {
+        graph.open("GlobalVar", "GlobalVar", *id);
         *id += 1;
         self.vd.graph_display(graph, id);
-        graph.push_str("\nend")
+        graph.close();
     }
 }
 
 impl_debug!(GlobalVar);
 
+impl ToSource for GlobalVar {
+    fn to_source(&self) -> String {
+        format!("fu {}", self.vd.to_source())
+    }
+}
+
+impl FoldConstants for GlobalVar {
+    fn fold_constants(&mut self) {
+        self.vd.fold_constants();
+    }
+}
+
+impl Visitable for GlobalVar {
+    fn accept<V: Visitor>(&self, visitor: &mut V) {
+        visitor.visit_global_var(self);
+        self.vd.accept(visitor);
+    }
+}
+
 impl GraphDisplay for PrivateVar {
-    fn graph_display(&self, graph: &mut String, id: &mut usize) {
-        graph.push_str(&format!("\nsubgraph PrivateVar_{}[PrivateVar]", id));
+    fn graph_display(&self, graph: &mut Graph, id: &mut usize) {
+        graph.open("PrivateVar", "PrivateVar", *id);
         *id += 1;
         self.vd.graph_display(graph, id);
-        graph.push_str("\nend")
+        graph.close();
     }
 }
 
 impl_debug!(PrivateVar);
 
+impl ToSource for PrivateVar {
+    fn to_source(&self) -> String {
+        format!("pu {}", self.vd.to_source())
+    }
+}
+
+impl FoldConstants for PrivateVar {
+    fn fold_constants(&mut self) {
+        self.vd.fold_constants();
+    }
+}
+
+impl Visitable for PrivateVar {
+    fn accept<V: Visitor>(&self, visitor: &mut V) {
+        visitor.visit_private_var(self);
+        self.vd.accept(visitor);
+    }
+}
+
 impl GlobalVar {
     fn new(vd: Vd) -> Self {
         Self { vd }
     }
 
-    fn parse(tokens: &mut VecDeque<TokenContainer>) -> ResultOption<Self> {
+    fn parse(
+        tokens: &mut VecDeque<TokenContainer>,
+        context: &mut ParseContext,
+    ) -> ResultOption<Self> {
         // <global_var> ::= fu <vd>
-        if let some_token!(Token::KeywordModifier(ModifierKeyword::Global)) = tokens.front() {
-            tokens.pop_front();
-            match Vd::parse(tokens)? {
-                Some(vd) => Ok(Some(GlobalVar::new(vd))),
-                None => Err(CustomError::UnexpectedToken(
-                    "Expected a variable declaration".to_string(),
-                )),
-            }
-        } else {
-            Ok(None)
-        }
+        Ok(parse_modified_vd(ModifierKeyword::Global, tokens, context)?.map(GlobalVar::new))
+    }
+}
+
+impl Evaluate for GlobalVar {
+    fn evaluate(&self, operation_context: &OperationContext) -> ShortResult<OperationIO> {
+        self.vd.evaluate(operation_context)
     }
 }
 
@@ -173,19 +460,18 @@ impl PrivateVar {
         Self { vd }
     }
 
-    fn parse(tokens: &mut VecDeque<TokenContainer>) -> ResultOption<Self> {
+    fn parse(
+        tokens: &mut VecDeque<TokenContainer>,
+        context: &mut ParseContext,
+    ) -> ResultOption<Self> {
         // <private_var> ::= pu <vd>
-        if let some_token!(Token::KeywordModifier(ModifierKeyword::Private)) = tokens.front() {
-            tokens.pop_front();
-            match Vd::parse(tokens)? {
-                Some(vd) => Ok(Some(PrivateVar::new(vd))),
-                None => Err(CustomError::UnexpectedToken(
-                    "Expected a variable declaration".to_string(),
-                )),
-            }
-        } else {
-            Ok(None)
-        }
+        Ok(parse_modified_vd(ModifierKeyword::Private, tokens, context)?.map(PrivateVar::new))
+    }
+}
+
+impl Evaluate for PrivateVar {
+    fn evaluate(&self, operation_context: &OperationContext) -> ShortResult<OperationIO> {
+        self.vd.evaluate(operation_context)
     }
 }
 
@@ -202,7 +488,7 @@ impl PrivateVar {
 /// `<const_var> ::= ju (<private_var> | <global_var> | <vd>)`
 ///
 /// See [PrivateVar], [GlobalVar], [Vd]
-#[derive(PartialEq)]
+#[derive(PartialEq, Serialize)]
 pub enum ConstVar {
     PrivateVar(PrivateVar),
     GlobalVar(GlobalVar),
@@ -210,44 +496,113 @@ pub enum ConstVar {
 }
 
 impl GraphDisplay for ConstVar {
-    fn graph_display(&self, graph: &mut String, id: &mut usize) {
-        graph.push_str(&format!("\nsubgraph ConstVar_{}[ConstVar]", id));
+    fn graph_display(&self, graph: &mut Graph, id: &mut usize) {
+        graph.open("ConstVar", "ConstVar", *id);
         *id += 1;
         match self {
             ConstVar::PrivateVar(private_var) => private_var.graph_display(graph, id),
             ConstVar::GlobalVar(global_var) => global_var.graph_display(graph, id),
             ConstVar::Vd(vd) => vd.graph_display(graph, id),
         }
-        graph.push_str("\nend")
+        graph.close();
     }
 }
 
 impl_debug!(ConstVar);
 
+impl ToSource for ConstVar {
+    fn to_source(&self) -> String {
+        let declaration = match self {
+            ConstVar::PrivateVar(private_var) => private_var.to_source(),
+            ConstVar::GlobalVar(global_var) => global_var.to_source(),
+            ConstVar::Vd(vd) => vd.to_source(),
+        };
+        format!("ju {}", declaration)
+    }
+}
+
+impl FoldConstants for ConstVar {
+    fn fold_constants(&mut self) {
+        match self {
+            ConstVar::PrivateVar(private_var) => private_var.fold_constants(),
+            ConstVar::GlobalVar(global_var) => global_var.fold_constants(),
+            ConstVar::Vd(vd) => vd.fold_constants(),
+        }
+    }
+}
+
+impl Visitable for ConstVar {
+    fn accept<V: Visitor>(&self, visitor: &mut V) {
+        visitor.visit_const_var(self);
+        match self {
+            ConstVar::PrivateVar(private_var) => private_var.accept(visitor),
+            ConstVar::GlobalVar(global_var) => global_var.accept(visitor),
+            ConstVar::Vd(vd) => vd.accept(visitor),
+        }
+    }
+}
+
 impl ConstVar {
     fn new(vd: Vd) -> Self {
         ConstVar::Vd(vd)
     }
 
-    fn parse(tokens: &mut VecDeque<TokenContainer>) -> ResultOption<Self> {
+    fn parse(
+        tokens: &mut VecDeque<TokenContainer>,
+        context: &mut ParseContext,
+    ) -> ResultOption<Self> {
         // <const_var> ::= ju (<private_var> | <global_var> | <vd>)
-        if let some_token!(Token::KeywordModifier(ModifierKeyword::Constant)) = tokens.front() {
-            tokens.pop_front();
-            if let Some(private_var) = PrivateVar::parse(tokens)? {
-                Ok(Some(ConstVar::PrivateVar(private_var)))
-            } else if let Some(global_var) = GlobalVar::parse(tokens)? {
-                Ok(Some(ConstVar::GlobalVar(global_var)))
-            } else if let Some(vd) = Vd::parse(tokens)? {
-                Ok(Some(ConstVar::Vd(vd)))
+        let modifier = expect(tokens, |token| {
+            matches!(token, Token::KeywordModifier(ModifierKeyword::Constant))
+        });
+        if modifier.is_some() {
+            check_next_modifier(&ModifierKeyword::Constant, tokens)?;
+            let declaration_position = peek_position(tokens);
+            let const_var = if let Some(private_var) = PrivateVar::parse(tokens, context)? {
+                ConstVar::PrivateVar(private_var)
+            } else if let Some(global_var) = GlobalVar::parse(tokens, context)? {
+                ConstVar::GlobalVar(global_var)
+            } else if let Some(vd) = Vd::parse(tokens, context)? {
+                ConstVar::Vd(vd)
             } else {
-                Err(CustomError::UnexpectedToken(
-                    "Expected a variable declaration".to_string(),
-                ))
+                return Err(CustomError::unexpected_token(
+                    "Expected a variable declaration",
+                    tokens.front(),
+                ));
+            };
+            // a constant cannot be assigned later, its value must be given
+            let vd = const_var.vd();
+            if vd.exp.is_none() {
+                return Err(missing_part(
+                    &format!("Expected the value of the constant {}", vd.identifier),
+                    tokens.front(),
+                    declaration_position,
+                ));
             }
+            Ok(Some(const_var))
         } else {
             Ok(None)
         }
     }
+
+    /// The declaration made constant.
+    fn vd(&self) -> &Vd {
+        match self {
+            ConstVar::PrivateVar(private_var) => &private_var.vd,
+            ConstVar::GlobalVar(global_var) => &global_var.vd,
+            ConstVar::Vd(vd) => vd,
+        }
+    }
+}
+
+impl Evaluate for ConstVar {
+    fn evaluate(&self, operation_context: &OperationContext) -> ShortResult<OperationIO> {
+        match self {
+            ConstVar::PrivateVar(private_var) => private_var.vd.declare(operation_context, true),
+            ConstVar::GlobalVar(global_var) => global_var.vd.declare(operation_context, true),
+            ConstVar::Vd(vd) => vd.declare(operation_context, true),
+        }
+    }
 }
 
 // --------------
@@ -263,7 +618,7 @@ impl ConstVar {
 /// `<var_dec> ::= <const_var> | <private_var> | <global_var> | <vd>`
 ///
 /// See [ConstVar], [PrivateVar], [GlobalVar], [Vd]
-#[derive(PartialEq)]
+#[derive(PartialEq, Serialize)]
 pub enum VarDec {
     ConstVar(ConstVar),
     PrivateVar(PrivateVar),
@@ -272,8 +627,8 @@ pub enum VarDec {
 }
 
 impl GraphDisplay for VarDec {
-    fn graph_display(&self, graph: &mut String, id: &mut usize) {
-        graph.push_str(&format!("\nsubgraph VarDec_{}[VarDec]", id));
+    fn graph_display(&self, graph: &mut Graph, id: &mut usize) {
+        graph.open("VarDec", "VarDec", *id);
         *id += 1;
         match self {
             VarDec::ConstVar(const_var) => const_var.graph_display(graph, id),
@@ -281,22 +636,89 @@ impl GraphDisplay for VarDec {
             VarDec::GlobalVar(global_var) => global_var.graph_display(graph, id),
             VarDec::Vd(vd) => vd.graph_display(graph, id),
         }
-        graph.push_str("\nend")
+        graph.close();
     }
 }
 
 impl_debug!(VarDec);
 
+impl ToSource for VarDec {
+    fn to_source(&self) -> String {
+        match self {
+            VarDec::ConstVar(const_var) => const_var.to_source(),
+            VarDec::PrivateVar(private_var) => private_var.to_source(),
+            VarDec::GlobalVar(global_var) => global_var.to_source(),
+            VarDec::Vd(vd) => vd.to_source(),
+        }
+    }
+}
+
+impl FoldConstants for VarDec {
+    fn fold_constants(&mut self) {
+        match self {
+            VarDec::ConstVar(const_var) => const_var.fold_constants(),
+            VarDec::PrivateVar(private_var) => private_var.fold_constants(),
+            VarDec::GlobalVar(global_var) => global_var.fold_constants(),
+            VarDec::Vd(vd) => vd.fold_constants(),
+        }
+    }
+}
+
+impl Visitable for VarDec {
+    fn accept<V: Visitor>(&self, visitor: &mut V) {
+        visitor.visit_var_dec(self);
+        match self {
+            VarDec::ConstVar(const_var) => const_var.accept(visitor),
+            VarDec::PrivateVar(private_var) => private_var.accept(visitor),
+            VarDec::GlobalVar(global_var) => global_var.accept(visitor),
+            VarDec::Vd(vd) => vd.accept(visitor),
+        }
+        visitor.leave_var_dec(self);
+    }
+}
+
 impl VarDec {
-    pub(crate) fn parse(tokens: &mut VecDeque<TokenContainer>) -> ResultOption<Self> {
+    /// Name of the declared variable.
+    pub(crate) fn identifier(&self) -> &str {
+        &self.vd().identifier
+    }
+
+    /// Declared type of the variable.
+    pub(crate) fn type_(&self) -> &Type {
+        &self.vd().type_
+    }
+
+    /// Value given to the variable, if any.
+    pub(crate) fn exp(&self) -> Option<&Exp> {
+        self.vd().exp.as_deref()
+    }
+
+    /// Whether the variable is declared with `ju` and cannot be changed.
+    pub(crate) fn is_constant(&self) -> bool {
+        matches!(self, VarDec::ConstVar(_))
+    }
+
+    fn vd(&self) -> &Vd {
+        match self {
+            VarDec::ConstVar(const_var) => const_var.vd(),
+            VarDec::PrivateVar(PrivateVar { vd })
+            | VarDec::GlobalVar(GlobalVar { vd })
+            | VarDec::Vd(vd) => vd,
+        }
+    }
+
+    pub(crate) fn parse(
+        tokens: &mut VecDeque<TokenContainer>,
+        context: &mut ParseContext,
+    ) -> ResultOption<Self> {
         // <var_dec> ::= <const_var> | <private_var> | <global_var> | <vd>
-        if let Some(const_var) = ConstVar::parse(tokens)? {
+        if let Some(const_var) = ConstVar::parse(tokens, context)? {
             Ok(Some(VarDec::ConstVar(const_var)))
-        } else if let Some(private_var) = PrivateVar::parse(tokens)? {
+        } else if let Some(private_var) = PrivateVar::parse(tokens, context)? {
             Ok(Some(VarDec::PrivateVar(private_var)))
-        } else if let Some(global_var) = GlobalVar::parse(tokens)? {
+        } else if let Some(global_var) = GlobalVar::parse(tokens, context)? {
             Ok(Some(VarDec::GlobalVar(global_var)))
-        } else if let Some(vd) = Vd::parse(tokens)? {
+        } else if let Some(vd) = Vd::parse(tokens, context)? {
             Ok(Some(VarDec::Vd(vd)))
         } else {
             Ok(None)
@@ -304,46 +726,193 @@ impl VarDec {
     }
 }
 
+/// Evaluating a declaration gives the value stored in the variable, after checking its type.
+impl Evaluate for VarDec {
+    fn evaluate(&self, operation_context: &OperationContext) -> ShortResult<OperationIO> {
+        match self {
+            VarDec::ConstVar(const_var) => const_var.evaluate(operation_context),
+            VarDec::PrivateVar(private_var) => private_var.evaluate(operation_context),
+            VarDec::GlobalVar(global_var) => global_var.evaluate(operation_context),
+            VarDec::Vd(vd) => vd.evaluate(operation_context),
+        }
+    }
+}
+
 // ---------------
 // --- VarMod ----
 // ---------------
 
-/// `VarMod` represents the left part of a variable modification in the AST. It only contains an
-/// expression, so this is a simple node.
+/// The change made by a [VarMod].
+#[derive(PartialEq, Serialize)]
+pub enum Change {
+    /// A new value for the variable
+    Value(Exp),
+    /// `++`, add one to a number
+    Increment,
+    /// `--`, subtract one from a number
+    Decrement,
+}
+
+/// `VarMod` represents the left part of a variable modification in the AST. It contains the new
+/// value of the variable, or a `++` or `--` that adds or subtracts one to its number.
 ///
 /// Keep in mind that a variable modification follows the syntax `<name> <exp>`. The `<name>` part
 /// is not represented in this node : the LL1 grammar will take care of it -> the `<name>` part is
 /// and identifier and is already detected by the parser when the modification is read.
 ///
+/// The new value cannot start with a binary operator : `x + 1` and `x - 1` are operations on `x`,
+/// not new values.
+///
 /// # Grammar
 ///
-/// `<var_mod> ::= <exp>`
+/// `<var_mod> ::= <exp> | T_INCREMENT | T_DECREMENT`
 ///
 /// See [Exp]
-#[derive(PartialEq)]
+#[derive(PartialEq, Serialize)]
 pub struct VarMod {
-    exp: Exp,
+    change: Change,
+    #[serde(skip)]
+    line: usize,
 }
 
 impl GraphDisplay for VarMod {
-    fn graph_display(&self, graph: &mut String, id: &mut usize) {
-        graph.push_str(&format!("\nsubgraph VarMod_{}[VarMod]", id));
-        self.exp.graph_display(graph, id);
-        graph.push_str("\nend")
+    fn graph_display(&self, graph: &mut Graph, id: &mut usize) {
+        let label = match &self.change {
+            Change::Value(_) => "VarMod",
+            Change::Increment => "VarMod ++",
+            Change::Decrement => "VarMod --",
+        };
+        graph.open("VarMod", label, *id);
+        *id += 1;
+        if let Change::Value(exp) = &self.change {
+            exp.graph_display(graph, id);
+        }
+        graph.close();
     }
 }
 
 impl_debug!(VarMod);
 
+impl ToSource for VarMod {
+    fn to_source(&self) -> String {
+        match &self.change {
+            Change::Value(exp) => exp.to_source(),
+            Change::Increment => String::from("++"),
+            Change::Decrement => String::from("--"),
+        }
+    }
+}
+
+impl FoldConstants for VarMod {
+    fn fold_constants(&mut self) {
+        if let Change::Value(exp) = &mut self.change {
+            exp.fold_constants();
+        }
+    }
+}
+
+impl Visitable for VarMod {
+    fn accept<V: Visitor>(&self, visitor: &mut V) {
+        visitor.visit_var_mod(self);
+        if let Change::Value(exp) = &self.change {
+            exp.accept(visitor);
+        }
+    }
+}
+
 impl VarMod {
-    fn new(exp: Exp) -> Self {
-        Self { exp }
+    fn new(change: Change, line: usize) -> Self {
+        Self { change, line }
     }
 
-    pub(crate) fn parse(tokens: &mut VecDeque<TokenContainer>) -> ResultOption<Self> {
-        match Exp::parse(tokens)? {
-            Some(exp) => Ok(Some(VarMod::new(exp))),
+    pub(crate) fn parse(
+        tokens: &mut VecDeque<TokenContainer>,
+        context: &mut ParseContext,
+    ) -> ResultOption<Self> {
+        // <var_mod> ::= <exp> | T_INCREMENT | T_DECREMENT
+        // in `c ? x sula y`, the sula ends the ternary and is not a value for x, and in `x + 1`
+        // the operator starts an operation on x, it does not give a new value to x
+        match peek_kind(tokens) {
+            Some(Token::KeywordElse) => return Ok(None),
+            Some(token) if token.get_level().is_some() => return Ok(None),
+            _ => {}
+        }
+        let line = tokens.front().map_or(0, |token| token.line);
+        let step = expect(tokens, |token| {
+            matches!(token, Token::Increment | Token::Decrement)
+        });
+        if let Some(step) = step {
+            let change = if step.token == Token::Increment {
+                Change::Increment
+            } else {
+                Change::Decrement
+            };
+            return Ok(Some(VarMod::new(change, line)));
+        }
+        match Exp::parse(tokens, context)? {
+            Some(exp) => Ok(Some(VarMod::new(Change::Value(exp), line))),
             None => Ok(None),
         }
     }
+
+    pub(crate) fn change(&self) -> &Change {
+        &self.change
+    }
+
+    pub(crate) fn line(&self) -> usize {
+        self.line
+    }
+
+    /// The text written between the name of the variable and the modification : a space before a
+    /// value, nothing before `++` and `--`.
+    pub(crate) fn separator(&self) -> &'static str {
+        match self.change {
+            Change::Value(_) => " ",
+            Change::Increment | Change::Decrement => "",
+        }
+    }
+
+    /// Evaluate the new value and store it in the variable `identifier`. The value must match the
+    /// declared type of the variable, and the value stored is returned. `++` and `--` only change
+    /// an int or a float.
+    pub(crate) fn apply(
+        &self,
+        operation_context: &OperationContext,
+        identifier: &str,
+    ) -> ShortResult<OperationIO> {
+        let value = match &self.change {
+            Change::Value(exp) => exp.evaluate(operation_context)?,
+            Change::Increment | Change::Decrement => {
+                let step = if self.change == Change::Increment {
+                    1
+                } else {
+                    -1
+                };
+                let symbol = self.to_source();
+                match operation_context.variables.borrow().get(identifier)? {
+                    VariableType::Integer(value) => match value.checked_add(step) {
+                        Some(value) => VariableType::Integer(value),
+                        None => {
+                            return Err(CustomError::IntegerOverflow(format!(
+                                "{}{}",
+                                value, symbol
+                            )))
+                        }
+                    },
+                    VariableType::Float(value) => VariableType::Float(value + step as FloatType),
+                    value => {
+                        return Err(CustomError::InvalidOperation(format!(
+                            "Cannot apply {} to a {}",
+                            symbol,
+                            value.type_name()
+                        )))
+                    }
+                }
+            }
+        };
+        operation_context
+            .variables
+            .borrow_mut()
+            .set(identifier, value, self.line)
+    }
 }
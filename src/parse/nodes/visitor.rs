@@ -0,0 +1,125 @@
+//! Walk the AST without changing it, for the tools that analyse the code like a linter.
+//!
+//! Each node implements [Visitable] : [Visitable::accept] calls the method of the [Visitor] for the
+//! node, then `accept` on its children in the order of the code. The methods of the [Visitor] do
+//! nothing by default, so a visitor only overrides the nodes it is interested in.
+//!
+//! The nodes that open a scope or a declaration also call a `leave_` method after their children,
+//! for the visitors that follow the variables visible at each point of the code.
+
+use crate::parse::nodes::blocs::{Biuli, KName, KStart, Kodi, Scope, ScopeBase, Spoki};
+use crate::parse::nodes::classes::{ClassDec, ClassMember};
+use crate::parse::nodes::expressions::{
+    Exp, ExpBase, ExpTp, IdUse, IdUseV, NatCall, Return, Sta, StaL, Ternary,
+};
+use crate::parse::nodes::files_node::FileNode;
+use crate::parse::nodes::functions::{FctDec, Param, Params};
+use crate::parse::nodes::id_nodes::{CGet, FieldAccess, IdGet, OpIn, TupleNode};
+use crate::parse::nodes::if_else::{Cond, Ij, Sula};
+use crate::parse::nodes::loops::Luba;
+use crate::parse::nodes::matches::{Kasu, KasuArm};
+use crate::parse::nodes::operations::{
+    ArrayNode, NoValueN, OperationN, TakePriority, TakePriorityLast, TakePriorityN, UnaryTP,
+    ValueBase, ValueNode,
+};
+use crate::parse::nodes::statements::Statement;
+use crate::parse::nodes::vars::{ConstVar, GlobalVar, PrivateVar, Type, VarDec, VarMod, Vd};
+
+/// Called on each node of the tree by [Visitable::accept], a parent before its children.
+pub(crate) trait Visitor {
+    // --- file ---
+
+    fn visit_file(&mut self, _node: &FileNode) {}
+    fn visit_statement(&mut self, _node: &Statement) {}
+
+    // --- expressions ---
+
+    fn visit_nat_call(&mut self, _node: &NatCall) {}
+    fn visit_id_use(&mut self, _node: &IdUse) {}
+    fn visit_id_use_v(&mut self, _node: &IdUseV) {}
+    fn visit_exp_base(&mut self, _node: &ExpBase) {}
+    fn visit_exp_tp(&mut self, _node: &ExpTp) {}
+    fn visit_exp(&mut self, _node: &Exp) {}
+    fn visit_ternary(&mut self, _node: &Ternary) {}
+    fn visit_return(&mut self, _node: &Return) {}
+    fn visit_sta(&mut self, _node: &Sta) {}
+    fn visit_sta_l(&mut self, _node: &StaL) {}
+
+    // --- blocs ---
+
+    fn visit_k_name(&mut self, _node: &KName) {}
+    fn visit_k_start(&mut self, _node: &KStart) {}
+    fn visit_kodi(&mut self, _node: &Kodi) {}
+    fn visit_biuli(&mut self, _node: &Biuli) {}
+    fn visit_spoki(&mut self, _node: &Spoki) {}
+    fn visit_scope_base(&mut self, _node: &ScopeBase) {}
+    fn visit_scope(&mut self, _node: &Scope) {}
+
+    // --- vars ---
+
+    fn visit_type(&mut self, _node: &Type) {}
+    fn visit_vd(&mut self, _node: &Vd) {}
+    fn visit_global_var(&mut self, _node: &GlobalVar) {}
+    fn visit_private_var(&mut self, _node: &PrivateVar) {}
+    fn visit_const_var(&mut self, _node: &ConstVar) {}
+    fn visit_var_dec(&mut self, _node: &VarDec) {}
+    fn visit_var_mod(&mut self, _node: &VarMod) {}
+
+    // --- functions ---
+
+    fn visit_param(&mut self, _node: &Param) {}
+    fn visit_params(&mut self, _node: &Params) {}
+    fn visit_fct_dec(&mut self, _node: &FctDec) {}
+
+    // --- classes ---
+
+    fn visit_class_member(&mut self, _node: &ClassMember) {}
+    fn visit_class_dec(&mut self, _node: &ClassDec) {}
+
+    // --- id nodes ---
+
+    fn visit_tuple(&mut self, _node: &TupleNode) {}
+    fn visit_c_get(&mut self, _node: &CGet) {}
+    fn visit_id_get(&mut self, _node: &IdGet) {}
+    fn visit_field_access(&mut self, _node: &FieldAccess) {}
+    fn visit_op_in(&mut self, _node: &OpIn) {}
+
+    // --- if else ---
+
+    fn visit_sula(&mut self, _node: &Sula) {}
+    fn visit_ij(&mut self, _node: &Ij) {}
+    fn visit_cond(&mut self, _node: &Cond) {}
+
+    // --- loops ---
+
+    fn visit_luba(&mut self, _node: &Luba) {}
+
+    // --- matches ---
+
+    fn visit_kasu_arm(&mut self, _node: &KasuArm) {}
+    fn visit_kasu(&mut self, _node: &Kasu) {}
+
+    // --- operations ---
+
+    fn visit_value_base(&mut self, _node: &ValueBase) {}
+    fn visit_array(&mut self, _node: &ArrayNode) {}
+    fn visit_value_node(&mut self, _node: &ValueNode) {}
+    fn visit_take_priority(&mut self, _node: &TakePriority) {}
+    fn visit_unary_tp(&mut self, _node: &UnaryTP) {}
+    fn visit_operation_n(&mut self, _node: &OperationN) {}
+    fn visit_take_priority_n(&mut self, _node: &TakePriorityN) {}
+    fn visit_take_priority_last(&mut self, _node: &TakePriorityLast) {}
+    fn visit_no_value_n(&mut self, _node: &NoValueN) {}
+
+    // --- after the children ---
+
+    fn leave_sta_l(&mut self, _node: &StaL) {}
+    fn leave_var_dec(&mut self, _node: &VarDec) {}
+    fn leave_fct_dec(&mut self, _node: &FctDec) {}
+    fn leave_class_dec(&mut self, _node: &ClassDec) {}
+}
+
+/// A node of the AST that can be walked by a [Visitor].
+pub(crate) trait Visitable {
+    fn accept<V: Visitor>(&self, visitor: &mut V);
+}
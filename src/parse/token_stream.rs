@@ -0,0 +1,33 @@
+use std::collections::VecDeque;
+
+use crate::tokens::{Token, TokenContainer};
+
+// Helpers to read the tokens of the parser without repeating the match of the next token before
+// consuming it.
+
+/// Consume the next token only when `predicate` accepts it. The tokens are not changed otherwise.
+pub(crate) fn expect(
+    tokens: &mut VecDeque<TokenContainer>,
+    predicate: impl FnOnce(&Token) -> bool,
+) -> Option<TokenContainer> {
+    if tokens
+        .front()
+        .is_some_and(|container| predicate(&container.token))
+    {
+        tokens.pop_front()
+    } else {
+        None
+    }
+}
+
+/// The next token, without consuming it and without its position.
+pub(crate) fn peek_kind(tokens: &VecDeque<TokenContainer>) -> Option<&Token> {
+    tokens.front().map(|container| &container.token)
+}
+
+/// Line and column of the next token, used to place an error. `None` at the end of the file.
+pub(crate) fn peek_position(tokens: &VecDeque<TokenContainer>) -> Option<(usize, usize)> {
+    tokens
+        .front()
+        .map(|container| (container.line, container.column))
+}
@@ -1,5 +1,8 @@
 use thiserror::Error;
 
+use crate::execute::IntType;
+use crate::tokens::TokenContainer;
+
 #[allow(dead_code)]
 #[derive(Error, Debug, PartialEq)]
 pub enum NotYetImplementedType {
@@ -22,15 +25,121 @@ pub enum NotYetImplementedType {
 pub enum CustomError {
     #[error("Invalid float: {0} at line {1}")]
     InvalidFloat(String, usize),
+    #[error("Invalid int: {0} at line {1}")]
+    InvalidInt(String, usize),
     #[error("Invalid string: {0} at line {1}")]
     InvalidString(String, usize),
-    #[error("Unexpected token: {0}")]
-    UnexpectedToken(String),
+    #[error("Invalid char: {0} at line {1}")]
+    InvalidCharLiteral(String, usize),
+    #[error("Invalid comment: {0} at line {1}")]
+    InvalidComment(String, usize),
+    #[error("Invalid character {0} at line {1} column {2}")]
+    InvalidCharacter(char, usize, usize),
+    /// The line 0 is an unknown position, at the end of the file
+    #[error("Unexpected token: {0}{}", at_position(*.1, *.2))]
+    UnexpectedToken(String, usize, usize),
+    #[error("The parenthesis opened at line {0} column {1} is never closed")]
+    UnclosedParenthesis(usize, usize),
+    #[error("Invalid operation: {0}")]
+    InvalidOperation(String),
+    #[error("Invalid call: {0} at line {1}")]
+    InvalidCall(String, usize),
+    #[error("Cannot redefine the value of the constant {0} at line {1}")]
+    ConstantReassignment(String, usize),
+    #[error("Integer overflow: the result of {0} is too big")]
+    IntegerOverflow(String),
+    #[error("Recursion limit: the maximum call depth of {0} is exceeded")]
+    RecursionLimit(usize),
+    #[error("Unknown variable: {0}")]
+    UnknownVariable(String),
+    #[error("Type mismatch: {0}")]
+    TypeMismatch(String),
+    #[error("Index out of bounds: {0} for an array of length {1}")]
+    IndexOutOfBounds(IntType, usize),
+    #[error("Strict mode: {0}")]
+    StrictMode(String),
+    #[error("The file {0} does not have a valid extension")]
+    WrongExtension(String),
+    #[error("The file {0} does not exist")]
+    FileNotFound(String),
+    #[error("Cannot read the file {0}")]
+    InvalidFile(String),
     #[error("Not yet implemented: {0}")]
     NotYetImplemented(NotYetImplementedType),
     // Add other kinds of errors as needed
 }
 
+impl CustomError {
+    /// Create an [CustomError::UnexpectedToken] at the position of the given token. When there is
+    /// no token (end of the file), the position is unknown : `0:0`, not written in the message.
+    pub fn unexpected_token(message: &str, token: Option<&TokenContainer>) -> Self {
+        match token {
+            Some(token) => {
+                CustomError::UnexpectedToken(message.to_string(), token.line, token.column)
+            }
+            None => CustomError::UnexpectedToken(message.to_string(), 0, 0),
+        }
+    }
+
+    /// Line and column (when known) of the error, both starting at 1. The errors of the
+    /// evaluation and the errors at the end of the file have no position.
+    pub fn position(&self) -> Option<(usize, Option<usize>)> {
+        let position = match self {
+            CustomError::UnexpectedToken(_, line, column)
+            | CustomError::InvalidCharacter(_, line, column) => (*line, Some(*column)),
+            CustomError::UnclosedParenthesis(line, column) => (*line, Some(*column)),
+            CustomError::InvalidFloat(_, line)
+            | CustomError::InvalidInt(_, line)
+            | CustomError::InvalidString(_, line)
+            | CustomError::InvalidCharLiteral(_, line)
+            | CustomError::InvalidComment(_, line)
+            | CustomError::InvalidCall(_, line)
+            | CustomError::ConstantReassignment(_, line) => (*line, None),
+            _ => return None,
+        };
+        if position.0 == 0 {
+            None
+        } else {
+            Some(position)
+        }
+    }
+}
+
+/// The position written after the message of an error, empty when it is unknown (line 0).
+fn at_position(line: usize, column: usize) -> String {
+    if line == 0 {
+        String::new()
+    } else {
+        format!(" at line {} column {}", line, column)
+    }
+}
+
+/// Render the error like rustc : the message, then the line of the `source` where the error is
+/// with a `^` under the column. When the column is after the end of the line, the `^` is placed
+/// right after the last character.
+pub fn render_diagnostic(source: &str, err: &CustomError) -> String {
+    let mut diagnostic = format!("error: {}", err);
+    let Some((line, column)) = err.position() else {
+        return diagnostic;
+    };
+    let Some(code) = source.lines().nth(line - 1) else {
+        return diagnostic;
+    };
+    let margin = " ".repeat(line.to_string().len());
+    diagnostic.push_str(&format!("\n{} |\n{} | {}", margin, line, code));
+    if let Some(column) = column {
+        let caret = column.clamp(1, code.chars().count() + 1);
+        // a tab takes the same width in the caret line as in the code
+        let offset: String = code
+            .chars()
+            .take(caret - 1)
+            .map(|ch| if ch == '\t' { '\t' } else { ' ' })
+            .collect();
+        diagnostic.push_str(&format!("\n{} | {}^", margin, offset));
+    }
+    diagnostic
+}
+
 pub type ShortResult<T> = Result<T, CustomError>;
 
 pub type ResultOption<T> = ShortResult<Option<T>>;
@@ -1,4 +1,7 @@
+mod check_tests;
 mod execute_tests;
 mod full_evaluation_tests;
+mod get_file_content_tests;
 mod parse_tests;
+mod skr_errors_tests;
 mod tokens_tests;
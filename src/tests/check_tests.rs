@@ -0,0 +1,159 @@
+use crate::execute::check::check;
+use crate::execute::variables::VariableType;
+use crate::execute::OperationContext;
+use crate::parse::nodes::FoldConstants;
+use crate::parse::parse;
+use crate::skr_errors::CustomError;
+use crate::tokens::tokenize;
+
+fn check_file_with(
+    code: &str,
+    operation_context: &OperationContext,
+) -> Result<(), Vec<CustomError>> {
+    let mut program = parse(tokenize(String::from(code)).unwrap())
+        .unwrap()
+        .unwrap();
+    program.fold_constants();
+    check(&program, operation_context)
+}
+
+fn check_file(code: &str) -> Result<(), Vec<CustomError>> {
+    check_file_with(code, &OperationContext::default())
+}
+
+#[test]
+fn test_check_valid_program() {
+    assert_eq!(
+        Ok(()),
+        check_file(
+            "fu int x 1\nx 2\nx++\nkodi {\n    ju int x 3\n    int y (x)\n}\nx 4\n\
+             ums f (int n) -> int { n n + 1\nei n }\nkat Point {\n    int x 0\n}"
+        )
+    );
+    // a variable declared null takes the type of its first value during the evaluation
+    assert_eq!(Ok(()), check_file("null n nul\nn 1"));
+    // a value that is not a literal is only known during the evaluation
+    assert_eq!(Ok(()), check_file("fu skr s \"a\"\ns (s)"));
+}
+
+#[test]
+fn test_check_redeclaration() {
+    assert_eq!(
+        Err(vec![CustomError::InvalidOperation(String::from(
+            "The variable x is already declared in this scope"
+        ))]),
+        check_file("fu int x 1\nfu int x 2")
+    );
+    assert_eq!(
+        Err(vec![CustomError::InvalidOperation(String::from(
+            "The variable f is already declared in this scope"
+        ))]),
+        check_file("int f 0\nums f () { ei 1 }")
+    );
+    // the parameter can shadow a global variable, not another parameter
+    assert_eq!(Ok(()), check_file("int n 0\nums f (int n) { ei n }"));
+    assert_eq!(
+        Err(vec![CustomError::InvalidOperation(String::from(
+            "The variable n is already declared in this scope"
+        ))]),
+        check_file("ums f (int n, int n) { ei n }")
+    );
+}
+
+#[test]
+fn test_check_constant_reassignment() {
+    assert_eq!(
+        Err(vec![CustomError::ConstantReassignment(
+            String::from("x"),
+            3
+        )]),
+        check_file("ju int x 1\n\nx 2")
+    );
+    assert_eq!(
+        Err(vec![CustomError::ConstantReassignment(
+            String::from("x"),
+            4
+        )]),
+        check_file("fu int x 1\nkodi {\n    ju int x 2\n    x 3\n}\nx 4")
+    );
+    // the modifications in a branch that is never executed are checked too
+    assert_eq!(
+        Err(vec![CustomError::ConstantReassignment(
+            String::from("i"),
+            2
+        )]),
+        check_file("ju int i 1\nij no { i-- }")
+    );
+}
+
+#[test]
+fn test_check_every_error() {
+    assert_eq!(
+        Err(vec![
+            CustomError::UnknownVariable(String::from("y")),
+            CustomError::TypeMismatch(String::from(
+                "Cannot assign a string to a variable of type int"
+            )),
+            CustomError::InvalidOperation(String::from(
+                "The variable x is already declared in this scope"
+            )),
+        ]),
+        check_file("int x 1\ny 2\nx \"a\"\nint x 3")
+    );
+    // a global variable can be declared after the function that changes it
+    assert_eq!(Ok(()), check_file("ums f () { count++ }\nint count 0\nf()"));
+}
+
+#[test]
+fn test_check_without_evaluation() {
+    let context = OperationContext::default();
+    context
+        .variables
+        .borrow_mut()
+        .declare("x", VariableType::Integer(1))
+        .unwrap();
+    assert_eq!(Ok(()), check_file_with("x 2\nint y 3", &context));
+    // nothing is evaluated, the context is not changed
+    assert_eq!(
+        Ok(VariableType::Integer(1)),
+        context.variables.borrow().get("x")
+    );
+    assert_eq!(
+        Err(CustomError::UnknownVariable(String::from("y"))),
+        context.variables.borrow().get("y")
+    );
+    assert_eq!(
+        Err(vec![CustomError::InvalidOperation(String::from(
+            "The variable x is already declared in this scope"
+        ))]),
+        check_file_with("int x 2", &context)
+    );
+}
+
+#[test]
+fn test_check_declaration_literal() {
+    assert_eq!(
+        Err(vec![CustomError::TypeMismatch(String::from(
+            "Cannot assign a string to a variable of type int"
+        ))]),
+        check_file("int x \"a\"")
+    );
+    // the variable is still declared after the error
+    assert_eq!(
+        Err(vec![
+            CustomError::TypeMismatch(String::from(
+                "Cannot assign a int to a variable of type ioi"
+            )),
+            CustomError::ConstantReassignment(String::from("b"), 2),
+        ]),
+        check_file("ju ioi b 1\nb io")
+    );
+    // an int is converted to a float, and the operations are folded into a literal first
+    assert_eq!(Ok(()), check_file("dar d 1\nint i 2 * 3"));
+    assert_eq!(
+        Err(vec![CustomError::TypeMismatch(String::from(
+            "Cannot assign a float to a variable of type int"
+        ))]),
+        check_file("int i 2 * 1.5")
+    );
+}
@@ -1,6 +1,11 @@
-use crate::execute::Evaluate;
-use crate::parse::nodes::operations::TakePriorityLast;
+use std::cmp::Ordering;
+
+use crate::execute::variables::VariableType;
+use crate::execute::{Evaluate, OperationContext};
+use crate::parse::context::ParseContext;
+use crate::parse::nodes::operations::{Operations, TakePriorityLast};
 use crate::parse::nodes::Parsable;
+use crate::skr_errors::CustomError;
 use crate::tokens::{Token, TokenContainer};
 use std::collections::VecDeque;
 
@@ -11,9 +16,113 @@ fn add_test() {
         .map(|x| x.into())
         .collect();
 
-    let res = TakePriorityLast::parse(&mut vec)
+    let res = TakePriorityLast::parse(&mut vec, &mut ParseContext::new())
         .unwrap()
         .unwrap()
-        .evaluate(&());
-    assert_eq!(res, 3);
+        .evaluate(&OperationContext::default());
+    assert_eq!(res, Ok(VariableType::Integer(3)));
+}
+
+#[test]
+fn test_float_display() {
+    for (value, expected) in [
+        (1.0, "1.0"),
+        (1.5, "1.5"),
+        (0.1, "0.1"),
+        (-2.0, "-2.0"),
+        (1.0 / 3.0, "0.3333333333333333"),
+        (f64::INFINITY, "inf"),
+    ] {
+        assert_eq!(expected, VariableType::Float(value).to_string());
+    }
+}
+
+#[test]
+fn test_variable_ordering() {
+    let string = |value: &str| VariableType::String(String::from(value));
+    let order = |left: VariableType, right: VariableType| left.compare(&right);
+    assert_eq!(
+        Some(Ordering::Less),
+        order(VariableType::Integer(1), VariableType::Integer(2))
+    );
+    assert_eq!(
+        Some(Ordering::Greater),
+        order(VariableType::Float(2.5), VariableType::Float(-1.0))
+    );
+    // an int is converted to a float to be compared to a float
+    assert_eq!(
+        Some(Ordering::Less),
+        order(VariableType::Integer(1), VariableType::Float(1.5))
+    );
+    assert_eq!(
+        Some(Ordering::Greater),
+        order(VariableType::Float(2.5), VariableType::Integer(2))
+    );
+    // like `1 == 1.0` in the language, which is not the equality of the values in rust
+    assert_eq!(
+        Some(Ordering::Equal),
+        order(VariableType::Integer(1), VariableType::Float(1.0))
+    );
+    assert_ne!(VariableType::Integer(1), VariableType::Float(1.0));
+    assert_eq!(
+        Ok(VariableType::Boolean(true)),
+        Operations::Equal.apply(VariableType::Integer(1), VariableType::Float(1.0))
+    );
+    assert_eq!(Some(Ordering::Less), order(string("abc"), string("abd")));
+    assert_eq!(
+        Some(Ordering::Less),
+        order(VariableType::Char('a'), VariableType::Char('b'))
+    );
+
+    for (left, right) in [
+        (VariableType::Integer(1), VariableType::Boolean(true)),
+        (VariableType::Boolean(false), VariableType::Boolean(true)),
+        (string("1"), VariableType::Integer(1)),
+        (VariableType::Char('a'), string("a")),
+        (VariableType::Null, VariableType::Null),
+        (VariableType::Float(f64::NAN), VariableType::Float(1.0)),
+        (
+            VariableType::Array(vec![VariableType::Integer(1)]),
+            VariableType::Array(vec![VariableType::Integer(2)]),
+        ),
+    ] {
+        assert_eq!(None, left.compare(&right), "{:?} {:?}", left, right);
+    }
+    // a NaN is never ordered, the other values without an order cannot be compared
+    assert_eq!(
+        Ok(VariableType::Boolean(false)),
+        Operations::LessEqual.apply(VariableType::Float(f64::NAN), VariableType::Integer(1))
+    );
+    assert_eq!(
+        Err(CustomError::InvalidOperation(String::from(
+            "Cannot apply < to a bool and a bool"
+        ))),
+        Operations::Less.apply(VariableType::Boolean(false), VariableType::Boolean(true))
+    );
+}
+
+#[test]
+fn test_variable_partial_ord() {
+    assert!(VariableType::Integer(1) < VariableType::Integer(2));
+    assert!(VariableType::Integer(1) < VariableType::Float(1.5));
+    assert!(VariableType::Float(2.5) > VariableType::Integer(2));
+    assert!(VariableType::Char('a') < VariableType::Char('b'));
+    // consistent with PartialEq : the equal values are Equal, the others are not
+    for (left, right) in [
+        (VariableType::Integer(1), VariableType::Float(1.0)),
+        (VariableType::Boolean(false), VariableType::Boolean(true)),
+        (VariableType::Float(f64::NAN), VariableType::Float(f64::NAN)),
+        (VariableType::Null, VariableType::Integer(0)),
+    ] {
+        assert_ne!(left, right);
+        assert_eq!(None, left.partial_cmp(&right), "{:?} {:?}", left, right);
+    }
+    for value in [
+        VariableType::Integer(1),
+        VariableType::Boolean(true),
+        VariableType::Null,
+        VariableType::Array(vec![VariableType::Integer(1)]),
+    ] {
+        assert_eq!(Some(Ordering::Equal), value.partial_cmp(&value.clone()));
+    }
 }
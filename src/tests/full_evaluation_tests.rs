@@ -1,14 +1,27 @@
-use crate::execute::{Evaluate, OperationIO};
+use std::cell::RefCell;
+
+use crate::execute::variables::{VariableStack, VariableType};
+use crate::execute::{Evaluate, OperationContext};
+use crate::parse::context::ParseContext;
 use crate::parse::nodes::operations::TakePriorityLast;
-use crate::parse::nodes::Parsable;
+use crate::parse::nodes::{FoldConstants, Parsable};
+use crate::parse::parse;
+use crate::skr_errors::CustomError;
 use crate::tokens::tokenize;
+use crate::utils::read;
+
+fn assert_evaluation(file: String, expected: i64) {
+    assert_evaluation_value(file, VariableType::Integer(expected));
+}
 
-fn assert_evaluation(file: String, expected: OperationIO) {
+fn assert_evaluation_value(file: String, expected: VariableType) {
     let mut tokens = tokenize(file).unwrap();
-    let ast = TakePriorityLast::parse(&mut tokens).unwrap().unwrap();
+    let ast = TakePriorityLast::parse(&mut tokens, &mut ParseContext::new())
+        .unwrap()
+        .unwrap();
     println!("{:?}", ast);
-    let result = ast.evaluate(&());
-    assert_eq!(result, expected, "{:?}", ast);
+    let result = ast.evaluate(&OperationContext::default());
+    assert_eq!(result, Ok(expected), "{:?}", ast);
 }
 
 #[test]
@@ -53,9 +66,1105 @@ fn test_combination() {
     assert_evaluation(String::from("3*4-1*2"), 10);
 }
 
-// TODO - fix in a later pull request
-// #[test]
-// fn test_combination_difficult() {
-//     assert_evaluation(String::from("3+2-5"), 0);
-//     assert_evaluation(String::from("2*5/10"), 1);
-// }
+#[test]
+fn test_modulo() {
+    assert_evaluation(String::from("7%3"), 1);
+    assert_evaluation(String::from("6 % 3"), 0);
+    assert_evaluation(read("resources/test_programs/modulo.skrb").unwrap(), 1);
+}
+
+#[test]
+fn test_pow() {
+    assert_evaluation(read("resources/test_programs/pow.skrb").unwrap(), 1024);
+    assert_evaluation(String::from("2 ^ 3 * 2"), 16);
+    assert_evaluation(String::from("2 * 3 ** 2"), 18);
+    assert_evaluation_value(String::from("2 ** -1"), VariableType::Float(0.5));
+    assert_evaluation_value(String::from("4.0 ** 0.5"), VariableType::Float(2.0));
+    assert_eq!(
+        Err(CustomError::IntegerOverflow(String::from("10 ** 100"))),
+        evaluate_file("10 ** 100")
+    );
+}
+
+#[test]
+fn test_division_by_zero() {
+    for file in ["1/0", "1%0", "1.5/0", "1/0.0"] {
+        let mut tokens = tokenize(String::from(file)).unwrap();
+        let ast = TakePriorityLast::parse(&mut tokens, &mut ParseContext::new())
+            .unwrap()
+            .unwrap();
+        assert!(matches!(
+            ast.evaluate(&OperationContext::default()),
+            Err(CustomError::InvalidOperation(_))
+        ));
+    }
+}
+
+#[test]
+fn test_float_promotion() {
+    assert_evaluation_value(String::from("1.5 + 1"), VariableType::Float(2.5));
+    assert_evaluation_value(String::from("1 + 1.5"), VariableType::Float(2.5));
+    assert_evaluation_value(String::from("3 / 2.0"), VariableType::Float(1.5));
+    assert_evaluation_value(String::from("0.5 * 0.5"), VariableType::Float(0.25));
+    assert_evaluation_value(String::from("3 / 2"), VariableType::Integer(1));
+}
+
+#[test]
+fn test_comparison_and_logic() {
+    assert_evaluation_value(String::from("1 + 1 == 2"), VariableType::Boolean(true));
+    assert_evaluation_value(String::from("1 != 1.0"), VariableType::Boolean(false));
+    assert_evaluation_value(String::from("\"a\" == \"b\""), VariableType::Boolean(false));
+    assert_evaluation_value(
+        String::from("\"a\" + \"b\""),
+        VariableType::String(String::from("ab")),
+    );
+}
+
+#[test]
+fn test_mixed_arithmetic() {
+    assert_evaluation_value(String::from("2 + 3.0"), VariableType::Float(5.0));
+    assert_evaluation_value(String::from("2.5 * 2"), VariableType::Float(5.0));
+    assert_evaluation(String::from("2 + 3"), 5);
+    assert_evaluation(String::from("7 / 2"), 3);
+    assert_eq!(
+        Err(CustomError::InvalidOperation(String::from(
+            "Cannot apply + to a bool and a int"
+        ))),
+        evaluate_file("io + 1")
+    );
+}
+
+#[test]
+fn test_identifier_operations() {
+    // an identifier followed by an operator starts an operation, without parentheses
+    assert_eq!(
+        Ok(VariableType::Integer(6)),
+        evaluate_file("fu int x 5\nx + 1")
+    );
+    assert_eq!(
+        Ok(VariableType::Integer(4)),
+        evaluate_file("fu int x 5\nx - 1")
+    );
+    assert_eq!(
+        Ok(VariableType::Integer(2)),
+        evaluate_file("fu int x 5\nx % 3")
+    );
+    assert_eq!(
+        Ok(VariableType::Integer(25)),
+        evaluate_file("fu int x 5\nx ** 2")
+    );
+    assert_eq!(
+        Ok(VariableType::Boolean(true)),
+        evaluate_file("fu int x 2\nx < 3")
+    );
+    assert_eq!(
+        Ok(VariableType::Boolean(true)),
+        evaluate_file("fu int x 5\nx == 5 && x != 3")
+    );
+    assert_eq!(
+        Ok(VariableType::Integer(3)),
+        evaluate_file("ums f (int n) { ei n }\nf(1) + 1 * 2")
+    );
+    assert_eq!(
+        Ok(VariableType::Boolean(true)),
+        evaluate_file("ums f (int n) { ei n }\nf(1) < 3")
+    );
+    // an operation on a variable does not change it
+    assert_eq!(
+        Ok(VariableType::Integer(5)),
+        evaluate_file("fu int x 5\nx + 1\nx")
+    );
+    assert_eq!(
+        Ok(VariableType::Integer(5)),
+        evaluate_file("fu int x 5\nx - 1\nx")
+    );
+    // in the middle of an operation, the identifier is only a value
+    assert_eq!(
+        Ok(VariableType::Integer(4)),
+        evaluate_file("fu int x 5\n1 + x - 2")
+    );
+    assert_eq!(
+        Ok(VariableType::Integer(3)),
+        evaluate_file("fu int i 0\nluba i < 3 { i++ }\ni")
+    );
+}
+
+#[test]
+fn test_string_concatenation() {
+    assert_eq!(
+        Ok(VariableType::String(String::from("Hello, World!"))),
+        evaluate_file(&read("resources/test_programs/concat.skrb").unwrap())
+    );
+    assert_eq!(
+        Err(CustomError::InvalidOperation(String::from(
+            "Cannot apply + to a int and a string"
+        ))),
+        evaluate_file("fu skr a \"a\"\n1 + a")
+    );
+}
+
+#[test]
+fn test_and_or() {
+    assert_evaluation_value(String::from("io && no"), VariableType::Boolean(false));
+    assert_evaluation_value(String::from("no || io"), VariableType::Boolean(true));
+    assert_evaluation_value(
+        String::from("1 == 2 || 2 == 2 && 3 != 3"),
+        VariableType::Boolean(false),
+    );
+    // the right side is not evaluated
+    assert_evaluation_value(String::from("no && 1 / 0"), VariableType::Boolean(false));
+    assert_evaluation_value(String::from("io || 1 / 0"), VariableType::Boolean(true));
+    assert_eq!(
+        Ok(VariableType::Boolean(true)),
+        evaluate_file(&read("resources/test_programs/cmp/and_or.skrb").unwrap())
+    );
+}
+
+#[test]
+fn test_ternary() {
+    assert_eq!(
+        Ok(VariableType::Integer(2)),
+        evaluate_file("1 == 2 ? 1 sula 2")
+    );
+    assert_eq!(
+        Ok(VariableType::String(String::from("b"))),
+        evaluate_file("fu int x 2\n(1 == x) ? \"a\" sula (2 == x) ? \"b\" sula \"c\"")
+    );
+    // the other expression is not evaluated
+    assert_eq!(
+        Ok(VariableType::Integer(1)),
+        evaluate_file("io ? 1 sula 1 / 0")
+    );
+    assert_eq!(
+        Err(CustomError::InvalidOperation(String::from(
+            "The condition of a ternary must be a bool, not a int"
+        ))),
+        evaluate_file("1 ? 1 sula 2")
+    );
+}
+
+#[test]
+fn test_ternary_type_mismatch() {
+    for file in [
+        "io ? 1 sula \"x\"",
+        "no ? 1 sula \"x\"",
+        "fu int x 2\nx == 2 ? x sula \"x\"",
+    ] {
+        assert_eq!(
+            Err(CustomError::TypeMismatch(String::from(
+                "The expressions of a ternary must have the same type, not a int and a string"
+            ))),
+            evaluate_file(file),
+            "{}",
+            file
+        );
+    }
+    assert_eq!(
+        Ok(VariableType::Integer(2)),
+        evaluate_file("fu int x 2\nx == 2 ? x sula 3")
+    );
+}
+
+#[test]
+fn test_operation_type_mismatch() {
+    for (file, message) in [
+        ("\"a\" + 1", "Cannot apply + to a string and a int"),
+        ("io * 2", "Cannot apply * to a bool and a int"),
+        ("1 == io", "Cannot apply == to a int and a bool"),
+        ("1 && io", "Cannot apply && to a int and a bool"),
+        ("no || \"a\"", "Cannot apply || to a bool and a string"),
+    ] {
+        let mut tokens = tokenize(String::from(file)).unwrap();
+        let ast = TakePriorityLast::parse(&mut tokens, &mut ParseContext::new())
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            Err(CustomError::InvalidOperation(String::from(message))),
+            ast.evaluate(&OperationContext::default())
+        );
+    }
+}
+
+#[test]
+fn test_combination_difficult() {
+    assert_evaluation(String::from("3+2-5"), 0);
+    assert_evaluation(String::from("2*5/10"), 1);
+    assert_evaluation(String::from("10-2-3"), 5);
+    assert_evaluation(String::from("1 + 7 % 3 * 2"), 3);
+}
+
+#[test]
+fn test_parentheses() {
+    assert_evaluation(String::from("(1+2)*3"), 9);
+    assert_evaluation(String::from("10-(2-3)"), 11);
+    assert_evaluation(String::from("2*(3+4)*2"), 28);
+}
+
+#[test]
+fn test_unary() {
+    assert_evaluation(String::from("-5"), -5);
+    assert_evaluation(String::from("--5"), 5);
+    assert_evaluation(String::from("2 * -3"), -6);
+    assert_evaluation(String::from("-(1 + 2)"), -3);
+    assert_evaluation_value(String::from("-1.5"), VariableType::Float(-1.5));
+    assert_evaluation_value(String::from("!io"), VariableType::Boolean(false));
+    assert_evaluation_value(String::from("!!io"), VariableType::Boolean(true));
+}
+
+#[test]
+fn test_unary_type_mismatch() {
+    for file in ["-io", "!1", "+\"a\""] {
+        let mut tokens = tokenize(String::from(file)).unwrap();
+        let ast = TakePriorityLast::parse(&mut tokens, &mut ParseContext::new())
+            .unwrap()
+            .unwrap();
+        assert!(matches!(
+            ast.evaluate(&OperationContext::default()),
+            Err(CustomError::InvalidOperation(_))
+        ));
+    }
+}
+
+#[test]
+fn test_array() {
+    assert_evaluation_value(String::from("[]"), VariableType::Array(vec![]));
+    assert_evaluation_value(
+        String::from("[1, 2 + 3, 4 * 2]"),
+        VariableType::Array(vec![
+            VariableType::Integer(1),
+            VariableType::Integer(5),
+            VariableType::Integer(8),
+        ]),
+    );
+    assert_evaluation_value(
+        String::from("[[1], []]"),
+        VariableType::Array(vec![
+            VariableType::Array(vec![VariableType::Integer(1)]),
+            VariableType::Array(vec![]),
+        ]),
+    );
+}
+
+#[test]
+fn test_array_parse_errors() {
+    let mut tokens = tokenize(String::from("[1, 2")).unwrap();
+    assert_eq!(
+        Err(CustomError::UnexpectedToken(
+            String::from("Expected a comma or a right bracket"),
+            0,
+            0
+        )),
+        TakePriorityLast::parse(&mut tokens, &mut ParseContext::new())
+    );
+
+    let mut tokens = tokenize(String::from("[1, ]")).unwrap();
+    assert_eq!(
+        Err(CustomError::UnexpectedToken(
+            String::from("Expected an expression after the comma"),
+            1,
+            5
+        )),
+        TakePriorityLast::parse(&mut tokens, &mut ParseContext::new())
+    );
+}
+
+#[test]
+fn test_array_index() {
+    let array = VariableType::Array(vec![VariableType::Integer(1), VariableType::Integer(2)]);
+    assert_eq!(Ok(VariableType::Integer(2)), array.index(1));
+    assert_eq!(Err(CustomError::IndexOutOfBounds(2, 2)), array.index(2));
+    assert_eq!(Err(CustomError::IndexOutOfBounds(-1, 2)), array.index(-1));
+    assert_eq!(
+        Err(CustomError::InvalidOperation(String::from(
+            "Cannot index a int"
+        ))),
+        VariableType::Integer(1).index(0)
+    );
+    assert_eq!("[1, 2]", array.to_string());
+}
+
+fn evaluate_file(file: &str) -> Result<VariableType, CustomError> {
+    evaluate_file_with(file, &OperationContext::default())
+}
+
+fn evaluate_file_with(
+    file: &str,
+    operation_context: &OperationContext,
+) -> Result<VariableType, CustomError> {
+    let tokens = tokenize(String::from(file)).unwrap();
+    let file_node = parse(tokens).unwrap().unwrap();
+    file_node.evaluate(operation_context)
+}
+
+#[test]
+fn test_variables() {
+    assert_eq!(
+        Ok(VariableType::Integer(2)),
+        evaluate_file("fu int x 1\nx 1 + x\nx")
+    );
+    assert_eq!(
+        Ok(VariableType::Integer(3)),
+        evaluate_file(&read("resources/test_programs/counting_loop.skrb").unwrap())
+    );
+    assert_eq!(
+        Err(CustomError::UnknownVariable(String::from("x"))),
+        evaluate_file("x")
+    );
+    assert_eq!(
+        Err(CustomError::InvalidOperation(String::from(
+            "The variable x is already declared in this scope"
+        ))),
+        evaluate_file("fu int x 1\nfu int x 2")
+    );
+}
+
+#[test]
+fn test_variable_scopes() {
+    assert_eq!(
+        Err(CustomError::UnknownVariable(String::from("y"))),
+        evaluate_file("kodi { fu int y 2 }\ny")
+    );
+    // the inner declaration shadows the outer one until the end of the scope
+    assert_eq!(
+        Ok(VariableType::Integer(1)),
+        evaluate_file("fu int x 1\nkodi { fu int x 2 }\nx")
+    );
+    assert_eq!(
+        Ok(VariableType::Integer(2)),
+        evaluate_file("biuli { fu int y 2 y }")
+    );
+    // a variable of an outer scope can be modified
+    assert_eq!(
+        Ok(VariableType::Integer(5)),
+        evaluate_file("fu int x 1\nkodi { x 5 }\nx")
+    );
+}
+
+#[test]
+fn test_bubble_scope_value() {
+    assert_eq!(
+        Ok(VariableType::Integer(3)),
+        evaluate_file("biuli {\n    fu int x 1\n    1 + 2\n}")
+    );
+    assert_eq!(
+        Ok(VariableType::Null),
+        evaluate_file("kodi {\n    fu int x 1\n    1 + 2\n}")
+    );
+    // a declaration is not a trailing expression
+    assert_eq!(
+        Ok(VariableType::Null),
+        evaluate_file("biuli { fu int x 1 }")
+    );
+    assert_eq!(Ok(VariableType::Null), evaluate_file("biuli {}"));
+    // an ei inside the scope still returns from the function
+    assert_eq!(
+        Ok(VariableType::Integer(4)),
+        evaluate_file("ums f () { biuli { ei 4 } }\nf()")
+    );
+}
+
+#[test]
+fn test_function_calls() {
+    assert_eq!(
+        Ok(VariableType::Integer(610)),
+        evaluate_file(&read("resources/test_programs/fibo.skrb").unwrap())
+    );
+    assert_eq!(
+        Ok(VariableType::Integer(7)),
+        evaluate_file("ums add (int a, int b) { ei a + b }\nadd(3, 4)")
+    );
+    assert_eq!(
+        Ok(VariableType::Integer(14)),
+        evaluate_file(&read("resources/test_programs/clamp.skrb").unwrap())
+    );
+    // the statements after ei are not executed
+    assert_eq!(
+        Ok(VariableType::Integer(1)),
+        evaluate_file("ums f () { ei 1\n1 / 0 }\nf()")
+    );
+    assert_eq!(Ok(VariableType::Null), evaluate_file("ums f () { 1 }\nf()"));
+    // the variables of the caller are not visible inside the function
+    assert_eq!(
+        Err(CustomError::UnknownVariable(String::from("y"))),
+        evaluate_file("ums f () { ei y }\nkodi { fu int y 1\nf() }")
+    );
+}
+
+#[test]
+fn test_function_call_errors() {
+    assert_eq!(
+        Err(CustomError::InvalidCall(
+            String::from("The function f expects 1 arguments, got 2"),
+            2
+        )),
+        evaluate_file("ums f (int a) { ei a }\nf(1, 2)")
+    );
+    assert_eq!(
+        Err(CustomError::InvalidCall(
+            String::from("Unknown function g"),
+            1
+        )),
+        evaluate_file("g()")
+    );
+    assert_eq!(
+        Err(CustomError::InvalidCall(
+            String::from("x is a int, not a function"),
+            2
+        )),
+        evaluate_file("fu int x 1\nx()")
+    );
+    assert_eq!(
+        Err(CustomError::TypeMismatch(String::from(
+            "Cannot assign a string to a variable of type int"
+        ))),
+        evaluate_file("ums f (int a) { ei a }\nf(\"a\")")
+    );
+}
+
+#[test]
+fn test_native_calls() {
+    assert_eq!(
+        Ok(VariableType::Null),
+        evaluate_file("skr_app println(\"Hello\", 1 + 1)")
+    );
+    assert_eq!(
+        Err(CustomError::InvalidCall(
+            String::from("Unknown native function double"),
+            2
+        )),
+        evaluate_file("\nskr_app double(2)")
+    );
+
+    let mut operation_context = OperationContext::default();
+    operation_context.register_native(
+        "double",
+        Box::new(|args| match args {
+            [VariableType::Integer(value)] => Ok(VariableType::Integer(value * 2)),
+            _ => Err(CustomError::InvalidOperation(String::from(
+                "Expected an int",
+            ))),
+        }),
+    );
+    assert_eq!(
+        Ok(VariableType::Integer(6)),
+        evaluate_file_with("skr_app(\"double\", 1 + 2)", &operation_context)
+    );
+    assert_eq!(
+        Err(CustomError::InvalidOperation(String::from(
+            "Expected an int"
+        ))),
+        evaluate_file_with("skr_app double()", &operation_context)
+    );
+}
+
+#[test]
+fn test_typeof_native() {
+    assert_eq!(
+        Ok(VariableType::String(String::from("int"))),
+        evaluate_file("fu int x 1\nskr_app typeof(x)")
+    );
+    for (value, type_name) in [
+        ("1.5", "float"),
+        ("\"a\"", "string"),
+        ("io", "bool"),
+        ("skr_app print()", "null"),
+    ] {
+        assert_eq!(
+            Ok(VariableType::String(String::from(type_name))),
+            evaluate_file(&format!("skr_app typeof({})", value))
+        );
+    }
+    assert_eq!(
+        Err(CustomError::InvalidOperation(String::from(
+            "The native function typeof expects 1 arguments, got 2"
+        ))),
+        evaluate_file("skr_app typeof(1, 2)")
+    );
+}
+
+#[test]
+fn test_len_native() {
+    assert_eq!(
+        Ok(VariableType::Integer(5)),
+        evaluate_file("fu skr s \"hello\"\nskr_app len(s)")
+    );
+    assert_eq!(
+        Ok(VariableType::Integer(4)),
+        evaluate_file("skr_app len(\"café\")")
+    );
+    assert_eq!(
+        Err(CustomError::TypeMismatch(String::from(
+            "Cannot get the length of a int"
+        ))),
+        evaluate_file("skr_app len(3)")
+    );
+    assert_eq!(
+        Err(CustomError::TypeMismatch(String::from(
+            "Cannot get the length of a bool"
+        ))),
+        evaluate_file("skr_app len(io)")
+    );
+}
+
+#[test]
+fn test_str_native() {
+    for (value, expected) in [
+        ("42", "42"),
+        ("-7", "-7"),
+        ("2.0", "2.0"),
+        ("0.1", "0.1"),
+        ("io", "io"),
+        ("no", "no"),
+        ("nul", "null"),
+        ("\"text\"", "text"),
+        ("'c'", "c"),
+        ("[1, 2]", "[1, 2]"),
+    ] {
+        assert_eq!(
+            Ok(VariableType::String(String::from(expected))),
+            evaluate_file(&format!("skr_app str({})", value)),
+            "{}",
+            value
+        );
+    }
+    assert_eq!(
+        Ok(VariableType::String(String::from("n = 3"))),
+        evaluate_file("fu int n 3\n\"n = \" + skr_app str(n)")
+    );
+    assert_eq!(
+        Err(CustomError::InvalidOperation(String::from(
+            "The native function str expects 1 arguments, got 0"
+        ))),
+        evaluate_file("skr_app str()")
+    );
+}
+
+#[test]
+fn test_read_line_native() {
+    let mut operation_context = OperationContext::default();
+    operation_context.set_input(Box::new("Ada\r\n42\nlast".as_bytes()));
+    let read = "skr_app read_line()";
+    for expected in ["Ada", "42", "last"] {
+        assert_eq!(
+            Ok(VariableType::String(String::from(expected))),
+            evaluate_file_with(read, &operation_context)
+        );
+    }
+    // at the end of the input
+    assert_eq!(
+        Ok(VariableType::Null),
+        evaluate_file_with(read, &operation_context)
+    );
+    assert_eq!(
+        Ok(VariableType::Boolean(true)),
+        evaluate_file_with("(skr_app read_line()) == nul", &operation_context)
+    );
+    assert_eq!(
+        Err(CustomError::InvalidOperation(String::from(
+            "The native function read_line expects 0 arguments, got 1"
+        ))),
+        evaluate_file_with("skr_app read_line(1)", &operation_context)
+    );
+}
+
+#[test]
+fn test_abs_native() {
+    assert_eq!(
+        Ok(VariableType::Integer(3)),
+        evaluate_file("skr_app abs(-3)")
+    );
+    assert_eq!(
+        Ok(VariableType::Integer(3)),
+        evaluate_file("skr_app abs(3)")
+    );
+    assert_eq!(
+        Ok(VariableType::Float(2.5)),
+        evaluate_file("skr_app abs(-2.5)")
+    );
+    assert_eq!(
+        Err(CustomError::IntegerOverflow(String::from(
+            "abs(-9223372036854775808)"
+        ))),
+        evaluate_file("skr_app abs(-9223372036854775807 - 1)")
+    );
+    assert_eq!(
+        Err(CustomError::TypeMismatch(String::from(
+            "Cannot get the absolute value of a string"
+        ))),
+        evaluate_file("skr_app abs(\"a\")")
+    );
+}
+
+#[test]
+fn test_min_max_natives() {
+    assert_eq!(
+        Ok(VariableType::Integer(2)),
+        evaluate_file("skr_app min(2, 5)")
+    );
+    assert_eq!(
+        Ok(VariableType::Integer(5)),
+        evaluate_file("skr_app max(2, 5)")
+    );
+    assert_eq!(
+        Ok(VariableType::Float(-1.5)),
+        evaluate_file("skr_app min(-1.5, 0.5)")
+    );
+    // an int with a float gives a float
+    assert_eq!(
+        Ok(VariableType::Float(2.0)),
+        evaluate_file("skr_app min(2, 2.5)")
+    );
+    assert_eq!(
+        Ok(VariableType::Float(2.5)),
+        evaluate_file("skr_app max(2, 2.5)")
+    );
+    assert_eq!(
+        Ok(VariableType::Float(3.0)),
+        evaluate_file("skr_app max(1.5, 3)")
+    );
+    assert_eq!(
+        Err(CustomError::InvalidOperation(String::from(
+            "The native function max expects 2 arguments, got 3"
+        ))),
+        evaluate_file("skr_app max(1, 2, 3)")
+    );
+    assert_eq!(
+        Err(CustomError::TypeMismatch(String::from(
+            "Cannot get the min of a int and a bool"
+        ))),
+        evaluate_file("skr_app min(1, io)")
+    );
+}
+
+#[test]
+fn test_scope_depth() {
+    // more than 255 nested scopes are accepted
+    let countdown = "ums f (int n) { ij n > 0 { ei f(n - 1) }\nei n }\nf(150)";
+    assert_eq!(Ok(VariableType::Integer(0)), evaluate_file(countdown));
+
+    let too_deep = Err(CustomError::InvalidOperation(String::from(
+        "The maximum nesting depth of 10 scopes is exceeded",
+    )));
+    let context = || OperationContext {
+        variables: RefCell::new(VariableStack::with_max_depth(10)),
+        ..OperationContext::default()
+    };
+    let nested = format!("{}1{}", "{ ".repeat(11), " }".repeat(11));
+    assert_eq!(too_deep, evaluate_file_with(&nested, &context()));
+    let nested = format!("{}1{}", "{ ".repeat(10), " }".repeat(10));
+    assert_eq!(
+        Ok(VariableType::Integer(1)),
+        evaluate_file_with(&nested, &context())
+    );
+
+    // the scopes hidden by a function call are not counted
+    let nested_call = format!(
+        "ums f () {{ {}ei 1{} }}\n{}f(){}",
+        "{ ".repeat(8),
+        " }".repeat(8),
+        "{ ".repeat(8),
+        " }".repeat(8)
+    );
+    assert_eq!(
+        Ok(VariableType::Integer(1)),
+        evaluate_file_with(&nested_call, &context())
+    );
+}
+
+#[test]
+fn test_recursion_limit() {
+    // the recursion is stopped before it overflows the stack, the binary runs the default limit
+    assert_eq!(10_000, OperationContext::default().max_call_depth);
+    let context = || OperationContext {
+        max_call_depth: 10,
+        ..OperationContext::default()
+    };
+    let infinite = context();
+    assert_eq!(
+        Err(CustomError::RecursionLimit(10)),
+        evaluate_file_with("ums f () { ei f() }\nf()", &infinite)
+    );
+    assert_eq!(0, infinite.variables.borrow().call_depth());
+    assert_eq!(0, infinite.variables.borrow().depth());
+
+    let countdown = "ums f (int n) { ij n > 0 { ei f(n - 1) }\nei n }\n";
+    assert_eq!(
+        Ok(VariableType::Integer(0)),
+        evaluate_file_with(&format!("{}f(9)", countdown), &context())
+    );
+    assert_eq!(
+        Err(CustomError::RecursionLimit(10)),
+        evaluate_file_with(&format!("{}f(10)", countdown), &context())
+    );
+    // a constructor call counts as a call
+    assert_eq!(
+        Err(CustomError::RecursionLimit(10)),
+        evaluate_file_with(
+            &format!("{}kat Point {{ int x f(9) }}\nPoint()", countdown),
+            &context()
+        )
+    );
+}
+
+#[test]
+fn test_constant_reassignment() {
+    assert_eq!(
+        Err(CustomError::ConstantReassignment(String::from("x"), 3)),
+        evaluate_file("ju int x 1\n\nx 2")
+    );
+    assert_eq!(
+        Err(CustomError::ConstantReassignment(String::from("y"), 2)),
+        evaluate_file("ju fu int y 1\ny 2")
+    );
+    assert_eq!(Ok(VariableType::Integer(1)), evaluate_file("ju int x 1\nx"));
+    assert_eq!(
+        Ok(VariableType::Integer(2)),
+        evaluate_file("fu int x 1\nx 2\nx")
+    );
+}
+
+#[test]
+fn test_constant_shadowing() {
+    // a constant of an inner scope cannot be changed
+    assert_eq!(
+        Err(CustomError::ConstantReassignment(String::from("x"), 4)),
+        evaluate_file("fu int x 1\nkodi {\n    ju int x 2\n    x 3\n}")
+    );
+    // the mutable outer variable is visible again, and still mutable, after the scope
+    assert_eq!(
+        Ok(VariableType::Integer(5)),
+        evaluate_file("fu int x 1\nkodi { ju int x 2 }\nx 5\nx")
+    );
+    assert_eq!(
+        Ok(VariableType::Integer(2)),
+        evaluate_file("fu int x 1\nbiuli { ju int x 2 x }")
+    );
+}
+
+#[test]
+fn test_increment() {
+    assert_eq!(
+        Ok(VariableType::Integer(3)),
+        evaluate_file("fu int i 1\ni++\ni++\n(i)")
+    );
+    assert_eq!(
+        Ok(VariableType::Float(0.5)),
+        evaluate_file("fu dar x 1.5\nx--")
+    );
+    // a counting loop
+    assert_eq!(
+        Ok(VariableType::Integer(5)),
+        evaluate_file("fu int i 0\nluba i < 5 { i++ }\ni")
+    );
+    assert_eq!(
+        Err(CustomError::InvalidOperation(String::from(
+            "Cannot apply ++ to a string"
+        ))),
+        evaluate_file("fu skr s \"a\"\ns++")
+    );
+    assert_eq!(
+        Err(CustomError::ConstantReassignment(String::from("i"), 2)),
+        evaluate_file("ju int i 1\ni--")
+    );
+    assert_eq!(
+        Err(CustomError::IntegerOverflow(String::from(
+            "9223372036854775807++"
+        ))),
+        evaluate_file("fu int i 9223372036854775807\ni++")
+    );
+}
+
+#[test]
+fn test_variable_modification() {
+    assert_eq!(
+        Ok(VariableType::Integer(7)),
+        evaluate_file("fu int x 5\nx 7\nx")
+    );
+    // the value stored is converted to the declared type
+    assert_eq!(
+        Ok(VariableType::Float(2.0)),
+        evaluate_file("fu dar f 1.5\nf 2")
+    );
+    assert_eq!(
+        Err(CustomError::TypeMismatch(String::from(
+            "Cannot assign a string to a variable of type int"
+        ))),
+        evaluate_file("fu int x 5\nx \"seven\"")
+    );
+    assert_eq!(
+        Err(CustomError::UnknownVariable(String::from("y"))),
+        evaluate_file("fu int x 5\ny 7")
+    );
+    // the parameters keep the type of their declaration
+    assert_eq!(
+        Err(CustomError::TypeMismatch(String::from(
+            "Cannot assign a bool to a variable of type int"
+        ))),
+        evaluate_file("ums f (int n) { n io }\nf(1)")
+    );
+}
+
+#[test]
+fn test_nested_fields() {
+    let program = read("resources/test_programs/nested_fields.skrb").unwrap();
+    assert_eq!(Ok(VariableType::Integer(3)), evaluate_file(&program));
+    assert_eq!(
+        Ok(VariableType::Integer(5)),
+        evaluate_file(&program.replace("value:inner:o", "3 + count:o"))
+    );
+    for (chain, message) in [
+        ("missing:inner:o", "The class Inner has no field missing"),
+        ("value:missing:o", "The class Outer has no field missing"),
+        ("value:count:o", "Cannot get the field value of a int"),
+    ] {
+        assert_eq!(
+            Err(CustomError::InvalidOperation(String::from(message))),
+            evaluate_file(&program.replace("value:inner:o", chain))
+        );
+    }
+    assert_eq!(
+        Err(CustomError::UnknownVariable(String::from("p"))),
+        evaluate_file(&program.replace("value:inner:o", "value:inner:p"))
+    );
+    // the same chains read from the left with .
+    for chain in ["o.inner.value", "value:o.inner", "(o.count) + 1"] {
+        assert_eq!(
+            Ok(VariableType::Integer(3)),
+            evaluate_file(&program.replace("value:inner:o", chain))
+        );
+    }
+    assert_eq!(
+        Err(CustomError::InvalidOperation(String::from(
+            "The class Inner has no field missing"
+        ))),
+        evaluate_file(&program.replace("value:inner:o", "o.inner.missing"))
+    );
+}
+
+#[test]
+fn test_null_literal() {
+    assert_eq!(Ok(VariableType::Null), evaluate_file("fu null x nul\nx"));
+    assert_eq!(Ok(VariableType::Null), evaluate_file("nul"));
+    assert_eq!(
+        Err(CustomError::InvalidOperation(String::from(
+            "Cannot apply + to a null and a int"
+        ))),
+        evaluate_file("nul + 1")
+    );
+    assert_eq!(Ok(VariableType::Boolean(true)), evaluate_file("nul == nul"));
+    assert_eq!(
+        Ok(VariableType::Boolean(false)),
+        evaluate_file("nul != nul")
+    );
+    assert_eq!(Ok(VariableType::Boolean(false)), evaluate_file("1 == nul"));
+    assert_eq!(
+        Ok(VariableType::Boolean(true)),
+        evaluate_file("nul != \"a\"")
+    );
+    assert_eq!(
+        Err(CustomError::InvalidOperation(String::from(
+            "Cannot apply < to a null and a int"
+        ))),
+        evaluate_file("nul < 1")
+    );
+    assert_eq!(
+        Err(CustomError::InvalidOperation(String::from(
+            "Cannot apply - to a null"
+        ))),
+        evaluate_file("-nul")
+    );
+}
+
+#[test]
+fn test_strict_mode() {
+    let strict = || OperationContext {
+        strict: true,
+        ..OperationContext::default()
+    };
+    let ignored_bubble = "biuli { 1 }\n2";
+    assert_eq!(Ok(VariableType::Integer(2)), evaluate_file(ignored_bubble));
+    assert_eq!(
+        Err(CustomError::StrictMode(String::from(
+            "The value of a biuli scope is never used, use kodi instead"
+        ))),
+        evaluate_file_with(ignored_bubble, &strict())
+    );
+    // the last statement bubbles up to the block around it
+    assert_eq!(
+        Ok(VariableType::Integer(1)),
+        evaluate_file_with("2\nbiuli { kodi { ij no { 3 } }\nbiuli { 1 } }", &strict())
+    );
+    assert_eq!(
+        Err(CustomError::StrictMode(String::from(
+            "The kodi scope produces a int that is lost, use biuli to return it"
+        ))),
+        evaluate_file_with("kodi { 3 }\n2", &strict())
+    );
+}
+
+#[test]
+fn test_ordered_comparison() {
+    for (code, expected) in [
+        ("1 < 2", true),
+        ("2 < 1", false),
+        ("2 > 1", true),
+        ("1 <= 1", true),
+        ("2 <= 1", false),
+        ("1 >= 2", false),
+        ("1.5 > 1", true),
+        ("1 < 0.5", false),
+        ("2 == 2.0", true),
+        ("\"abc\" < \"abd\"", true),
+        ("\"abc\" == \"abc\"", true),
+        ("'b' >= 'a'", true),
+        ("io != no", true),
+    ] {
+        assert_evaluation_value(String::from(code), VariableType::Boolean(expected));
+    }
+    assert_eq!(
+        Ok(VariableType::Boolean(true)),
+        evaluate_file(&read("resources/test_programs/cmp/or_eq.skrb").unwrap())
+    );
+    assert_eq!(
+        Err(CustomError::InvalidOperation(String::from(
+            "Cannot apply < to a string and a int"
+        ))),
+        evaluate_file("\"a\" < 1")
+    );
+    assert_eq!(
+        Err(CustomError::InvalidOperation(String::from(
+            "Cannot apply > to a bool and a bool"
+        ))),
+        evaluate_file("io > no")
+    );
+}
+
+#[test]
+fn test_integer_overflow() {
+    for (file, operation) in [
+        ("9223372036854775807 + 1", "9223372036854775807 + 1"),
+        ("4000000000 * 4000000000", "4000000000 * 4000000000"),
+        ("-9223372036854775807 - 2", "-9223372036854775807 - 2"),
+        ("-(-9223372036854775807 - 1)", "-(-9223372036854775808)"),
+    ] {
+        assert_eq!(
+            Err(CustomError::IntegerOverflow(String::from(operation))),
+            evaluate_file(file)
+        );
+    }
+    assert_eq!(
+        "Integer overflow: the result of 4000000000 * 4000000000 is too big",
+        CustomError::IntegerOverflow(String::from("4000000000 * 4000000000")).to_string()
+    );
+}
+
+#[test]
+fn test_bool_literal() {
+    assert_evaluation_value(String::from("io"), VariableType::Boolean(true));
+    assert_evaluation_value(String::from("no"), VariableType::Boolean(false));
+    assert_eq!(
+        Ok(VariableType::Boolean(false)),
+        evaluate_file("fu ioi a io\nfu ioi b no\na && b")
+    );
+}
+
+#[test]
+fn test_null_type_inference() {
+    // the first value that is not null fixes the type
+    assert_eq!(
+        Ok(VariableType::Integer(3)),
+        evaluate_file("fu null x nul\nx 2\nx 3\n(x)")
+    );
+    assert_eq!(
+        Ok(VariableType::Null),
+        evaluate_file("fu null x nul\nx nul\nx")
+    );
+    for (code, type_name) in [
+        ("fu null x nul\nx 2\nx \"a\"", "string"),
+        ("fu null x nul\nx 2\nx nul", "null"),
+        ("fu null x nul\nx 2\nx 1.5", "float"),
+    ] {
+        assert_eq!(
+            Err(CustomError::TypeMismatch(format!(
+                "Cannot assign a {} to a variable of type int inferred from its first value",
+                type_name
+            ))),
+            evaluate_file(code)
+        );
+    }
+}
+
+#[test]
+fn test_expression_statements() {
+    // every top-level expression is evaluated, the file keeps the value of the last one
+    assert_eq!(
+        Ok(VariableType::Integer(5)),
+        evaluate_file("fu int x 1\nums add (int n) {\n    x n + x\n}\nadd(2)\n1 + 2\nadd(2)\nx")
+    );
+    assert!(parse(tokenize(String::from("skr_app print(1 + 2)\nf(x)")).unwrap()).is_ok());
+}
+
+#[test]
+fn test_float_precision() {
+    assert_evaluation_value(
+        String::from("3.141592653589793"),
+        VariableType::Float(std::f64::consts::PI),
+    );
+    assert_eq!(
+        Ok(String::from("3.141592653589793")),
+        evaluate_file("3.141592653589793").map(|value| value.to_string())
+    );
+    assert_evaluation_value(String::from("0.1 + 0.2"), VariableType::Float(0.1 + 0.2));
+}
+
+#[test]
+fn test_function_return_type() {
+    assert_eq!(
+        Ok(VariableType::Float(2.0)),
+        evaluate_file("ums two () -> dar { ei 2 }\ntwo()")
+    );
+    assert_eq!(
+        Err(CustomError::TypeMismatch(String::from(
+            "Cannot assign a string to a variable of type int"
+        ))),
+        evaluate_file("ums f () -> int { ei \"a\" }\nf()")
+    );
+}
+
+#[test]
+fn test_kasu() {
+    let program = "kasu (n) {\n    1 { \"one\" }\n    2 { \"two\" }\n    3 { \"three\" }\n    sula { \"many\" }\n}";
+    for (n, expected) in [(1, "one"), (2, "two"), (3, "three"), (7, "many")] {
+        assert_eq!(
+            Ok(VariableType::String(String::from(expected))),
+            evaluate_file(&format!("fu int n {}\n{}", n, program))
+        );
+    }
+}
+
+#[test]
+fn test_fold_constants_evaluation() {
+    let programs = [
+        String::from("2 + 3 * 4"),
+        String::from("(1 + 2) * 3 - 10 / 4"),
+        String::from("2 ** 3 ** 2 % 7 + 1.5"),
+        String::from("1 < 2 && 3 >= 4 || -(2 - 5) == 3"),
+        String::from("fu int x 2\nx 3 * 4 + x\nx * (1 + 1)"),
+        String::from("9223372036854775807 + 1"),
+        String::from("1 / 0"),
+        read("resources/test_programs/fibo.skrb").unwrap(),
+        read("resources/test_programs/counting_loop.skrb").unwrap(),
+        read("resources/test_programs/clamp.skrb").unwrap(),
+    ];
+    for program in programs {
+        let mut file_node = parse(tokenize(program.clone()).unwrap()).unwrap().unwrap();
+        let unfolded = file_node.evaluate(&OperationContext::default());
+        file_node.fold_constants();
+        assert_eq!(
+            unfolded,
+            file_node.evaluate(&OperationContext::default()),
+            "{}",
+            program
+        );
+    }
+}
@@ -0,0 +1,44 @@
+use crate::get_file_content::{get_content, DEFAULT_EXTENSIONS};
+use crate::skr_errors::CustomError;
+use crate::utils::read;
+
+fn args(path: &str) -> Vec<String> {
+    vec![String::from("skribi"), String::from(path)]
+}
+
+#[test]
+fn test_extensions() {
+    assert_eq!(
+        Err(CustomError::WrongExtension(String::from("notes.txt"))),
+        get_content(args("notes.txt"), &DEFAULT_EXTENSIONS)
+    );
+    assert_eq!(
+        Err(CustomError::WrongExtension(String::from("skrb"))),
+        get_content(args("skrb"), &DEFAULT_EXTENSIONS)
+    );
+
+    let path = "resources/test_programs/pow.skrb";
+    assert_eq!(
+        read(path).ok(),
+        get_content(args(path), &DEFAULT_EXTENSIONS).ok()
+    );
+    // a tool can use its own extensions
+    assert_eq!(
+        Err(CustomError::WrongExtension(String::from(path))),
+        get_content(args(path), &["txt"])
+    );
+}
+
+#[test]
+fn test_missing_file() {
+    let path = "resources/test_programs/missing.skrb";
+    assert_eq!(
+        Err(CustomError::FileNotFound(String::from(path))),
+        get_content(args(path), &DEFAULT_EXTENSIONS)
+    );
+    // the extension is checked first
+    assert_eq!(
+        Err(CustomError::WrongExtension(String::from("missing.txt"))),
+        get_content(args("missing.txt"), &DEFAULT_EXTENSIONS)
+    );
+}
@@ -1,2 +1,14 @@
+mod blocs_tests;
+mod classes_tests;
 mod expressions_tests;
+mod files_node_tests;
+mod functions_tests;
 mod id_nodes_tests;
+mod if_else_tests;
+mod loops_tests;
+mod matches_tests;
+mod operations_tests;
+mod statements_tests;
+mod token_stream_tests;
+mod vars_tests;
+mod visitor_tests;
@@ -0,0 +1,87 @@
+use crate::parse::context::ParseContext;
+use crate::parse::nodes::blocs::{Biuli, KStart, Kodi, ScopeBase, Spoki};
+use crate::parse::nodes::expressions::StaL;
+use crate::tokens::tokenize;
+
+fn parse_sta_l(code: &str) -> StaL {
+    StaL::parse(
+        &mut tokenize(String::from(code)).unwrap(),
+        &mut ParseContext::new(),
+    )
+    .unwrap()
+    .unwrap()
+}
+
+fn parse_scope_base(code: &str) -> Option<ScopeBase> {
+    let mut tokens = tokenize(String::from(code)).unwrap();
+    let scope_base = ScopeBase::parse(&mut tokens, &mut ParseContext::new()).unwrap();
+    assert!(tokens.is_empty());
+    scope_base
+}
+
+#[test]
+fn test_kodi() {
+    assert_eq!(
+        Some(ScopeBase::Kodi(Kodi::new(KStart::new(
+            None,
+            parse_sta_l("{}")
+        )))),
+        parse_scope_base("kodi {}")
+    );
+    assert_eq!(
+        Some(ScopeBase::Kodi(Kodi::new(KStart::new(
+            None,
+            parse_sta_l("{ fu int a 1 }")
+        )))),
+        parse_scope_base("kodi { fu int a 1 }")
+    );
+    assert!(format!("{:?}", parse_sta_l("{ fu int a 1 }")).contains("[VarDec]"));
+}
+
+#[test]
+fn test_biuli() {
+    assert_eq!(
+        Some(ScopeBase::Biuli(Biuli::new(KStart::new(
+            None,
+            parse_sta_l("{}")
+        )))),
+        parse_scope_base("biuli {}")
+    );
+    assert_eq!(
+        Some(ScopeBase::Biuli(Biuli::new(KStart::new(
+            None,
+            parse_sta_l("{ fu int a 1 }")
+        )))),
+        parse_scope_base("biuli { fu int a 1 }")
+    );
+}
+
+#[test]
+fn test_spoki() {
+    assert_eq!(
+        Some(ScopeBase::Spoki(Spoki::new(KStart::new(
+            None,
+            parse_sta_l("{}")
+        )))),
+        parse_scope_base("spoki {}")
+    );
+    assert_eq!(
+        Some(ScopeBase::Spoki(Spoki::new(KStart::new(
+            None,
+            parse_sta_l("{ fu int a 1 }")
+        )))),
+        parse_scope_base("spoki { fu int a 1 }")
+    );
+}
+
+#[test]
+fn test_scope_graph_label() {
+    for (code, label) in [
+        ("kodi {}", "[Kodi]"),
+        ("biuli {}", "[Biuli]"),
+        ("spoki {}", "[Spoki]"),
+    ] {
+        let graph = format!("{:?}", parse_scope_base(code).unwrap());
+        assert!(graph.contains(label), "{}", graph);
+    }
+}
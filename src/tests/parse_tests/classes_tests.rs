@@ -0,0 +1,157 @@
+use crate::parse::context::ParseContext;
+use crate::parse::nodes::classes::ClassDec;
+use crate::parse::nodes::vars::{parse_type, Type};
+use crate::parse::parse;
+use crate::skr_errors::CustomError;
+use crate::tokens::tokenize;
+
+#[test]
+fn test_class_registers_type() {
+    let mut context = ParseContext::new();
+
+    let mut tokens = tokenize(String::from("Foo")).unwrap();
+    assert_eq!(None, parse_type(&mut tokens, &mut context).unwrap());
+
+    let mut tokens = tokenize(String::from("kat Foo")).unwrap();
+    assert!(ClassDec::parse(&mut tokens, &mut context)
+        .unwrap()
+        .is_some());
+
+    let mut tokens = tokenize(String::from("Foo")).unwrap();
+    assert_eq!(
+        Some(Type {
+            name: String::from("Foo")
+        }),
+        parse_type(&mut tokens, &mut context).unwrap()
+    );
+}
+
+#[test]
+fn test_class_manager() {
+    let mut context = ParseContext::new();
+
+    for built_in in ["int", "dar", "ioi", "skr", "null"] {
+        assert!(context.classes.is_type_def(built_in), "{}", built_in);
+    }
+    assert!(!context.classes.is_type_def("Foo"));
+    assert!(!context.classes.is_type_def("hello"));
+
+    context.classes.register("Foo");
+    assert!(context.classes.is_type_def("Foo"));
+    assert!(!context.classes.is_type_def("hello"));
+
+    // each manager has its own classes
+    assert!(!ParseContext::new().classes.is_type_def("Foo"));
+}
+
+#[test]
+fn test_many_type_references() {
+    let mut code = String::new();
+    for i in 0..100 {
+        code.push_str(&format!("kat C{}\n", i));
+    }
+    for i in 0..1000 {
+        code.push_str(&format!("fu C{} v{} 0\n", i % 100, i));
+    }
+    let file_node = parse(tokenize(code).unwrap()).unwrap().unwrap();
+
+    let graph = format!("{:?}", file_node);
+    assert_eq!(1000, graph.matches("[Vd v").count());
+    assert!(graph.contains("[Vd v999]"), "{}", graph);
+}
+
+#[test]
+fn test_class_body() {
+    let mut tokens = tokenize(String::from(
+        "kat Point {\n  int x 0\n  int y 0\n  Point origin 0\n  ums norm() { x }\n}",
+    ))
+    .unwrap();
+    let class_dec = ClassDec::parse(&mut tokens, &mut ParseContext::new())
+        .unwrap()
+        .unwrap();
+    assert!(tokens.is_empty());
+
+    let graph = format!("{:?}", class_dec);
+    assert!(graph.contains("[ClassDec Point]"));
+    assert!(graph.contains("[Vd x]"));
+    assert!(graph.contains("[Vd y]"));
+    assert!(graph.contains("[Vd origin]"));
+    assert!(graph.contains("[FctDec norm]"));
+}
+
+#[test]
+fn test_nested_class_types() {
+    let mut context = ParseContext::new();
+    let mut tokens = tokenize(String::from(
+        "kat Outer {\n  kat Inner {\n    kat Deep\n  }\n  int v 0\n}",
+    ))
+    .unwrap();
+    assert!(ClassDec::parse(&mut tokens, &mut context)
+        .unwrap()
+        .is_some());
+
+    for name in ["Outer", "Inner:Outer", "Deep:Inner:Outer"] {
+        assert!(context.classes.is_type_def(name), "{}", name);
+    }
+    // the nested classes are only known with their qualified name
+    assert!(!context.classes.is_type_def("Inner"));
+    assert!(!context.classes.is_type_def("Deep:Inner"));
+
+    let mut tokens = tokenize(String::from("Inner:Outer x")).unwrap();
+    assert_eq!(
+        Some(Type {
+            name: String::from("Inner:Outer")
+        }),
+        parse_type(&mut tokens, &mut context).unwrap()
+    );
+    assert_eq!(1, tokens.len());
+
+    // a static field of a class is not a type
+    let mut tokens = tokenize(String::from("v:Outer")).unwrap();
+    assert_eq!(None, parse_type(&mut tokens, &mut context).unwrap());
+    assert_eq!(3, tokens.len());
+
+    let file_node = parse(
+        tokenize(String::from(
+            "kat Outer {\n  kat Inner\n}\nfu Inner:Outer x nul\nfu Outer y nul",
+        ))
+        .unwrap(),
+    )
+    .unwrap()
+    .unwrap();
+    let graph = format!("{:?}", file_node);
+    assert!(graph.contains("[CGet Inner:Outer]"), "{}", graph);
+    assert!(graph.contains("[CGet Outer]"), "{}", graph);
+}
+
+#[test]
+fn test_unknown_nested_class_type() {
+    let mut context = ParseContext::new();
+    let mut tokens = tokenize(String::from("kat Outer {\n  kat Inner\n}")).unwrap();
+    ClassDec::parse(&mut tokens, &mut context).unwrap();
+
+    let mut tokens = tokenize(String::from("Other:Outer x")).unwrap();
+    assert_eq!(
+        Err(CustomError::UnexpectedToken(
+            String::from("Unknown type Other:Outer, the class Outer has no class Other"),
+            1,
+            1
+        )),
+        parse_type(&mut tokens, &mut context)
+    );
+
+    let result = parse(
+        tokenize(String::from(
+            "kat Outer {\n  kat Inner\n}\nint a 0\nfu Other:Outer x nul",
+        ))
+        .unwrap(),
+    );
+    assert_eq!(
+        Err(CustomError::UnexpectedToken(
+            String::from("Unknown type Other:Outer, the class Outer has no class Other"),
+            5,
+            4
+        )),
+        result.map(|_| ())
+    );
+}
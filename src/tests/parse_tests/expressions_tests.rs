@@ -1,8 +1,13 @@
-use crate::parse::nodes::expressions::{IdUseV, InsideIdUseV};
+use crate::parse::context::ParseContext;
+use crate::parse::nodes::expressions::{
+    parse_exp_list, Exp, IdUseV, InsideIdUseV, NatCall, Return, Ternary,
+};
 use crate::parse::nodes::id_nodes::OpIn;
 use crate::parse::nodes::operations::NoValueN;
 use crate::parse::nodes::Parsable;
-use crate::tokens::Token;
+use crate::parse::{parse, Program};
+use crate::skr_errors::CustomError;
+use crate::tokens::{tokenize, Token};
 
 #[test]
 fn test_simple_exp_id_use_v() {
@@ -16,7 +21,7 @@ fn test_simple_exp_id_use_v() {
 
     let mut tokens = tokens.into_iter().map(|x| x.into()).collect();
     let mut tokens2 = tokens2.into_iter().map(|x| x.into()).collect();
-    let id_use_v = IdUseV::parse(&mut tokens);
+    let id_use_v = IdUseV::parse(&mut tokens, &mut ParseContext::new());
 
     match id_use_v {
         Ok(Some(id_use_v)) => {
@@ -24,7 +29,11 @@ fn test_simple_exp_id_use_v() {
                 IdUseV::new(
                     String::from("a"),
                     OpIn::Empty,
-                    InsideIdUseV::NoValue(NoValueN::parse(&mut tokens2).unwrap().unwrap())
+                    InsideIdUseV::NoValue(
+                        NoValueN::parse(&mut tokens2, &mut ParseContext::new())
+                            .unwrap()
+                            .unwrap()
+                    )
                 ),
                 id_use_v
             );
@@ -33,3 +42,255 @@ fn test_simple_exp_id_use_v() {
         Err(err) => panic!("Error parsing IdUseV: {:?}", err),
     }
 }
+
+#[test]
+fn test_return_with_exp() {
+    let mut tokens = tokenize(String::from("ei 5")).unwrap();
+    let return_node = Return::parse(&mut tokens, &mut ParseContext::new())
+        .unwrap()
+        .unwrap();
+
+    let mut exp_tokens = tokenize(String::from("5")).unwrap();
+    let exp = Exp::parse(&mut exp_tokens, &mut ParseContext::new()).unwrap();
+    assert!(exp.is_some());
+    assert_eq!(Return::new(exp), return_node);
+    assert!(tokens.is_empty());
+}
+
+#[test]
+fn test_bare_return() {
+    let mut tokens = tokenize(String::from("ei")).unwrap();
+    let return_node = Return::parse(&mut tokens, &mut ParseContext::new())
+        .unwrap()
+        .unwrap();
+    assert_eq!(Return::new(None), return_node);
+
+    let mut tokens = tokenize(String::from("ei\n")).unwrap();
+    let return_node = Return::parse(&mut tokens, &mut ParseContext::new())
+        .unwrap()
+        .unwrap();
+    assert_eq!(Return::new(None), return_node);
+}
+
+fn parse_exp(code: &str) -> Exp {
+    let mut tokens = tokenize(String::from(code)).unwrap();
+    let exp = Exp::parse(&mut tokens, &mut ParseContext::new())
+        .unwrap()
+        .unwrap();
+    assert!(tokens.is_empty());
+    exp
+}
+
+#[test]
+fn test_ternary() {
+    let expected = Exp::Ternary(Box::new(Ternary::new(
+        parse_exp("1 == 2"),
+        parse_exp("x"),
+        parse_exp("3 + 4"),
+    )));
+    assert_eq!(expected, parse_exp("1 == 2 ? x sula 3 + 4"));
+
+    // the ternary after sula is nested in the first one
+    let expected = Exp::Ternary(Box::new(Ternary::new(
+        parse_exp("a"),
+        parse_exp("1"),
+        parse_exp("b ? 2 sula 3"),
+    )));
+    assert_eq!(expected, parse_exp("a ? 1 sula b ? 2 sula 3"));
+
+    let graph = format!("{:?}", parse_exp("io ? 1 sula 2"));
+    assert!(graph.contains("[Ternary]"), "{}", graph);
+    assert_eq!(4, graph.matches("[Exp]").count(), "{}", graph);
+}
+
+#[test]
+fn test_ternary_errors() {
+    for (code, message, line, column) in [
+        ("io ?", "Expected an expression after ?", 0, 0),
+        (
+            "io ? 1 2",
+            "Expected sula after the first expression of the ternary",
+            1,
+            8,
+        ),
+        ("io ? 1 sula", "Expected an expression after sula", 0, 0),
+    ] {
+        let mut tokens = tokenize(String::from(code)).unwrap();
+        assert_eq!(
+            Err(CustomError::UnexpectedToken(
+                String::from(message),
+                line,
+                column
+            )),
+            Exp::parse(&mut tokens, &mut ParseContext::new())
+        );
+    }
+}
+
+fn parse_nat_call(code: &str) -> NatCall {
+    let mut tokens = tokenize(String::from(code)).unwrap();
+    let nat_call = NatCall::parse(&mut tokens, &mut ParseContext::new())
+        .unwrap()
+        .unwrap();
+    assert!(tokens.is_empty());
+    nat_call
+}
+
+#[test]
+fn test_nat_call() {
+    let nat_call = parse_nat_call("skr_app(\"print\", x)");
+    assert_eq!("print", nat_call.name());
+    // the name can also be written before the arguments
+    assert_eq!(parse_nat_call("skr_app print(x)"), nat_call);
+
+    let graph = format!("{:?}", nat_call);
+    assert!(graph.contains("[NatCall print]"), "{}", graph);
+    assert!(graph.contains("[IdUseV x]"), "{}", graph);
+
+    assert_eq!(
+        parse_nat_call("skr_app exit()"),
+        parse_nat_call("skr_app(\"exit\")")
+    );
+
+    let graph = format!("{:?}", parse_exp("skr_app(\"print\", 1 + 2)"));
+    assert!(graph.contains("[NatCall print]"), "{}", graph);
+}
+
+#[test]
+fn test_nat_call_errors() {
+    for (code, message, line, column) in [
+        ("skr_app", "Expected the name of the native function", 0, 0),
+        (
+            "skr_app(1)",
+            "Expected the name of the native function as a string",
+            1,
+            9,
+        ),
+        (
+            "skr_app print",
+            "Expected the arguments of the native function",
+            0,
+            0,
+        ),
+    ] {
+        let mut tokens = tokenize(String::from(code)).unwrap();
+        assert_eq!(
+            Err(CustomError::UnexpectedToken(
+                String::from(message),
+                line,
+                column
+            )),
+            NatCall::parse(&mut tokens, &mut ParseContext::new())
+        );
+    }
+}
+
+#[test]
+fn test_parse_exp_list() {
+    let mut tokens = tokenize(String::from("a, b, c)")).unwrap();
+    let exps = parse_exp_list(
+        &mut tokens,
+        &mut ParseContext::new(),
+        Token::RightParenthesis,
+        "right parenthesis",
+    )
+    .unwrap();
+    assert_eq!(3, exps.len());
+    assert!(tokens.is_empty());
+
+    let mut tokens = tokenize(String::from(")")).unwrap();
+    assert_eq!(
+        Ok(vec![]),
+        parse_exp_list(
+            &mut tokens,
+            &mut ParseContext::new(),
+            Token::RightParenthesis,
+            "right parenthesis",
+        )
+    );
+
+    for (file, message, column) in [
+        ("1, 2,]", "Expected an expression after the comma", 6),
+        ("1 2]", "Expected a comma or a right bracket", 3),
+        ("1, 2)", "Expected a comma or a right bracket", 5),
+    ] {
+        let mut tokens = tokenize(String::from(file)).unwrap();
+        assert_eq!(
+            Err(CustomError::UnexpectedToken(
+                String::from(message),
+                1,
+                column
+            )),
+            parse_exp_list(
+                &mut tokens,
+                &mut ParseContext::new(),
+                Token::RightBracket,
+                "right bracket",
+            )
+        );
+    }
+}
+
+#[test]
+fn test_unclosed_parenthesis() {
+    for (code, line, column) in [
+        ("(1 + 2", 1, 1),
+        ("1 + (2 * 3\n4", 1, 5),
+        ("\n(x", 2, 1),
+        ("2 * ((1 + 2)", 1, 5),
+    ] {
+        assert_eq!(
+            Some(CustomError::UnclosedParenthesis(line, column)),
+            parse(tokenize(String::from(code)).unwrap()).err(),
+            "{}",
+            code
+        );
+    }
+    // another token than the end of the line is unexpected
+    assert_eq!(
+        Some(CustomError::UnexpectedToken(
+            String::from("Expected a right parenthesis"),
+            1,
+            4
+        )),
+        parse(tokenize(String::from("(1 2)")).unwrap()).err()
+    );
+}
+
+#[test]
+fn test_nesting_limit() {
+    let parse_with = |code: String, context: &mut ParseContext| {
+        Program::parse(&mut tokenize(code).unwrap(), context).map(|_| ())
+    };
+    let nested = |open: &str, close: &str, depth: usize| {
+        let code = format!("{}1{}", open.repeat(depth), close.repeat(depth));
+        parse_with(code, &mut ParseContext::with_max_nesting(64))
+    };
+    let too_deep = |column| {
+        Err(CustomError::UnexpectedToken(
+            String::from("The code is nested too deeply, the maximum is 64 nested expressions"),
+            1,
+            column,
+        ))
+    };
+
+    // the value inside the blocks is the last nested expression
+    assert_eq!(Ok(()), nested("{ ", " }", 63));
+    assert_eq!(too_deep(129), nested("{ ", " }", 64));
+    assert_eq!(too_deep(129), nested("{ ", " }", 1000));
+    assert_eq!(too_deep(65), nested("(", ")", 1000));
+    assert_eq!(too_deep(129), nested("- ", "", 1000));
+
+    // the expressions that fail to parse are not counted after the error
+    let mut context = ParseContext::with_max_nesting(64);
+    for _ in 0..100 {
+        assert!(parse_with(format!("{}1 2", "(".repeat(60)), &mut context).is_err());
+    }
+    assert_eq!(
+        Ok(()),
+        parse_with(
+            format!("{}1{}", "(".repeat(63), ")".repeat(63)),
+            &mut context
+        )
+    );
+}
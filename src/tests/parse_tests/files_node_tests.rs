@@ -0,0 +1,192 @@
+use crate::parse::context::ParseContext;
+use crate::parse::nodes::files_node::FileNode;
+use crate::parse::nodes::statements::Statement;
+use crate::parse::nodes::ToSource;
+use crate::parse::{parse, parse_all, parse_statement, Program};
+use crate::skr_errors::CustomError;
+use crate::tokens::{tokenize, SpaceTypes, Token};
+
+fn parse_statement_of(code: &str) -> Statement {
+    Statement::parse(
+        &mut tokenize(String::from(code)).unwrap(),
+        &mut ParseContext::new(),
+    )
+    .unwrap()
+    .unwrap()
+}
+
+#[test]
+fn test_last_line_is_parsed() {
+    let file_node = parse(tokenize(String::from("1\n2 + 3")).unwrap());
+    assert_eq!(
+        Ok(Some(FileNode::new(vec![
+            parse_statement_of("1"),
+            parse_statement_of("2 + 3")
+        ]))),
+        file_node
+    );
+
+    let file_node = parse(tokenize(String::from("\n1\n\n2\n")).unwrap());
+    assert_eq!(
+        Ok(Some(FileNode::new(vec![
+            parse_statement_of("1"),
+            parse_statement_of("2")
+        ]))),
+        file_node
+    );
+}
+
+#[test]
+fn test_empty_file() {
+    assert_eq!(
+        Ok(Some(FileNode::new(vec![]))),
+        parse(tokenize(String::new()).unwrap())
+    );
+    assert_eq!(
+        Ok(Some(FileNode::new(vec![]))),
+        parse(tokenize(String::from("\n\n")).unwrap())
+    );
+}
+
+#[test]
+fn test_unparsed_tokens() {
+    assert_eq!(
+        Err(CustomError::UnexpectedToken(
+            String::from("Expected a statement"),
+            2,
+            1
+        )),
+        parse(tokenize(String::from("1\n)")).unwrap())
+    );
+}
+
+#[test]
+fn test_program_declarations() {
+    let program: Program = parse(
+        tokenize(String::from(
+            "fu int a 1\nums f (int b) { ei b }\nkat Point { int x 0 }\nfu Point p 0",
+        ))
+        .unwrap(),
+    )
+    .unwrap()
+    .unwrap();
+    assert_eq!(4, program.statements().len());
+    // the class declared above is a type in the next declaration
+    let graph = format!("{:?}", program.statements()[3]);
+    assert!(
+        graph.contains("[CGet Point]") && graph.contains("[GlobalVar]"),
+        "{}",
+        graph
+    );
+}
+
+#[test]
+fn test_parse_all_errors() {
+    let errors = parse_all(tokenize(String::from("fu int\n1 + 1\nfu int b\n)\n2")).unwrap());
+    assert_eq!(
+        Err(vec![
+            CustomError::UnexpectedToken(
+                String::from("Expected the name of the variable after the type int"),
+                1,
+                7
+            ),
+            CustomError::UnexpectedToken(String::from("Expected a statement"), 4, 1),
+        ]),
+        errors.map(|program| program.statements().len())
+    );
+
+    // the error removes the new line while looking for the name of the variable
+    let errors = parse_all(tokenize(String::from("fu int\nju int b\n")).unwrap());
+    assert_eq!(
+        Err(vec![
+            CustomError::UnexpectedToken(
+                String::from("Expected the name of the variable after the type int"),
+                1,
+                7
+            ),
+            CustomError::UnexpectedToken(
+                String::from("Expected the value of the constant b"),
+                2,
+                9
+            ),
+        ]),
+        errors.map(|program| program.statements().len())
+    );
+
+    let program = parse_all(tokenize(String::from("1\n2 + 3")).unwrap());
+    assert_eq!(Ok(2), program.map(|program| program.statements().len()));
+}
+
+fn to_source(code: &str) -> String {
+    parse(tokenize(String::from(code)).unwrap())
+        .unwrap()
+        .unwrap()
+        .to_source()
+}
+
+fn token_kinds(code: &str) -> Vec<Token> {
+    tokenize(String::from(code))
+        .unwrap()
+        .into_iter()
+        .map(|container| container.token)
+        .filter(|token| *token != Token::Space(SpaceTypes::NewLine))
+        .collect()
+}
+
+#[test]
+fn test_to_source_round_trip() {
+    assert_eq!("ju fu int x 5", to_source("ju fu int x 5"));
+    assert_eq!("fu int x 2 ** (1 + 3)", to_source("fu  int x 2**(1+3)"));
+
+    for path in [
+        "resources/test_programs/fibo.skrb",
+        "resources/test_programs/clamp.skrb",
+    ] {
+        let code = std::fs::read_to_string(path).unwrap();
+        let source = to_source(&code);
+        assert_eq!(token_kinds(&code), token_kinds(&source), "{}", path);
+        // the canonical form is stable
+        assert_eq!(source, to_source(&source), "{}", path);
+    }
+}
+
+#[test]
+fn test_parse_statement() {
+    let mut tokens = tokenize(String::from(
+        "\nkat Point { int x 0 }\nfu Point p Point()\n",
+    ))
+    .unwrap();
+    let mut context = ParseContext::new();
+
+    let first = parse_statement(&mut tokens, &mut context).unwrap().unwrap();
+    assert!(matches!(first, Statement::ClassDef(_)));
+    assert_eq!("kat Point {\n    int x 0\n}", first.to_source());
+    // the second statement is still in the tokens
+    assert_eq!(Some(3), tokens.get(1).map(|token| token.line));
+
+    let second = parse_statement(&mut tokens, &mut context).unwrap().unwrap();
+    assert!(matches!(second, Statement::VarDec(_)));
+    assert_eq!("fu Point p Point()", second.to_source());
+    assert_eq!(Ok(None), parse_statement(&mut tokens, &mut context));
+    assert!(tokens.is_empty());
+}
+
+#[test]
+fn test_semicolon_separator() {
+    let program = parse(tokenize(String::from("fu int x 1; fu int y 2")).unwrap())
+        .unwrap()
+        .unwrap();
+    assert_eq!(2, program.statements().len());
+    assert_eq!("fu int x 1", program.statements()[0].to_source());
+    assert_eq!("fu int y 2", program.statements()[1].to_source());
+
+    // the same program on two lines
+    assert_eq!(
+        parse(tokenize(String::from("fu int x 1\nfu int y 2")).unwrap()),
+        parse(tokenize(String::from(";fu int x 1;;\n; fu int y 2;")).unwrap())
+    );
+
+    // the statements of a block are separated the same way
+    let exp = parse_statement_of("kodi { fu int x 1; x 2; x }");
+    assert_eq!("kodi {\n    fu int x 1\n    x 2\n    x\n}", exp.to_source());
+}
@@ -0,0 +1,122 @@
+use crate::parse::context::ParseContext;
+use crate::parse::nodes::blocs::Scope;
+use crate::parse::nodes::functions::{FctDec, Param, Params};
+use crate::parse::nodes::vars::Type;
+use crate::parse::nodes::ToSource;
+use crate::skr_errors::CustomError;
+use crate::tokens::tokenize;
+
+#[test]
+fn test_fct_dec() {
+    let mut tokens = tokenize(String::from("ums add(int a, int b) { 1 }")).unwrap();
+    let scope = Scope::parse(
+        &mut tokenize(String::from("{ 1 }")).unwrap(),
+        &mut ParseContext::new(),
+    )
+    .unwrap()
+    .unwrap();
+    let expected = FctDec::new(
+        String::from("add"),
+        Params::new(vec![
+            Param::new(
+                Type {
+                    name: String::from("int"),
+                },
+                String::from("a"),
+            ),
+            Param::new(
+                Type {
+                    name: String::from("int"),
+                },
+                String::from("b"),
+            ),
+        ]),
+        None,
+        scope,
+    );
+
+    let fct_dec = FctDec::parse(&mut tokens, &mut ParseContext::new());
+    assert_eq!(Ok(Some(expected)), fct_dec);
+    assert!(tokens.is_empty());
+
+    let fct_dec = fct_dec.unwrap().unwrap();
+    let graph = format!("{:?}", fct_dec);
+    assert!(graph.contains("[Param a]"));
+    assert!(graph.contains("[Param b]"));
+    assert_eq!("ums add (int a, int b) {\n    1\n}", fct_dec.to_source());
+}
+
+#[test]
+fn test_fct_dec_errors() {
+    let mut tokens = tokenize(String::from("ums f(a) {}")).unwrap();
+    assert_eq!(
+        Err(CustomError::UnexpectedToken(
+            String::from("Expected a type before the name of the parameter"),
+            1,
+            7
+        )),
+        FctDec::parse(&mut tokens, &mut ParseContext::new())
+    );
+
+    let mut tokens = tokenize(String::from("ums f(int) {}")).unwrap();
+    assert_eq!(
+        Err(CustomError::UnexpectedToken(
+            String::from("Expected the name of the parameter"),
+            1,
+            10
+        )),
+        FctDec::parse(&mut tokens, &mut ParseContext::new())
+    );
+
+    let mut tokens = tokenize(String::from("ums f(int a {}")).unwrap();
+    assert_eq!(
+        Err(CustomError::UnexpectedToken(
+            String::from("Expected a right parenthesis to close the parameters"),
+            1,
+            13
+        )),
+        FctDec::parse(&mut tokens, &mut ParseContext::new())
+    );
+
+    // the parameters are separated by commas
+    let mut tokens = tokenize(String::from("ums f(int a int b) {}")).unwrap();
+    assert_eq!(
+        Err(CustomError::UnexpectedToken(
+            String::from("Expected a right parenthesis to close the parameters"),
+            1,
+            13
+        )),
+        FctDec::parse(&mut tokens, &mut ParseContext::new())
+    );
+
+    let mut tokens = tokenize(String::from("ums f(int a,) {}")).unwrap();
+    assert_eq!(
+        Err(CustomError::UnexpectedToken(
+            String::from("Expected a parameter after the comma"),
+            1,
+            13
+        )),
+        FctDec::parse(&mut tokens, &mut ParseContext::new())
+    );
+}
+
+#[test]
+fn test_fct_dec_return_type() {
+    let mut tokens = tokenize(String::from("ums one () -> int { ei 1 }")).unwrap();
+    let fct_dec = FctDec::parse(&mut tokens, &mut ParseContext::new())
+        .unwrap()
+        .unwrap();
+    assert!(tokens.is_empty());
+    assert_eq!("ums one () -> int {\n    ei 1\n}", fct_dec.to_source());
+    assert!(format!("{:?}", fct_dec).contains("[CGet int]"));
+
+    let mut tokens = tokenize(String::from("ums f () -> { ei 1 }")).unwrap();
+    assert_eq!(
+        Err(CustomError::UnexpectedToken(
+            String::from("Expected the return type after ->"),
+            1,
+            13
+        )),
+        FctDec::parse(&mut tokens, &mut ParseContext::new())
+    );
+}
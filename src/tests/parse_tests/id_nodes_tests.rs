@@ -1,8 +1,10 @@
 use std::collections::VecDeque;
 
-use crate::parse::nodes::id_nodes::{parse_cget, CGet, IdGet, OpIn};
-use crate::skr_errors::ResultOption;
-use crate::tokens::Token;
+use crate::parse::context::ParseContext;
+use crate::parse::nodes::id_nodes::{parse_cget, CGet, FieldAccess, IdGet, OpIn, TupleNode};
+use crate::parse::nodes::ToSource;
+use crate::skr_errors::{CustomError, ResultOption};
+use crate::tokens::{tokenize, Token};
 
 #[test]
 fn test_id_simple() {
@@ -30,7 +32,7 @@ fn test_parse_id_simple() {
         .into_iter()
         .map(|x| x.into())
         .collect();
-    let c_get = parse_cget(&mut tokens);
+    let c_get = parse_cget(&mut tokens, &mut ParseContext::new());
 
     assert_eq!(None, c_get);
 
@@ -40,7 +42,7 @@ fn test_parse_id_simple() {
         .into_iter()
         .map(|x| x.into())
         .collect();
-    let c_get = parse_cget(&mut tokens).unwrap();
+    let c_get = parse_cget(&mut tokens, &mut ParseContext::new()).unwrap();
 
     assert_eq!(
         CGet {
@@ -67,7 +69,7 @@ fn test_parse_set_maxi() {
     .map(|x| x.into())
     .collect();
 
-    let res = IdGet::parse(&mut tokens);
+    let res = IdGet::parse(&mut tokens, &mut ParseContext::new());
     let expected: ResultOption<IdGet> = Ok(Some(IdGet {
         identifier: String::from("maxi"),
         tuple: None,
@@ -102,7 +104,7 @@ fn test_parse_set_mini() {
     .map(|x| x.into())
     .collect();
 
-    let res = IdGet::parse(&mut tokens);
+    let res = IdGet::parse(&mut tokens, &mut ParseContext::new());
     let expected: ResultOption<IdGet> = Ok(Some(IdGet {
         identifier: String::from("mini"),
         tuple: None,
@@ -117,3 +119,129 @@ fn test_parse_set_mini() {
 
     assert_eq!(expected, res);
 }
+
+#[test]
+fn test_parse_index() {
+    // test with "arr:0"
+
+    let mut tokens: VecDeque<_> = vec![
+        Token::Identifier(String::from("arr")),
+        Token::Inside,
+        Token::Int(0),
+    ]
+    .into_iter()
+    .map(|x| x.into())
+    .collect();
+
+    let res = IdGet::parse(&mut tokens, &mut ParseContext::new());
+    let expected: ResultOption<IdGet> = Ok(Some(IdGet {
+        identifier: String::from("arr"),
+        tuple: None,
+        op_in: Box::new(OpIn::Index(0)),
+    }));
+
+    assert_eq!(expected, res);
+}
+
+#[test]
+fn test_parse_tuple() {
+    let mut tokens = tokenize(String::from("()")).unwrap();
+    assert_eq!(
+        Ok(Some(TupleNode::new(vec![], 1))),
+        TupleNode::parse(&mut tokens, &mut ParseContext::new())
+    );
+
+    let mut tokens = tokenize(String::from("(1, 2 + 3, x)")).unwrap();
+    TupleNode::parse(&mut tokens, &mut ParseContext::new())
+        .unwrap()
+        .unwrap();
+    assert!(tokens.is_empty());
+
+    let mut tokens = tokenize(String::from("(1,)")).unwrap();
+    assert_eq!(
+        Err(CustomError::UnexpectedToken(
+            String::from("Expected an expression after the comma"),
+            1,
+            4
+        )),
+        TupleNode::parse(&mut tokens, &mut ParseContext::new())
+    );
+}
+
+#[test]
+fn test_parse_id_get_tuple() {
+    let tuple = |file: &str| {
+        TupleNode::parse(
+            &mut tokenize(String::from(file)).unwrap(),
+            &mut ParseContext::new(),
+        )
+        .unwrap()
+    };
+
+    let mut tokens = tokenize(String::from("foo(1, 2):bar")).unwrap();
+    assert_eq!(
+        Ok(Some(IdGet {
+            identifier: String::from("foo"),
+            tuple: tuple("(1, 2)"),
+            op_in: Box::new(OpIn::IdGet(IdGet {
+                identifier: String::from("bar"),
+                tuple: None,
+                op_in: Box::new(OpIn::Empty),
+            })),
+        })),
+        IdGet::parse(&mut tokens, &mut ParseContext::new())
+    );
+    assert!(tokens.is_empty());
+
+    let mut tokens = tokenize(String::from("foo()")).unwrap();
+    assert_eq!(
+        Ok(Some(IdGet {
+            identifier: String::from("foo"),
+            tuple: Some(TupleNode::new(vec![], 1)),
+            op_in: Box::new(OpIn::Empty),
+        })),
+        IdGet::parse(&mut tokens, &mut ParseContext::new())
+    );
+
+    let mut tokens = tokenize(String::from("foo(1,):bar")).unwrap();
+    assert_eq!(
+        Err(CustomError::UnexpectedToken(
+            String::from("Expected an expression after the comma"),
+            1,
+            7
+        )),
+        IdGet::parse(&mut tokens, &mut ParseContext::new())
+    );
+}
+
+#[test]
+fn test_parse_field_access() {
+    let mut tokens = tokenize(String::from("value:o.inner.count")).unwrap();
+    let id_get = IdGet::parse(&mut tokens, &mut ParseContext::new())
+        .unwrap()
+        .unwrap();
+    let expected = IdGet {
+        identifier: String::from("value"),
+        tuple: None,
+        op_in: Box::new(OpIn::IdGet(IdGet {
+            identifier: String::from("o"),
+            tuple: None,
+            op_in: Box::new(OpIn::Field(FieldAccess::new(
+                String::from("inner"),
+                Some(FieldAccess::new(String::from("count"), None)),
+            ))),
+        })),
+    };
+    assert_eq!(expected, id_get);
+    assert_eq!("value:o.inner.count", id_get.to_source());
+
+    let mut tokens = tokenize(String::from("o.1")).unwrap();
+    assert_eq!(
+        Err(CustomError::UnexpectedToken(
+            String::from("Expected the name of a field after the ."),
+            1,
+            3
+        )),
+        IdGet::parse(&mut tokens, &mut ParseContext::new())
+    );
+}
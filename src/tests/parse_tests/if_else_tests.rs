@@ -0,0 +1,109 @@
+use crate::parse::context::ParseContext;
+use crate::parse::nodes::blocs::Scope;
+use crate::parse::nodes::expressions::Exp;
+use crate::parse::nodes::if_else::{Cond, Ij, Sula};
+use crate::skr_errors::CustomError;
+use crate::tokens::tokenize;
+
+fn parse_exp(content: &str) -> Exp {
+    Exp::parse(
+        &mut tokenize(String::from(content)).unwrap(),
+        &mut ParseContext::new(),
+    )
+    .unwrap()
+    .unwrap()
+}
+
+fn parse_scope(content: &str) -> Scope {
+    Scope::parse(
+        &mut tokenize(String::from(content)).unwrap(),
+        &mut ParseContext::new(),
+    )
+    .unwrap()
+    .unwrap()
+}
+
+#[test]
+fn test_if() {
+    let mut tokens = tokenize(String::from("ij io { 1 }")).unwrap();
+    let expected = Cond::new(Ij::new(parse_exp("io"), parse_scope("{ 1 }")), None);
+
+    assert_eq!(
+        Ok(Some(expected)),
+        Cond::parse(&mut tokens, &mut ParseContext::new())
+    );
+    assert!(tokens.is_empty());
+}
+
+#[test]
+fn test_if_else() {
+    let mut tokens = tokenize(String::from("ij 1 == 2 { 1 } sula { 2 }")).unwrap();
+    let expected = Cond::new(
+        Ij::new(parse_exp("1 == 2"), parse_scope("{ 1 }")),
+        Some(Sula::Scope(parse_scope("{ 2 }"))),
+    );
+
+    assert_eq!(
+        Ok(Some(expected)),
+        Cond::parse(&mut tokens, &mut ParseContext::new())
+    );
+    assert!(tokens.is_empty());
+}
+
+#[test]
+fn test_dangling_else() {
+    let mut tokens = tokenize(String::from("sula { 2 }")).unwrap();
+
+    assert_eq!(
+        Err(CustomError::UnexpectedToken(
+            String::from("Expected an ij before a sula"),
+            1,
+            1
+        )),
+        Cond::parse(&mut tokens, &mut ParseContext::new())
+    );
+}
+
+#[test]
+fn test_else_if_chain() {
+    let mut tokens = tokenize(String::from(
+        "ij no { 1 } sula ij 1 == 2 { 2 } sula ij io { 3 } sula { 4 }",
+    ))
+    .unwrap();
+    let expected = Cond::new(
+        Ij::new(parse_exp("no"), parse_scope("{ 1 }")),
+        Some(Sula::new(
+            Ij::new(parse_exp("1 == 2"), parse_scope("{ 2 }")),
+            Some(Sula::new(
+                Ij::new(parse_exp("io"), parse_scope("{ 3 }")),
+                Some(Sula::Scope(parse_scope("{ 4 }"))),
+            )),
+        )),
+    );
+
+    let cond = Cond::parse(&mut tokens, &mut ParseContext::new());
+    assert_eq!(Ok(Some(expected)), cond);
+    assert!(tokens.is_empty());
+
+    // each sula is nested in the previous one
+    let graph = format!("{:?}", cond.unwrap().unwrap());
+    let sula_depths: Vec<usize> = graph
+        .lines()
+        .scan(0, |depth, line| {
+            if line.starts_with("subgraph ") {
+                *depth += 1;
+            } else if line == "end" {
+                *depth -= 1;
+            }
+            Some((line.starts_with("subgraph Sula_"), *depth))
+        })
+        .filter(|(is_sula, _)| *is_sula)
+        .map(|(_, depth)| depth)
+        .collect();
+    assert_eq!(3, sula_depths.len(), "{}", graph);
+    assert!(
+        sula_depths.windows(2).all(|pair| pair[0] < pair[1]),
+        "{}",
+        graph
+    );
+}
@@ -0,0 +1,82 @@
+use crate::execute::variables::VariableType;
+use crate::execute::{Evaluate, OperationContext};
+use crate::parse::context::ParseContext;
+use crate::parse::nodes::loops::Luba;
+use crate::parse::parse;
+use crate::skr_errors::CustomError;
+use crate::tokens::tokenize;
+use crate::utils::read;
+
+fn parse_luba(code: &str) -> Luba {
+    let mut tokens = tokenize(String::from(code)).unwrap();
+    let luba = Luba::parse(&mut tokens, &mut ParseContext::new())
+        .unwrap()
+        .unwrap();
+    assert!(tokens.is_empty());
+    luba
+}
+
+#[test]
+fn test_counting_loop_program() {
+    let tokens = tokenize(read("resources/test_programs/counting_loop.skrb").unwrap()).unwrap();
+    let file_node = parse(tokens).unwrap().unwrap();
+
+    let graph = format!("{:?}", file_node);
+    assert!(graph.contains("[Luba]"), "{}", graph);
+    assert!(graph.contains("[VarMod]"), "{}", graph);
+}
+
+#[test]
+fn test_luba_errors() {
+    let mut tokens = tokenize(String::from("luba io")).unwrap();
+    assert_eq!(
+        Err(CustomError::UnexpectedToken(
+            String::from("Expected a scope"),
+            0,
+            0
+        )),
+        Luba::parse(&mut tokens, &mut ParseContext::new())
+    );
+
+    let mut tokens = tokenize(String::from("luba { 1 }")).unwrap();
+    assert!(Luba::parse(&mut tokens, &mut ParseContext::new()).is_err());
+}
+
+#[test]
+fn test_luba_evaluation() {
+    assert_eq!(
+        Ok(VariableType::Null),
+        parse_luba("luba no { 1 }").evaluate(&OperationContext::default())
+    );
+    assert_eq!(
+        Ok(VariableType::Null),
+        parse_luba("luba 1 == 2 { 1 / 0 }").evaluate(&OperationContext::default())
+    );
+    assert_eq!(
+        Err(CustomError::InvalidOperation(String::from(
+            "The condition of a loop must be a bool, not a int"
+        ))),
+        parse_luba("luba 1 { 1 }").evaluate(&OperationContext::default())
+    );
+    // the body is executed at each iteration
+    assert_eq!(
+        Err(CustomError::InvalidOperation(String::from(
+            "Division by zero"
+        ))),
+        parse_luba("luba io { 1 / 0 }").evaluate(&OperationContext::default())
+    );
+}
+
+#[test]
+fn test_luba_max_iterations() {
+    let operation_context = OperationContext {
+        max_loop_iterations: 10,
+        ..OperationContext::default()
+    };
+    assert_eq!(
+        Err(CustomError::InvalidOperation(String::from(
+            "The loop did not end after 10 iterations"
+        ))),
+        parse_luba("luba io { 1 }").evaluate(&operation_context)
+    );
+}
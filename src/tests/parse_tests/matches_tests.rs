@@ -0,0 +1,129 @@
+use crate::execute::variables::VariableType;
+use crate::execute::{Evaluate, OperationContext};
+use crate::parse::context::ParseContext;
+use crate::parse::nodes::blocs::Scope;
+use crate::parse::nodes::expressions::Exp;
+use crate::parse::nodes::matches::{Kasu, KasuArm};
+use crate::parse::nodes::operations::ValueBase;
+use crate::parse::nodes::ToSource;
+use crate::skr_errors::CustomError;
+use crate::tokens::tokenize;
+
+fn parse_exp(content: &str) -> Exp {
+    Exp::parse(
+        &mut tokenize(String::from(content)).unwrap(),
+        &mut ParseContext::new(),
+    )
+    .unwrap()
+    .unwrap()
+}
+
+fn parse_scope(content: &str) -> Scope {
+    Scope::parse(
+        &mut tokenize(String::from(content)).unwrap(),
+        &mut ParseContext::new(),
+    )
+    .unwrap()
+    .unwrap()
+}
+
+fn parse_kasu(code: &str) -> Kasu {
+    let mut tokens = tokenize(String::from(code)).unwrap();
+    let kasu = Kasu::parse(&mut tokens, &mut ParseContext::new())
+        .unwrap()
+        .unwrap();
+    assert!(tokens.is_empty());
+    kasu
+}
+
+const THREE_ARMS: &str = "kasu 1 + 1 {
+    1 { 10 }
+    2 { 20 }
+    3 { 30 }
+    sula { 0 }
+}";
+
+#[test]
+fn test_kasu() {
+    let expected = Kasu::new(
+        parse_exp("1 + 1"),
+        vec![
+            KasuArm::new(ValueBase::Int(1), parse_scope("{ 10 }")),
+            KasuArm::new(ValueBase::Int(2), parse_scope("{ 20 }")),
+            KasuArm::new(ValueBase::Int(3), parse_scope("{ 30 }")),
+        ],
+        Some(parse_scope("{ 0 }")),
+    );
+    assert_eq!(expected, parse_kasu(THREE_ARMS));
+
+    let source = parse_kasu(THREE_ARMS).to_source();
+    assert_eq!(parse_kasu(&source), parse_kasu(THREE_ARMS));
+}
+
+#[test]
+fn test_kasu_errors() {
+    let mut tokens = tokenize(String::from("kasu 1 { 1 }")).unwrap();
+    assert_eq!(
+        Err(CustomError::UnexpectedToken(
+            String::from("Expected a scope"),
+            1,
+            12
+        )),
+        Kasu::parse(&mut tokens, &mut ParseContext::new())
+    );
+
+    let mut tokens = tokenize(String::from("kasu 1 { sula { 1 } 2 { 2 } }")).unwrap();
+    assert_eq!(
+        Err(CustomError::UnexpectedToken(
+            String::from("Expected a value, a sula or a right curly bracket"),
+            1,
+            21
+        )),
+        Kasu::parse(&mut tokens, &mut ParseContext::new())
+    );
+}
+
+#[test]
+fn test_kasu_evaluation() {
+    let context = OperationContext::default();
+    assert_eq!(
+        Ok(VariableType::Integer(20)),
+        parse_kasu(THREE_ARMS).evaluate(&context)
+    );
+    assert_eq!(
+        Ok(VariableType::Integer(30)),
+        parse_kasu(&THREE_ARMS.replace("1 + 1", "3")).evaluate(&context)
+    );
+    assert_eq!(
+        Ok(VariableType::Integer(0)),
+        parse_kasu(&THREE_ARMS.replace("1 + 1", "4")).evaluate(&context)
+    );
+    // only the first matching arm is executed
+    assert_eq!(
+        Ok(VariableType::Integer(1)),
+        parse_kasu("kasu 'a' { 'a' { 1 } 'a' { 2 } }").evaluate(&context)
+    );
+    assert_eq!(
+        Ok(VariableType::Null),
+        parse_kasu("kasu 5 { 1 { 1 } }").evaluate(&context)
+    );
+}
+
+#[test]
+fn test_kasu_bool_exhaustiveness() {
+    let context = OperationContext::default();
+    assert_eq!(
+        Ok(VariableType::Integer(2)),
+        parse_kasu("kasu 1 == 2 { io { 1 } no { 2 } }").evaluate(&context)
+    );
+    assert_eq!(
+        Ok(VariableType::Integer(2)),
+        parse_kasu("kasu no { io { 1 } sula { 2 } }").evaluate(&context)
+    );
+    assert_eq!(
+        Err(CustomError::InvalidOperation(String::from(
+            "The kasu on a bool has no arm for no, add it or add a sula"
+        ))),
+        parse_kasu("kasu io { io { 1 } }").evaluate(&context)
+    );
+}
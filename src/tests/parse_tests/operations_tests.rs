@@ -0,0 +1,204 @@
+use crate::execute::variables::VariableType;
+use crate::parse::context::ParseContext;
+use crate::parse::nodes::expressions::Exp;
+use crate::parse::nodes::operations::{
+    NoValueN, OperationN, Operations, TakePriority, TakePriorityLast, TakePriorityN, UnaryTP,
+    ValueBase, ValueNode,
+};
+use crate::parse::nodes::{FoldConstants, Parsable, ToSource};
+use crate::tokens::tokenize;
+
+fn int_tp(value: i64) -> TakePriorityN {
+    TakePriorityN::ElementUnary0(Box::new(UnaryTP::TakePriority(TakePriority::Value(
+        ValueNode::ValueBase(ValueBase::Int(value)),
+    ))))
+}
+
+fn element_n(
+    level: u8,
+    left: TakePriorityN,
+    operation: Operations,
+    right: TakePriorityN,
+) -> TakePriorityN {
+    TakePriorityN::ElementN {
+        level,
+        tp_nm1: Box::new(left),
+        op_n: Some(Box::new(OperationN::new(level, operation, right))),
+    }
+}
+
+#[test]
+fn test_priority() {
+    let mut tokens = tokenize(String::from("1 + 2 * 3")).unwrap();
+    let expected = Exp::TPLast(TakePriorityLast::new(element_n(
+        3,
+        int_tp(1),
+        Operations::Add,
+        element_n(2, int_tp(2), Operations::Mul, int_tp(3)),
+    )));
+
+    assert_eq!(
+        Ok(Some(expected)),
+        Exp::parse(&mut tokens, &mut ParseContext::new())
+    );
+    assert!(tokens.is_empty());
+}
+
+#[test]
+fn test_pow_priority() {
+    let mut tokens = tokenize(String::from("2 * 3 ** 2")).unwrap();
+    let expected = Exp::TPLast(TakePriorityLast::new(element_n(
+        2,
+        int_tp(2),
+        Operations::Mul,
+        element_n(1, int_tp(3), Operations::Pow, int_tp(2)),
+    )));
+
+    assert_eq!(
+        Ok(Some(expected)),
+        Exp::parse(&mut tokens, &mut ParseContext::new())
+    );
+}
+
+#[test]
+fn test_left_associativity() {
+    let mut tokens = tokenize(String::from("1 - 2 - 3")).unwrap();
+    let expected = Exp::TPLast(TakePriorityLast::new(element_n(
+        3,
+        element_n(3, int_tp(1), Operations::Sub, int_tp(2)),
+        Operations::Sub,
+        int_tp(3),
+    )));
+
+    assert_eq!(
+        Ok(Some(expected)),
+        Exp::parse(&mut tokens, &mut ParseContext::new())
+    );
+}
+
+#[test]
+fn test_parentheses_priority() {
+    let mut tokens = tokenize(String::from("(1 + 2) * 3")).unwrap();
+    let parentheses = TakePriorityN::ElementUnary0(Box::new(UnaryTP::TakePriority(
+        TakePriority::Exp(Box::new(Exp::TPLast(TakePriorityLast::new(element_n(
+            3,
+            int_tp(1),
+            Operations::Add,
+            int_tp(2),
+        ))))),
+    )));
+    let expected = Exp::TPLast(TakePriorityLast::new(element_n(
+        2,
+        parentheses,
+        Operations::Mul,
+        int_tp(3),
+    )));
+
+    assert_eq!(
+        Ok(Some(expected)),
+        Exp::parse(&mut tokens, &mut ParseContext::new())
+    );
+}
+
+#[test]
+fn test_no_value_chain() {
+    let mut tokens = tokenize(String::from("+ 1 * 2 + 3 - 4")).unwrap();
+    assert!(NoValueN::parse(&mut tokens, &mut ParseContext::new())
+        .unwrap()
+        .is_some());
+    assert!(tokens.is_empty());
+}
+
+#[test]
+fn test_unary_graph() {
+    let mut tokens = tokenize(String::from("--5")).unwrap();
+    let unary = UnaryTP::parse(&mut tokens, &mut ParseContext::new())
+        .unwrap()
+        .unwrap();
+    let graph = format!("{:?}", unary);
+
+    assert_eq!(graph.matches("[unary_tp -]").count(), 2);
+    assert!(graph.contains("[ValueBase Int 5]"));
+}
+
+#[test]
+fn test_bool_literal() {
+    for (code, value) in [("io", true), ("no", false)] {
+        let mut tokens = tokenize(String::from(code)).unwrap();
+        let expected = Exp::TPLast(TakePriorityLast::new(TakePriorityN::ElementUnary0(
+            Box::new(UnaryTP::TakePriority(TakePriority::Value(
+                ValueNode::ValueBase(ValueBase::Bool(value)),
+            ))),
+        )));
+
+        assert_eq!(
+            Ok(Some(expected)),
+            Exp::parse(&mut tokens, &mut ParseContext::new())
+        );
+        assert!(tokens.is_empty());
+    }
+}
+
+fn folded_exp(code: &str) -> Exp {
+    let mut exp = parse_exp(code);
+    exp.fold_constants();
+    exp
+}
+
+fn parse_exp(code: &str) -> Exp {
+    let mut tokens = tokenize(String::from(code)).unwrap();
+    let exp = Exp::parse(&mut tokens, &mut ParseContext::new())
+        .unwrap()
+        .unwrap();
+    assert!(tokens.is_empty());
+    exp
+}
+
+#[test]
+fn test_fold_constants() {
+    assert_eq!(
+        Exp::TPLast(TakePriorityLast::new(int_tp(14))),
+        folded_exp("2 + 3 * 4")
+    );
+    assert_eq!(parse_exp("9"), folded_exp("(1 + 2) * 3"));
+    assert_eq!(parse_exp("2.5"), folded_exp("5 / 2.0"));
+    assert_eq!(parse_exp("io"), folded_exp("1 + 1 == 2"));
+    // only the constant part of the operation is folded
+    assert_eq!(parse_exp("ij (x) { 6 }"), folded_exp("ij (x) { 2 * 3 }"));
+    assert_eq!("(x) + 6", folded_exp("(x) + 2 * 3").to_source());
+    assert_eq!("(x) * 2 + 12", folded_exp("(x) * 2 + 3 * 4").to_source());
+    assert_eq!(
+        "ums f () {\n    ei 6\n}",
+        folded_exp("ums f () { ei 2 * 3 }").to_source()
+    );
+    assert_eq!(parse_exp("io"), folded_exp("!(1 > 2)"));
+    // the right side is not needed, like in the evaluation
+    assert_eq!(parse_exp("no"), folded_exp("1 > 2 && 1 / 0 > 1"));
+}
+
+#[test]
+fn test_literal() {
+    assert_eq!(
+        Some(VariableType::String(String::from("a"))),
+        parse_exp("\"a\"").literal()
+    );
+    assert_eq!(Some(VariableType::Null), parse_exp("nul").literal());
+    assert_eq!(None, parse_exp("1 + 2").literal());
+    assert_eq!(
+        Some(VariableType::Integer(3)),
+        folded_exp("1 + 2").literal()
+    );
+}
+
+#[test]
+fn test_fold_constants_errors() {
+    // the errors happen at runtime, the operations are not folded
+    for code in ["1 / 0", "9223372036854775807 + 1", "1 + io"] {
+        assert_eq!(parse_exp(code), folded_exp(code), "{}", code);
+    }
+    assert_eq!("1 / 0 + 1 + 2", folded_exp("1 / 0 + 1 + 2").to_source());
+    assert_eq!(
+        "-(-9223372036854775808)",
+        folded_exp("-(-9223372036854775807 - 1)").to_source()
+    );
+}
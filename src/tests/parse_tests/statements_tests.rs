@@ -0,0 +1,75 @@
+use crate::execute::variables::VariableType;
+use crate::execute::{Evaluate, OperationContext};
+use crate::parse::context::ParseContext;
+use crate::parse::nodes::statements::Statement;
+use crate::parse::parse;
+use crate::tokens::tokenize;
+
+fn parse_statement_of(code: &str) -> Statement {
+    let mut tokens = tokenize(String::from(code)).unwrap();
+    let statement = Statement::parse(&mut tokens, &mut ParseContext::new())
+        .unwrap()
+        .unwrap();
+    assert!(tokens.is_empty(), "{}", code);
+    statement
+}
+
+#[test]
+fn test_statement_dispatch() {
+    let variant = |statement: &Statement| match statement {
+        Statement::VarDec(_) => "VarDec",
+        Statement::VarMod(_) => "VarMod",
+        Statement::If(_) => "If",
+        Statement::FunctionDef(_) => "FunctionDef",
+        Statement::ClassDef(_) => "ClassDef",
+        Statement::Return(_) => "Return",
+        Statement::NatCall(_) => "NatCall",
+        Statement::Expr(_) => "Expr",
+    };
+    for (code, expected) in [
+        ("fu int x 1", "VarDec"),
+        ("ju int x 1", "VarDec"),
+        ("int x 1 ? 2 sula 3", "VarDec"),
+        ("x 2", "VarMod"),
+        ("x++", "VarMod"),
+        ("x:y 2", "VarMod"),
+        ("ij io { 1 }", "If"),
+        ("ij io { 1 } sula { 2 }", "If"),
+        ("ums f () { ei 1 }", "FunctionDef"),
+        ("kat Point { int x 0 }", "ClassDef"),
+        ("ei 1", "Return"),
+        ("ei", "Return"),
+        ("skr_app print(1)", "NatCall"),
+        ("1 + 2", "Expr"),
+        ("f(1)", "Expr"),
+        ("x", "Expr"),
+        ("x + 1", "Expr"),
+        ("luba no { 1 }", "Expr"),
+        ("biuli { 1 }", "Expr"),
+        // the condition of a ternary is an expression
+        ("ij io { 1 } ? 2 sula 3", "Expr"),
+        ("skr_app print(1) ? 2 sula 3", "Expr"),
+    ] {
+        assert_eq!(expected, variant(&parse_statement_of(code)), "{}", code);
+    }
+
+    // a class followed by parentheses is a constructor call
+    let program = parse(tokenize(String::from("kat Point { int x 0 }\nPoint()")).unwrap())
+        .unwrap()
+        .unwrap();
+    assert!(matches!(
+        program.statements(),
+        [Statement::ClassDef(_), Statement::Expr(_)]
+    ));
+}
+
+#[test]
+fn test_top_level_return() {
+    let program = parse(tokenize(String::from("fu int x 1\nei x + 1\nx 5")).unwrap())
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        Ok(VariableType::Integer(2)),
+        program.evaluate(&OperationContext::default())
+    );
+}
@@ -0,0 +1,37 @@
+use crate::parse::token_stream::{expect, peek_kind, peek_position};
+use crate::tokens::{tokenize, SpaceTypes, Token};
+
+#[test]
+fn test_expect() {
+    let mut tokens = tokenize(String::from("x 1")).unwrap();
+    // the token is left when the predicate refuses it
+    assert_eq!(None, expect(&mut tokens, |token| *token == Token::Int(1)));
+    assert_eq!(2, tokens.len());
+
+    let identifier = expect(&mut tokens, |token| matches!(token, Token::Identifier(_))).unwrap();
+    assert_eq!(Token::Identifier(String::from("x")), identifier.token);
+    assert_eq!((1, 1), (identifier.line, identifier.column));
+    assert_eq!(
+        Some(Token::Int(1)),
+        expect(&mut tokens, |token| *token == Token::Int(1)).map(|container| container.token)
+    );
+
+    // at the end of the file, the predicate is not called
+    assert_eq!(None, expect(&mut tokens, |_| true));
+}
+
+#[test]
+fn test_peek() {
+    let mut tokens = tokenize(String::from("\n  io")).unwrap();
+    assert_eq!(Some(&Token::Space(SpaceTypes::NewLine)), peek_kind(&tokens));
+    assert_eq!(Some((1, 1)), peek_position(&tokens));
+    tokens.pop_front();
+    assert_eq!(Some(&Token::Bool(true)), peek_kind(&tokens));
+    assert_eq!(Some((2, 3)), peek_position(&tokens));
+    // peeking does not consume the token
+    assert_eq!(1, tokens.len());
+
+    tokens.pop_front();
+    assert_eq!(None, peek_kind(&tokens));
+    assert_eq!(None, peek_position(&tokens));
+}
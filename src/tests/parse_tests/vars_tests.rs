@@ -0,0 +1,356 @@
+use crate::execute::variables::VariableType;
+use crate::execute::{Evaluate, OperationContext};
+use crate::parse::context::ParseContext;
+use crate::parse::nodes::vars::VarDec;
+use crate::parse::nodes::{Graph, GraphDisplay, ToSource};
+use crate::parse::{parse, parse_to_graph, parse_to_json};
+use crate::skr_errors::{CustomError, ShortResult};
+use crate::tokens::tokenize;
+
+#[test]
+fn test_var_dec_error_position() {
+    let mut tokens = tokenize(String::from("\nfu int 3")).unwrap();
+    tokens.pop_front();
+    assert_eq!(
+        VarDec::parse(&mut tokens, &mut ParseContext::new()),
+        Err(CustomError::UnexpectedToken(
+            String::from("Expected the name of the variable after the type int"),
+            2,
+            8
+        ))
+    );
+
+    // At the end of the file, the error is on the last token read
+    let mut tokens = tokenize(String::from("ju fu int")).unwrap();
+    assert_eq!(
+        VarDec::parse(&mut tokens, &mut ParseContext::new()),
+        Err(CustomError::UnexpectedToken(
+            String::from("Expected the name of the variable after the type int"),
+            1,
+            7
+        ))
+    );
+}
+
+#[test]
+fn test_bare_type() {
+    assert_eq!(
+        Err(CustomError::UnexpectedToken(
+            String::from("Expected the name of the variable after the type int"),
+            2,
+            4
+        )),
+        parse(tokenize(String::from("fu int x 1\nint\n(x)")).unwrap())
+    );
+    assert_eq!(
+        Err(CustomError::UnexpectedToken(
+            String::from("Expected the name of the variable after the type skr"),
+            1,
+            1
+        )),
+        parse(tokenize(String::from("skr")).unwrap())
+    );
+
+    // without modifier, a type followed by a name is a declaration
+    let file_node = parse(tokenize(String::from("int x 1\n(x)")).unwrap())
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        Ok(VariableType::Integer(1)),
+        file_node.evaluate(&OperationContext::default())
+    );
+    // a class name followed by parentheses is still a constructor call
+    assert!(parse(tokenize(String::from("kat Point { int x 0 }\nPoint()")).unwrap()).is_ok());
+}
+
+#[test]
+fn test_var_dec_without_value() {
+    let mut tokens = tokenize(String::from("fu int x\n5")).unwrap();
+    let var_dec = VarDec::parse(&mut tokens, &mut ParseContext::new())
+        .unwrap()
+        .unwrap();
+    assert_eq!("fu int x", var_dec.to_source());
+    assert_eq!(2, tokens.len());
+
+    // a constant cannot be assigned later
+    let mut tokens = tokenize(String::from("ju pu int x\n5")).unwrap();
+    assert_eq!(
+        VarDec::parse(&mut tokens, &mut ParseContext::new()),
+        Err(CustomError::UnexpectedToken(
+            String::from("Expected the value of the constant x"),
+            1,
+            12
+        ))
+    );
+    // At the end of the file, the error is on the declaration
+    let mut tokens = tokenize(String::from("ju int x")).unwrap();
+    assert_eq!(
+        VarDec::parse(&mut tokens, &mut ParseContext::new()),
+        Err(CustomError::UnexpectedToken(
+            String::from("Expected the value of the constant x"),
+            1,
+            4
+        ))
+    );
+
+    let mut tokens = tokenize(String::from("ju")).unwrap();
+    assert_eq!(
+        VarDec::parse(&mut tokens, &mut ParseContext::new()),
+        Err(CustomError::UnexpectedToken(
+            String::from("Expected a variable declaration"),
+            0,
+            0
+        ))
+    );
+    // every modifier reports the same error
+    for (code, column) in [("fu 3", 4), ("pu 3", 4), ("ju 3", 4), ("ju pu 3", 7)] {
+        let mut tokens = tokenize(String::from(code)).unwrap();
+        assert_eq!(
+            VarDec::parse(&mut tokens, &mut ParseContext::new()),
+            Err(CustomError::UnexpectedToken(
+                String::from("Expected a variable declaration"),
+                1,
+                column
+            )),
+            "{}",
+            code
+        );
+    }
+}
+
+#[test]
+fn test_var_dec_default_values() {
+    for (declaration, expected) in [
+        ("fu int x", VariableType::Integer(0)),
+        ("fu dar x", VariableType::Float(0.0)),
+        ("fu skr x", VariableType::String(String::new())),
+        ("fu ioi x", VariableType::Boolean(false)),
+        ("fu null x", VariableType::Null),
+        ("kat Point {}\nfu Point x", VariableType::Null),
+    ] {
+        let file_node = parse(tokenize(format!("{}\n(x)", declaration)).unwrap())
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            Ok(expected),
+            file_node.evaluate(&OperationContext::default()),
+            "{}",
+            declaration
+        );
+    }
+
+    // the value is given later
+    let file_node = parse(tokenize(String::from("fu dar x\nx 2\n(x)")).unwrap())
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        Ok(VariableType::Float(2.0)),
+        file_node.evaluate(&OperationContext::default())
+    );
+}
+
+#[test]
+fn test_var_dec_json() {
+    let json = parse_to_json(tokenize(String::from("ju fu int a 1")).unwrap()).unwrap();
+    let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+
+    let vd = &value["statements"][0]["VarDec"]["ConstVar"]["GlobalVar"]["vd"];
+    assert_eq!("int", vd["type"]["name"]);
+    assert_eq!("a", vd["identifier"]);
+    assert_eq!(
+        1,
+        vd["exp"]["TPLast"]["child"]["ElementUnary0"]["TakePriority"]["Value"]["ValueBase"]["Int"]
+    );
+}
+
+#[test]
+fn test_var_dec_graph() {
+    let graph = parse_to_graph(tokenize(String::from("fu int a 1")).unwrap()).unwrap();
+    let subgraphs: Vec<&str> = graph.lines().take(5).collect();
+    assert_eq!(
+        vec![
+            "flowchart TD",
+            "subgraph File_0[File]",
+            "subgraph Statement_1[Statement]",
+            "subgraph VarDec_2[VarDec]",
+            "subgraph GlobalVar_3[GlobalVar]",
+        ],
+        subgraphs
+    );
+}
+
+fn stable_graph(code: &str) -> String {
+    parse(tokenize(String::from(code)).unwrap())
+        .unwrap()
+        .unwrap()
+        .stable_graph()
+}
+
+#[test]
+fn test_stable_graph() {
+    let graph = stable_graph("1\nfu int a 2");
+    let subgraphs: Vec<&str> = graph.lines().take(3).collect();
+    assert_eq!(
+        vec![
+            "flowchart TD",
+            "subgraph File_0[File]",
+            "subgraph Statement_0_0[Statement]"
+        ],
+        subgraphs
+    );
+    assert!(
+        graph.contains("\nsubgraph VarDec_0_1_0[VarDec]\nsubgraph GlobalVar_0_1_0_0[GlobalVar]"),
+        "{}",
+        graph
+    );
+
+    // a bigger first expression does not change the ids of the declaration
+    let declaration =
+        |graph: &str| graph[graph.find("subgraph Statement_0_1[").unwrap()..].to_string();
+    let bigger = stable_graph("1 + 2 * 3\nfu int a 2");
+    assert_eq!(declaration(&graph), declaration(&bigger));
+}
+
+#[test]
+fn test_graph_labels_are_escaped() {
+    let graph = stable_graph("\"a\\\"b\\nc\"\n'\\''");
+    assert!(
+        graph.contains("[ValueBase String #quot;a\\#quot;b\\nc#quot;]\nend"),
+        "{}",
+        graph
+    );
+    assert!(graph.contains("[ValueBase Char '\\'']\nend"), "{}", graph);
+    // every line is a subgraph or its end
+    assert!(graph
+        .lines()
+        .skip(1)
+        .all(|line| line.starts_with("subgraph ") || line == "end"));
+}
+
+#[test]
+fn test_var_dec_dot() {
+    let var_dec = VarDec::parse(
+        &mut tokenize(String::from("fu int a 1")).unwrap(),
+        &mut ParseContext::new(),
+    )
+    .unwrap()
+    .unwrap();
+
+    let dot = var_dec.dot();
+    assert!(dot.starts_with("digraph AST {"));
+    assert!(dot.ends_with("\n}"));
+    assert!(dot.contains("node_0 [label=\"VarDec\"];"));
+    assert!(dot.contains("node_2 [label=\"Vd a\"];"));
+    assert!(dot.contains("node_0 -> node_1;"));
+    assert!(dot.contains("node_1 -> node_2;"));
+    assert!(dot.contains("node_2 -> node_3;"));
+}
+
+struct Label(&'static str);
+
+impl GraphDisplay for Label {
+    fn graph_display(&self, graph: &mut Graph, id: &mut usize) {
+        graph.open("Label", self.0, *id);
+        *id += 1;
+        graph.close();
+    }
+}
+
+#[test]
+fn test_dot_labels_are_escaped() {
+    assert_eq!(
+        "digraph AST {\n    node_0 [label=\"a\\\"b\\nc\\\\\"];\n}",
+        Label("a\"b\nc\\").dot()
+    );
+
+    let dot = parse(tokenize(String::from("\"a\\\"b\\nc\"")).unwrap())
+        .unwrap()
+        .unwrap()
+        .dot();
+    assert!(
+        dot.contains("[label=\"ValueBase String \\\"a\\\\\\\"b\\\\nc\\\"\"];"),
+        "{}",
+        dot
+    );
+}
+
+#[test]
+fn test_var_dec_modifiers() {
+    for (code, message, column) in [
+        ("pu pu int x 5", "Duplicate modifier pu", 4),
+        ("ju ju int x 5", "Duplicate modifier ju", 4),
+        ("ju fu fu int x 5", "Duplicate modifier fu", 7),
+        (
+            "fu pu int x 5",
+            "A variable cannot be both global (fu) and private (pu)",
+            4,
+        ),
+        (
+            "ju pu fu int x 5",
+            "A variable cannot be both global (fu) and private (pu)",
+            7,
+        ),
+        (
+            "pu ju int x 5",
+            "The modifier ju must be the first modifier of the declaration",
+            4,
+        ),
+    ] {
+        let mut tokens = tokenize(String::from(code)).unwrap();
+        assert_eq!(
+            VarDec::parse(&mut tokens, &mut ParseContext::new()),
+            Err(CustomError::UnexpectedToken(
+                String::from(message),
+                1,
+                column
+            )),
+            "{}",
+            code
+        );
+    }
+
+    let mut tokens = tokenize(String::from("ju fu int x 5")).unwrap();
+    assert!(VarDec::parse(&mut tokens, &mut ParseContext::new())
+        .unwrap()
+        .is_some());
+    assert!(tokens.is_empty());
+}
+
+fn evaluate_var_dec(code: &str) -> ShortResult<VariableType> {
+    VarDec::parse(
+        &mut tokenize(String::from(code)).unwrap(),
+        &mut ParseContext::new(),
+    )
+    .unwrap()
+    .unwrap()
+    .evaluate(&OperationContext::default())
+}
+
+#[test]
+fn test_var_dec_type_check() {
+    let values = [
+        ("1", VariableType::Integer(1)),
+        ("1.5", VariableType::Float(1.5)),
+        ("io", VariableType::Boolean(true)),
+        ("\"a\"", VariableType::String(String::from("a"))),
+        ("'c'", VariableType::Char('c')),
+    ];
+    for type_ in ["int", "dar", "ioi", "skr", "null"] {
+        for (literal, value) in &values {
+            let code = format!("ju {} x {}", type_, literal);
+            let expected = match (type_, value) {
+                ("int", VariableType::Integer(_))
+                | ("dar", VariableType::Float(_))
+                | ("ioi", VariableType::Boolean(_))
+                | ("skr", VariableType::String(_)) => Ok(value.clone()),
+                ("dar", VariableType::Integer(_)) => Ok(VariableType::Float(1.0)),
+                _ => Err(CustomError::TypeMismatch(format!(
+                    "Cannot assign a {} to a variable of type {}",
+                    value.type_name(),
+                    type_
+                ))),
+            };
+            assert_eq!(expected, evaluate_var_dec(&code), "{}", code);
+        }
+    }
+}
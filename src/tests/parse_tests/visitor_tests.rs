@@ -0,0 +1,48 @@
+use crate::parse::nodes::vars::{Type, Vd};
+use crate::parse::nodes::visitor::{Visitable, Visitor};
+use crate::parse::parse;
+use crate::tokens::tokenize;
+
+#[derive(Default)]
+struct VdCounter {
+    vd: usize,
+    types: Vec<String>,
+}
+
+impl Visitor for VdCounter {
+    fn visit_vd(&mut self, _node: &Vd) {
+        self.vd += 1;
+    }
+
+    fn visit_type(&mut self, node: &Type) {
+        self.types.push(node.name.clone());
+    }
+}
+
+fn count(code: &str) -> VdCounter {
+    let program = parse(tokenize(String::from(code)).unwrap())
+        .unwrap()
+        .unwrap();
+    let mut counter = VdCounter::default();
+    program.accept(&mut counter);
+    counter
+}
+
+#[test]
+fn test_count_vd() {
+    assert_eq!(0, count("1 + 2").vd);
+    assert_eq!(1, count("fu int x 1").vd);
+
+    let counter = count(
+        "kat Point {\n    int x 0\n    int y 0\n}\n\
+         fu Point p Point()\n\
+         ums f (int n) -> dar {\n    ij n == 0 { kodi { fu int a 1 } }\n    ei 1.0\n}\n\
+         ju skr s \"a\"",
+    );
+    assert_eq!(5, counter.vd);
+    // the types are visited in the order of the code
+    assert_eq!(
+        vec!["int", "int", "Point", "int", "dar", "int", "skr"],
+        counter.types
+    );
+}
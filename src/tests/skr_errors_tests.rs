@@ -0,0 +1,51 @@
+use crate::skr_errors::{render_diagnostic, CustomError};
+
+#[test]
+fn test_render_diagnostic() {
+    let source = "fu int a 1\nfu int\n";
+    let err = CustomError::UnexpectedToken(String::from("Expected a name"), 2, 7);
+    assert_eq!(
+        "error: Unexpected token: Expected a name at line 2 column 7\n  |\n2 | fu int\n  |       ^",
+        render_diagnostic(source, &err)
+    );
+
+    // the caret is aligned with the code after a tab
+    let err = CustomError::UnexpectedToken(String::from("Expected a name"), 1, 3);
+    assert_eq!(
+        "error: Unexpected token: Expected a name at line 1 column 3\n  |\n1 | \ta)\n  | \t ^",
+        render_diagnostic("\ta)", &err)
+    );
+}
+
+#[test]
+fn test_render_diagnostic_out_of_the_line() {
+    // the column is after the end of the line
+    let err = CustomError::UnexpectedToken(String::from("Expected a value"), 1, 40);
+    assert_eq!(
+        "error: Unexpected token: Expected a value at line 1 column 40\n  |\n1 | ab\n  |   ^",
+        render_diagnostic("ab", &err)
+    );
+
+    // errors without a column only show the line
+    let err = CustomError::InvalidInt(String::from("99999999999999999999"), 1);
+    assert_eq!(
+        "error: Invalid int: 99999999999999999999 at line 1\n  |\n1 | 99999999999999999999",
+        render_diagnostic("99999999999999999999", &err)
+    );
+
+    // errors at the end of the file, or after the last line, only show the message
+    let err = CustomError::UnexpectedToken(String::from("Expected a value"), 0, 0);
+    assert_eq!(
+        "error: Unexpected token: Expected a value",
+        render_diagnostic("ab", &err)
+    );
+    assert_eq!(
+        "Unexpected token: Expected a value",
+        CustomError::unexpected_token("Expected a value", None).to_string()
+    );
+    let err = CustomError::UnexpectedToken(String::from("Expected a value"), 3, 1);
+    assert_eq!(
+        "error: Unexpected token: Expected a value at line 3 column 1",
+        render_diagnostic("ab", &err)
+    );
+}
@@ -1,7 +1,10 @@
 use crate::skr_errors::CustomError;
 use crate::token_m;
 use crate::tokens::TokenContainer;
-use crate::tokens::{tokenize, SpaceTypes, Token};
+use crate::tokens::{
+    dump_tokens, tokenize, tokenize_str, ModifierKeyword, SpaceTypes, Token, TokenStream,
+    TokenizeOptions,
+};
 use std::collections::VecDeque;
 
 #[test]
@@ -68,6 +71,22 @@ fn test_simple_chain() {
     assert_valid_tokens(expected, tokens_res);
 }
 
+#[test]
+fn test_pow_tokens() {
+    let tokens_res = tokenize(String::from("2 ** 3 ^ 4 * 5"));
+    let expected = vec![
+        Token::Int(2),
+        Token::Pow,
+        Token::Int(3),
+        Token::Pow,
+        Token::Int(4),
+        Token::Mul,
+        Token::Int(5),
+    ];
+
+    assert_valid_tokens(expected, tokens_res);
+}
+
 #[test]
 fn test_simple_word() {
     let content = String::from("hello");
@@ -77,6 +96,16 @@ fn test_simple_word() {
     assert_valid_tokens(expected, tokens_res);
 }
 
+#[test]
+fn test_tokenize_str() {
+    let source = "ij a == 1 { \"b\" }";
+    assert_eq!(tokenize(String::from(source)), tokenize_str(source));
+
+    let tokens_res = tokenize_str("hello 1");
+    let expected = vec![Token::Identifier(String::from("hello")), Token::Int(1)];
+    assert_valid_tokens(expected, tokens_res);
+}
+
 #[test]
 fn test_simple_string() {
     let content = String::from("\"hello\"");
@@ -166,3 +195,631 @@ fn test_float() {
 
     assert_valid_tokens(expected, tokens_res);
 }
+
+#[test]
+fn test_radix_int() {
+    let content = String::from("0xFF + 0b1010 - 0o17");
+    let tokens_res = tokenize(content);
+    let expected = vec![
+        Token::Int(255),
+        Token::Add,
+        Token::Int(10),
+        Token::Sub,
+        Token::Int(15),
+    ];
+
+    assert_valid_tokens(expected, tokens_res);
+}
+
+#[test]
+fn test_invalid_radix_int() {
+    assert!(matches!(
+        tokenize(String::from("0b102")),
+        Err(CustomError::InvalidInt(_, 1))
+    ));
+    assert!(matches!(
+        tokenize(String::from("\n0x")),
+        Err(CustomError::InvalidInt(_, 2))
+    ));
+}
+
+#[test]
+fn test_digit_separators() {
+    let content = String::from("1_000_000 + 1_0.5_0 + 0xFF_FF");
+    let tokens_res = tokenize(content);
+    let expected = vec![
+        Token::Int(1000000),
+        Token::Add,
+        Token::Float(10.5),
+        Token::Add,
+        Token::Int(0xFFFF),
+    ];
+
+    assert_valid_tokens(expected, tokens_res);
+}
+
+#[test]
+fn test_misplaced_digit_separators() {
+    assert!(matches!(
+        tokenize(String::from("5_")),
+        Err(CustomError::InvalidInt(_, 1))
+    ));
+    assert!(matches!(
+        tokenize(String::from("5__0")),
+        Err(CustomError::InvalidInt(_, 1))
+    ));
+    assert!(matches!(
+        tokenize(String::from("5_.0")),
+        Err(CustomError::InvalidFloat(_, 1))
+    ));
+    assert!(matches!(
+        tokenize(String::from("0x_5")),
+        Err(CustomError::InvalidInt(_, 1))
+    ));
+}
+
+#[test]
+fn test_scientific_notation() {
+    let content = String::from("1e3 + 1.0e-2 + 2E+1");
+    let tokens_res = tokenize(content);
+    let expected = vec![
+        Token::Float(1000.0),
+        Token::Add,
+        Token::Float(0.01),
+        Token::Add,
+        Token::Float(20.0),
+    ];
+
+    assert_valid_tokens(expected, tokens_res);
+}
+
+#[test]
+fn test_invalid_scientific_notation() {
+    assert!(matches!(
+        tokenize(String::from("1e")),
+        Err(CustomError::InvalidFloat(_, 1))
+    ));
+    assert!(matches!(
+        tokenize(String::from("1e-")),
+        Err(CustomError::InvalidFloat(_, 1))
+    ));
+}
+
+#[test]
+fn test_big_int() {
+    let content = String::from("9223372036854775807 + 0x1_0000_0000");
+    let tokens_res = tokenize(content);
+    let expected = vec![Token::Int(i64::MAX), Token::Add, Token::Int(1 << 32)];
+
+    assert_valid_tokens(expected, tokens_res);
+}
+
+#[test]
+fn test_int_overflow() {
+    assert!(matches!(
+        tokenize(String::from("9223372036854775808")),
+        Err(CustomError::InvalidInt(_, 1))
+    ));
+    assert!(matches!(
+        tokenize(String::from("0xFFFF_FFFF_FFFF_FFFF")),
+        Err(CustomError::InvalidInt(_, 1))
+    ));
+    // the error keeps the text of the literal
+    assert_eq!(
+        Err(CustomError::InvalidInt(
+            String::from("The number 123456789012345678901234567890 is too big"),
+            3
+        )),
+        tokenize(String::from(
+            "fu int x 1\n\nx 123_456_789_012_345_678_901_234_567_890"
+        ))
+    );
+}
+
+#[test]
+fn test_number_followed_by_letter() {
+    assert_eq!(
+        Err(CustomError::InvalidInt(
+            String::from("A number cannot be followed by a letter, found a after 3"),
+            1
+        )),
+        tokenize(String::from("3abc"))
+    );
+    assert_eq!(
+        Err(CustomError::InvalidFloat(
+            String::from("A number cannot be followed by a letter, found f after 1.5"),
+            2
+        )),
+        tokenize(String::from("\n1.5f"))
+    );
+    assert_valid_tokens(
+        vec![Token::Int(3), Token::Identifier(String::from("abc"))],
+        tokenize(String::from("3 abc")),
+    );
+}
+
+#[test]
+fn test_comparison_operators() {
+    let content = String::from("a < b > c <= d >= e<=f>1");
+    let tokens_res = tokenize(content);
+    let expected = vec![
+        Token::Identifier(String::from("a")),
+        Token::Less,
+        Token::Identifier(String::from("b")),
+        Token::Greater,
+        Token::Identifier(String::from("c")),
+        Token::LessEqual,
+        Token::Identifier(String::from("d")),
+        Token::GreaterEqual,
+        Token::Identifier(String::from("e")),
+        Token::LessEqual,
+        Token::Identifier(String::from("f")),
+        Token::Greater,
+        Token::Int(1),
+    ];
+
+    assert_valid_tokens(expected, tokens_res);
+}
+
+#[test]
+fn test_equality_operators() {
+    let expected: VecDeque<TokenContainer> = VecDeque::from(vec![
+        TokenContainer::new(Token::Identifier(String::from("a")), 1, 1),
+        TokenContainer::new(Token::Equal, 1, 3).with_end(1, 4),
+        TokenContainer::new(Token::Identifier(String::from("b")), 1, 6),
+    ]);
+    assert_eq!(Ok(expected), tokenize(String::from("a == b")));
+
+    let expected: VecDeque<TokenContainer> = VecDeque::from(vec![
+        TokenContainer::new(Token::Identifier(String::from("a")), 1, 1),
+        TokenContainer::new(Token::NotEqual, 1, 3).with_end(1, 4),
+        TokenContainer::new(Token::Identifier(String::from("b")), 1, 6),
+    ]);
+    assert_eq!(Ok(expected), tokenize(String::from("a != b")));
+}
+
+#[test]
+fn test_assign_and_not() {
+    let content = String::from("a = !b");
+    let tokens_res = tokenize(content);
+    let expected = vec![
+        Token::Identifier(String::from("a")),
+        Token::Assign,
+        Token::Not,
+        Token::Identifier(String::from("b")),
+    ];
+
+    assert_valid_tokens(expected, tokens_res);
+}
+
+#[test]
+fn test_and_or_tokens() {
+    let tokens_res = tokenize(String::from("a && b || c"));
+    let expected = vec![
+        Token::Identifier(String::from("a")),
+        Token::And,
+        Token::Identifier(String::from("b")),
+        Token::Or,
+        Token::Identifier(String::from("c")),
+    ];
+
+    assert_valid_tokens(expected, tokens_res);
+    assert_eq!(
+        Err(CustomError::InvalidCharacter('&', 1, 3)),
+        tokenize(String::from("c & d"))
+    );
+}
+
+#[test]
+fn test_block_comment() {
+    let content = String::from("1 /* a /* nested */ comment */ + /* multi\nline */ 2");
+    let tokens_res = tokenize(content);
+    let expected = vec![
+        Token::Int(1),
+        Token::Add,
+        Token::Space(SpaceTypes::NewLine),
+        Token::Int(2),
+    ];
+
+    assert_valid_tokens(expected, tokens_res);
+}
+
+#[test]
+fn test_block_comment_line_tracking() {
+    let tokens = tokenize(String::from("/*\n\n*/ 1")).unwrap();
+    assert_eq!(tokens.back().unwrap().line, 3);
+}
+
+#[test]
+fn test_unclosed_block_comment() {
+    assert_eq!(
+        tokenize(String::from("1\n/* a /* b */\n")),
+        Err(CustomError::InvalidComment(
+            String::from("Block comment not closed"),
+            2
+        ))
+    );
+}
+
+#[test]
+fn test_columns() {
+    let expected: VecDeque<TokenContainer> = VecDeque::from(vec![
+        TokenContainer::new(Token::Identifier(String::from("ab")), 1, 1).with_end(1, 2),
+        TokenContainer::new(Token::Identifier(String::from("cd")), 1, 4).with_end(1, 5),
+    ]);
+    assert_eq!(Ok(expected), tokenize(String::from("ab cd")));
+
+    let expected: VecDeque<TokenContainer> = VecDeque::from(vec![
+        TokenContainer::new(Token::Identifier(String::from("é")), 1, 1),
+        TokenContainer::new(Token::Space(SpaceTypes::NewLine), 1, 2),
+        TokenContainer::new(Token::Int(12), 2, 3).with_end(2, 4),
+        TokenContainer::new(Token::LessEqual, 2, 6).with_end(2, 7),
+    ]);
+    assert_eq!(Ok(expected), tokenize(String::from("é\n  12 <=")));
+}
+
+#[test]
+fn test_char() {
+    let content = String::from("'a' '\\n' '\\\\' '\\'' 'é'");
+    let tokens_res = tokenize(content);
+    let expected = vec![
+        Token::Char('a'),
+        Token::Char('\n'),
+        Token::Char('\\'),
+        Token::Char('\''),
+        Token::Char('é'),
+    ];
+
+    assert_valid_tokens(expected, tokens_res);
+}
+
+#[test]
+fn test_invalid_char() {
+    assert!(matches!(
+        tokenize(String::from("'ab'")),
+        Err(CustomError::InvalidCharLiteral(_, 1))
+    ));
+    assert!(matches!(
+        tokenize(String::from("\n'a")),
+        Err(CustomError::InvalidCharLiteral(_, 2))
+    ));
+    assert!(matches!(
+        tokenize(String::from("''")),
+        Err(CustomError::InvalidCharLiteral(_, 1))
+    ));
+}
+
+#[test]
+fn test_unicode_escape() {
+    let content = String::from("\"\\u{41}\" \"a\\u{e9}\\u{1F600}b\"");
+    let tokens_res = tokenize(content);
+    let expected = vec![
+        Token::String(String::from("A")),
+        Token::String(String::from("aé😀b")),
+    ];
+
+    assert_valid_tokens(expected, tokens_res);
+}
+
+#[test]
+fn test_invalid_unicode_escape() {
+    for content in [
+        "\"\\u{}\"",
+        "\"\\u{ZZ}\"",
+        "\"\\u{41\"",
+        "\"\\u41\"",
+        "\"\\u{110000}\"",
+        "\"\\u{D800}\"",
+    ] {
+        assert!(
+            matches!(
+                tokenize(String::from(content)),
+                Err(CustomError::InvalidString(_, 1))
+            ),
+            "{}",
+            content
+        );
+    }
+}
+
+#[test]
+fn test_raw_string() {
+    let content = String::from("r\"C:\\path\\n\" r rr\"a\"");
+    let tokens_res = tokenize(content);
+    let expected = vec![
+        Token::String(String::from("C:\\path\\n")),
+        Token::Identifier(String::from("r")),
+        Token::Identifier(String::from("rr")),
+        Token::String(String::from("a")),
+    ];
+
+    assert_valid_tokens(expected, tokens_res);
+}
+
+#[test]
+fn test_unclosed_raw_string() {
+    assert!(matches!(
+        tokenize(String::from("r\"abc")),
+        Err(CustomError::InvalidString(_, 1))
+    ));
+}
+
+#[test]
+fn test_token_stream_is_lazy() {
+    // The unclosed string is never read when stopping early
+    let mut stream = TokenStream::new("fu int a 1 \"not closed");
+    assert_eq!(
+        Some(Ok(TokenContainer::new(
+            Token::KeywordModifier(ModifierKeyword::Global),
+            1,
+            1
+        )
+        .with_end(1, 2))),
+        stream.next()
+    );
+    assert_eq!(
+        Some(Ok(TokenContainer::new(
+            Token::Identifier(String::from("int")),
+            1,
+            4
+        )
+        .with_end(1, 6))),
+        stream.next()
+    );
+}
+
+#[test]
+fn test_token_stream_stops_after_error() {
+    let mut stream = TokenStream::new("1 \"not closed\n2");
+    assert_eq!(
+        Some(Ok(TokenContainer::new(Token::Int(1), 1, 1))),
+        stream.next()
+    );
+    assert!(matches!(stream.next(), Some(Err(_))));
+    assert_eq!(None, stream.next());
+}
+
+#[test]
+fn test_keep_comments() {
+    let options = TokenizeOptions {
+        keep_comments: true,
+        ..TokenizeOptions::default()
+    };
+    let tokens: Result<VecDeque<TokenContainer>, CustomError> =
+        TokenStream::with_options("1 // one\n/* two\nlines */ 2", options).collect();
+    assert_eq!(
+        Ok(VecDeque::from(vec![
+            TokenContainer::new(Token::Int(1), 1, 1),
+            TokenContainer::new(Token::Comment(String::from(" one")), 1, 3).with_end(1, 8),
+            TokenContainer::new(Token::Space(SpaceTypes::NewLine), 1, 9),
+            TokenContainer::new(Token::Comment(String::from(" two\nlines ")), 2, 1).with_end(3, 8),
+            TokenContainer::new(Token::Int(2), 3, 10),
+        ])),
+        tokens
+    );
+
+    // comments are discarded by default
+    let tokens = tokenize(String::from("1 // one\n/* two */ 2")).unwrap();
+    assert!(tokens
+        .iter()
+        .all(|token| !matches!(token.token, Token::Comment(_))));
+}
+
+#[test]
+fn test_keep_spaces() {
+    let options = TokenizeOptions {
+        keep_spaces: true,
+        ..TokenizeOptions::default()
+    };
+    let tokens: Result<VecDeque<TokenContainer>, CustomError> =
+        TokenStream::with_options("a\tb c\n", options).collect();
+    assert_eq!(
+        Ok(VecDeque::from(vec![
+            TokenContainer::new(Token::Identifier(String::from("a")), 1, 1),
+            TokenContainer::new(Token::Space(SpaceTypes::Tab), 1, 2),
+            TokenContainer::new(Token::Identifier(String::from("b")), 1, 3),
+            TokenContainer::new(Token::Space(SpaceTypes::Space), 1, 4),
+            TokenContainer::new(Token::Identifier(String::from("c")), 1, 5),
+            TokenContainer::new(Token::Space(SpaceTypes::NewLine), 1, 6),
+        ])),
+        tokens
+    );
+
+    // spaces and tabs are discarded by default
+    let tokens = tokenize(String::from("a\tb c")).unwrap();
+    assert_eq!(3, tokens.len());
+}
+
+#[test]
+fn test_array_tokens() {
+    let tokens = tokenize(String::from("[1, 2]")).unwrap();
+    assert_eq!(
+        VecDeque::from(vec![
+            TokenContainer::new(Token::LeftBracket, 1, 1),
+            TokenContainer::new(Token::Int(1), 1, 2),
+            TokenContainer::new(Token::Comma, 1, 3),
+            TokenContainer::new(Token::Int(2), 1, 5),
+            TokenContainer::new(Token::RightBracket, 1, 6),
+        ]),
+        tokens
+    );
+}
+
+#[test]
+fn test_crlf_line_endings() {
+    for file in ["a\r\nb", "a\rb"] {
+        let tokens = tokenize(String::from(file)).unwrap();
+        assert_eq!(
+            VecDeque::from(vec![
+                TokenContainer::new(Token::Identifier(String::from("a")), 1, 1),
+                TokenContainer::new(Token::Space(SpaceTypes::NewLine), 1, 2),
+                TokenContainer::new(Token::Identifier(String::from("b")), 2, 1),
+            ]),
+            tokens
+        );
+    }
+
+    // the comments end at the \r and count the lines once
+    let tokens = tokenize(String::from("a // c\r\n/* x\r\ny\rz */ b\r\n\r\nc")).unwrap();
+    let lines: Vec<(usize, usize)> = tokens
+        .iter()
+        .filter(|token| matches!(token.token, Token::Identifier(_)))
+        .map(|token| (token.line, token.column))
+        .collect();
+    assert_eq!(vec![(1, 1), (4, 6), (6, 1)], lines);
+}
+
+#[test]
+fn test_invalid_character() {
+    assert_eq!(
+        Err(CustomError::InvalidCharacter('@', 3, 5)),
+        tokenize(String::from("1\n2\n3 + @"))
+    );
+
+    let options = TokenizeOptions {
+        keep_invalid: true,
+        ..TokenizeOptions::default()
+    };
+    let tokens: Result<VecDeque<TokenContainer>, CustomError> =
+        TokenStream::with_options("a @ & b", options).collect();
+    assert_eq!(
+        Ok(VecDeque::from(vec![
+            TokenContainer::new(Token::Identifier(String::from("a")), 1, 1),
+            TokenContainer::new(Token::Invalid(String::from("@")), 1, 3),
+            TokenContainer::new(Token::Invalid(String::from("&")), 1, 5),
+            TokenContainer::new(Token::Identifier(String::from("b")), 1, 7),
+        ])),
+        tokens
+    );
+}
+
+#[test]
+fn test_token_end() {
+    let tokens = tokenize(String::from("hello \"ab\" 'é' 3.25 ** x\n0x1F")).unwrap();
+    let spans: Vec<_> = tokens
+        .iter()
+        .map(|token| (token.line, token.column, token.end_line, token.end_column))
+        .collect();
+    assert_eq!(
+        vec![
+            (1, 1, 1, 5),
+            (1, 7, 1, 10),
+            (1, 12, 1, 14),
+            (1, 16, 1, 19),
+            (1, 21, 1, 22),
+            (1, 24, 1, 24),
+            (1, 25, 1, 25),
+            (2, 1, 2, 4),
+        ],
+        spans
+    );
+}
+
+#[test]
+fn test_unclosed_string_messages() {
+    for (code, message) in [
+        ("\"abc", "String not closed"),
+        (
+            "\"abc\\",
+            "Dangling escape: the \\ at the end of the string escapes nothing",
+        ),
+        (
+            "\"abc\\\"",
+            "String not closed: the last \" is escaped by a \\",
+        ),
+        ("1\n\"a\\\\", "String not closed"),
+    ] {
+        let line = code.lines().count();
+        assert_eq!(
+            Err(CustomError::InvalidString(String::from(message), line)),
+            tokenize(String::from(code))
+        );
+    }
+}
+
+#[test]
+fn test_dump_tokens() {
+    let tokens = tokenize(String::from("count 42")).unwrap();
+    assert_eq!(
+        "1:1-1:5 Identifier(\"count\")\n1:7-1:8 Int(42)\n",
+        dump_tokens(&tokens)
+    );
+}
+
+#[test]
+fn test_semicolon_tokens() {
+    let tokens_res = tokenize(String::from("a 1;b;"));
+    let expected = vec![
+        Token::Identifier(String::from("a")),
+        Token::Int(1),
+        Token::Semicolon,
+        Token::Identifier(String::from("b")),
+        Token::Semicolon,
+    ];
+
+    assert_valid_tokens(expected, tokens_res);
+}
+
+#[test]
+fn test_dot_tokens() {
+    let tokens_res = tokenize(String::from("obj.field 1.5 o.x.y x.5"));
+    let expected = vec![
+        Token::Identifier(String::from("obj")),
+        Token::Dot,
+        Token::Identifier(String::from("field")),
+        Token::Float(1.5),
+        Token::Identifier(String::from("o")),
+        Token::Dot,
+        Token::Identifier(String::from("x")),
+        Token::Dot,
+        Token::Identifier(String::from("y")),
+        Token::Identifier(String::from("x")),
+        Token::Dot,
+        Token::Int(5),
+    ];
+
+    assert_valid_tokens(expected, tokens_res);
+}
+
+#[test]
+fn test_increment_tokens() {
+    let tokens_res = tokenize(String::from("x++ count_2-- x + +y --5 1++"));
+    let expected = vec![
+        Token::Identifier(String::from("x")),
+        Token::Increment,
+        Token::Identifier(String::from("count_2")),
+        Token::Decrement,
+        Token::Identifier(String::from("x")),
+        Token::Add,
+        Token::Add,
+        Token::Identifier(String::from("y")),
+        // the signs are only joined right after a name
+        Token::Sub,
+        Token::Sub,
+        Token::Int(5),
+        Token::Int(1),
+        Token::Add,
+        Token::Add,
+    ];
+
+    assert_valid_tokens(expected, tokens_res);
+}
+
+#[test]
+fn test_arrow_tokens() {
+    let tokens_res = tokenize(String::from("-> - > a-b a->b -"));
+    let expected = vec![
+        Token::Arrow,
+        Token::Sub,
+        Token::Greater,
+        Token::Identifier(String::from("a")),
+        Token::Sub,
+        Token::Identifier(String::from("b")),
+        Token::Identifier(String::from("a")),
+        Token::Arrow,
+        Token::Identifier(String::from("b")),
+        Token::Sub,
+    ];
+
+    assert_valid_tokens(expected, tokens_res);
+}
@@ -22,20 +22,42 @@ pub enum SpaceTypes {
 #[derive(Debug, PartialEq)]
 pub enum Token {
     Bool(bool),
-    Int(u32),
-    Float(f32),
+    Int(i64),
+    Float(f64),
     String(String),
+    Char(char),
+    /// = nul
+    Null,
     NatCall,
     Add,
     Sub,
     Not,
     Div,
     Mul,
+    Mod,
+    /// `**` or `^`
+    Pow,
+    /// `->`, before the return type of a function
+    Arrow,
+    /// `++` right after a name : `x++`
+    Increment,
+    /// `--` right after a name : `x--`
+    Decrement,
     LeftParenthesis,
     RightParenthesis,
     LeftBrace,
     RightBrace,
+    LeftBracket,
+    RightBracket,
+    Comma,
+    /// `;`, separates the statements written on the same line
+    Semicolon,
+    Question,
+    /// `:`, the chains read from the right : `field:o`
     Inside,
+    /// `.`, the field access read from the left : `o.field`. After digits, the `.` is the point of
+    /// a float
+    Dot,
     Identifier(String),
     Space(SpaceTypes),
     KeywordModifier(ModifierKeyword),
@@ -50,14 +72,25 @@ pub enum Token {
     KeywordSimpleScope,
     /// = spoki
     KeywordUnusedScope,
-    Invalid(String), // Any character not used by other tokens, only used when parsing bloc title
-    // TODO : Pow
+    /// = luba
+    KeywordLoop,
+    /// = kasu
+    KeywordMatch,
+    /// Any character not used by other tokens, only used when parsing bloc title. Only emitted with
+    /// [TokenizeOptions::keep_invalid], else the character is an error.
+    Invalid(String),
+    /// Text of a comment, only kept with [TokenizeOptions::keep_comments]
+    Comment(String),
     // TODO : and, or, xor, not
-    // TODO : comparison operators
-    Equal,    // not tokenized for now : missing symbol
-    NotEqual, // not tokenized for now : missing symbol
-    And,      // not tokenized for now : missing symbol
-    Or,       // not tokenized for now : missing symbol
+    Less,
+    Greater,
+    LessEqual,
+    GreaterEqual,
+    Equal,
+    NotEqual,
+    Assign,
+    And, // &&
+    Or,  // ||
 }
 
 impl Display for Token {
@@ -71,40 +104,122 @@ pub struct TokenContainer {
     pub token: Token,
     pub line: usize,
     pub column: usize,
+    /// Line of the last character of the token
+    pub end_line: usize,
+    /// Column of the last character of the token, equal to `column` for a single character
+    pub end_column: usize,
 }
 
 #[cfg(test)]
 impl TokenContainer {
+    /// A token of a single character
     pub fn new(token: Token, line: usize, column: usize) -> Self {
         Self {
             token,
             line,
             column,
+            end_line: line,
+            end_column: column,
+        }
+    }
+
+    /// Set the position of the last character, for the tokens of several characters
+    pub fn with_end(self, end_line: usize, end_column: usize) -> Self {
+        Self {
+            end_line,
+            end_column,
+            ..self
         }
     }
 }
 
 #[cfg(test)]
-impl Into<TokenContainer> for Token {
-    fn into(self) -> TokenContainer {
-        TokenContainer::new(self, 0, 0)
+impl From<Token> for TokenContainer {
+    fn from(token: Token) -> Self {
+        TokenContainer::new(token, 0, 0)
+    }
+}
+
+/// Get the character represented by an escape sequence, `ch` being the character after the `\`.
+fn escape_char(ch: char) -> char {
+    match ch {
+        'n' => '\n',
+        't' => '\t',
+        'r' => '\r',
+        '0' => '\0',
+        _ => ch,
+    }
+}
+
+/// Tokenize the `{XXXX}` part of a unicode escape sequence `\u{XXXX}` in a string. The `\u` must
+/// already be consumed.
+fn tokenize_unicode_escape(file: &mut Chars, line: usize) -> Result<char, CustomError> {
+    if file.next() != Some('{') {
+        return Err(CustomError::InvalidString(
+            "Expected { after \\u".to_string(),
+            line,
+        ));
+    }
+
+    let mut digits = String::new();
+    loop {
+        match file.next() {
+            Some('}') => break,
+            Some(ch) if ch.is_ascii_hexdigit() && digits.len() < 6 => digits.push(ch),
+            Some(ch) if ch.is_ascii_hexdigit() => {
+                return Err(CustomError::InvalidString(
+                    "A unicode escape can have at most 6 digits".to_string(),
+                    line,
+                ))
+            }
+            Some(ch) => {
+                return Err(CustomError::InvalidString(
+                    format!("Invalid hexadecimal digit {} in a unicode escape", ch),
+                    line,
+                ))
+            }
+            None => {
+                return Err(CustomError::InvalidString(
+                    "Unicode escape not closed".to_string(),
+                    line,
+                ))
+            }
+        }
+    }
+
+    if digits.is_empty() {
+        return Err(CustomError::InvalidString(
+            "A unicode escape needs at least one digit".to_string(),
+            line,
+        ));
+    }
+
+    match char::from_u32(u32::from_str_radix(&digits, 16).unwrap()) {
+        Some(ch) => Ok(ch),
+        None => Err(CustomError::InvalidString(
+            format!("{} is not a valid unicode code point", digits),
+            line,
+        )),
     }
 }
 
+/// Tokenize a string, the opening `"` must already be consumed. When the file ends before the
+/// closing `"`, the error tells whether the last character is a `\` escaping nothing or an
+/// escaped `"` that was maybe meant to close the string.
 fn tokenize_string(file: &mut Chars, line: usize) -> Result<Token, CustomError> {
     let mut current_ch = file.next();
     let mut string_escape = false;
+    let mut escaped_quote = false;
     let mut res = String::new();
 
     while let Some(ch) = current_ch {
+        escaped_quote = string_escape && ch == '"';
         if string_escape {
-            res.push(match ch {
-                'n' => '\n',
-                't' => '\t',
-                'r' => '\r',
-                '0' => '\0',
-                _ => ch,
-            });
+            if ch == 'u' {
+                res.push(tokenize_unicode_escape(file, line)?);
+            } else {
+                res.push(escape_char(ch));
+            }
             string_escape = false;
         } else if ch == '\\' {
             string_escape = true;
@@ -117,12 +232,189 @@ fn tokenize_string(file: &mut Chars, line: usize) -> Result<Token, CustomError>
         current_ch = file.next();
     }
 
+    let message = if string_escape {
+        "Dangling escape: the \\ at the end of the string escapes nothing"
+    } else if escaped_quote {
+        "String not closed: the last \" is escaped by a \\"
+    } else {
+        "String not closed"
+    };
+    Err(CustomError::InvalidString(message.to_string(), line))
+}
+
+/// Tokenize a raw string (`r"..."`): every character is kept as is until the closing `"`. The
+/// opening `r"` must already be consumed.
+fn tokenize_raw_string(file: &mut Chars, line: usize) -> Result<Token, CustomError> {
+    let mut res = String::new();
+
+    for ch in file.by_ref() {
+        if ch == '"' {
+            return Ok(Token::String(res));
+        }
+        res.push(ch);
+    }
+
     Err(CustomError::InvalidString(
         "String not closed".to_string(),
         line,
     ))
 }
 
+fn tokenize_char(file: &mut Chars, line: usize) -> Result<Token, CustomError> {
+    let value = match file.next() {
+        Some('\\') => match file.next() {
+            Some(ch) => escape_char(ch),
+            None => {
+                return Err(CustomError::InvalidCharLiteral(
+                    "Char not closed".to_string(),
+                    line,
+                ))
+            }
+        },
+        Some('\'') => {
+            return Err(CustomError::InvalidCharLiteral(
+                "A char cannot be empty".to_string(),
+                line,
+            ))
+        }
+        Some(ch) => ch,
+        None => {
+            return Err(CustomError::InvalidCharLiteral(
+                "Char not closed".to_string(),
+                line,
+            ))
+        }
+    };
+
+    match file.next() {
+        Some('\'') => Ok(Token::Char(value)),
+        Some(_) => Err(CustomError::InvalidCharLiteral(
+            "A char must contain exactly one character".to_string(),
+            line,
+        )),
+        None => Err(CustomError::InvalidCharLiteral(
+            "Char not closed".to_string(),
+            line,
+        )),
+    }
+}
+
+/// Error returned when a `_` digit separator is not placed between two digits.
+fn misplaced_separator(line: usize, is_float: bool) -> CustomError {
+    let message = "A _ in a number must be placed between two digits".to_string();
+    if is_float {
+        CustomError::InvalidFloat(message, line)
+    } else {
+        CustomError::InvalidInt(message, line)
+    }
+}
+
+/// Tokenize the digits of an integer written with a base prefix (`0x`, `0b` or `0o`). The prefix
+/// must already be consumed.
+fn tokenize_radix_number(
+    file: &mut Chars,
+    line: usize,
+    radix: u32,
+) -> Result<(Token, Option<char>), CustomError> {
+    let mut current_ch = file.next();
+    let mut res = String::new();
+    let mut last_is_separator = false;
+
+    while let Some(ch) = current_ch {
+        if ch == '_' {
+            if res.is_empty() || last_is_separator {
+                return Err(misplaced_separator(line, false));
+            }
+            last_is_separator = true;
+        } else if ch.is_alphanumeric() {
+            if !ch.is_digit(radix) {
+                return Err(CustomError::InvalidInt(
+                    format!("Invalid digit {} for a number in base {}", ch, radix),
+                    line,
+                ));
+            }
+            res.push(ch);
+            last_is_separator = false;
+        } else {
+            break;
+        }
+        current_ch = file.next();
+    }
+
+    if last_is_separator {
+        return Err(misplaced_separator(line, false));
+    }
+
+    if res.is_empty() {
+        return Err(CustomError::InvalidInt(
+            format!(
+                "Expected digits after the prefix of a number in base {}",
+                radix
+            ),
+            line,
+        ));
+    }
+
+    match i64::from_str_radix(&res, radix) {
+        Ok(value) => Ok((Token::Int(value), current_ch)),
+        Err(_) => Err(CustomError::InvalidInt(
+            format!("The number {} is too big", res),
+            line,
+        )),
+    }
+}
+
+/// Tokenize the exponent of a float written in scientific notation (`1.5e10`, `2e-3`). The `e` must
+/// already be consumed and pushed at the end of `res`. A number with an exponent is always a float.
+fn tokenize_exponent(
+    file: &mut Chars,
+    line: usize,
+    mut res: String,
+) -> Result<(Token, Option<char>), CustomError> {
+    let mut current_ch = file.next();
+    if let Some(sign @ ('+' | '-')) = current_ch {
+        res.push(sign);
+        current_ch = file.next();
+    }
+
+    let mut has_digits = false;
+    let mut last_is_separator = false;
+    while let Some(ch) = current_ch {
+        if ch == '_' {
+            if !has_digits || last_is_separator {
+                return Err(misplaced_separator(line, true));
+            }
+            last_is_separator = true;
+        } else if ch.is_numeric() {
+            res.push(ch);
+            has_digits = true;
+            last_is_separator = false;
+        } else {
+            break;
+        }
+        current_ch = file.next();
+    }
+
+    if last_is_separator {
+        return Err(misplaced_separator(line, true));
+    }
+
+    if !has_digits {
+        return Err(CustomError::InvalidFloat(
+            "Expected digits after the exponent of a float".to_string(),
+            line,
+        ));
+    }
+
+    match res.parse() {
+        Ok(value) => Ok((Token::Float(value), current_ch)),
+        Err(_) => Err(CustomError::InvalidFloat(
+            format!("Invalid float {}", res),
+            line,
+        )),
+    }
+}
+
 fn tokenize_number(
     file: &mut Chars,
     line: usize,
@@ -132,6 +424,16 @@ fn tokenize_number(
     let mut res = String::new();
     res.push(first_char);
     let mut is_float = false;
+    let mut last_ch = first_char;
+
+    if first_char == '0' {
+        match current_ch {
+            Some('x') => return tokenize_radix_number(file, line, 16),
+            Some('b') => return tokenize_radix_number(file, line, 2),
+            Some('o') => return tokenize_radix_number(file, line, 8),
+            _ => {}
+        }
+    }
 
     while let Some(ch) = current_ch {
         if ch == '.' {
@@ -140,33 +442,53 @@ fn tokenize_number(
                     "A float can have only one . !".to_string(),
                     line,
                 ));
-            } else {
-                is_float = true;
-                res.push(ch);
             }
+            if last_ch == '_' {
+                return Err(misplaced_separator(line, true));
+            }
+            is_float = true;
+            res.push(ch);
+        } else if ch == '_' {
+            // digit separators are skipped, they are only allowed between two digits
+            if !last_ch.is_numeric() {
+                return Err(misplaced_separator(line, is_float));
+            }
+        } else if ch == 'e' || ch == 'E' {
+            if last_ch == '_' {
+                return Err(misplaced_separator(line, is_float));
+            }
+            res.push('e');
+            return tokenize_exponent(file, line, res);
         } else if ch.is_numeric() {
             res.push(ch);
         } else {
-            return Ok((
-                if is_float {
-                    Token::Float(res.parse().unwrap())
-                } else {
-                    Token::Int(res.parse().unwrap())
-                },
-                Some(ch),
-            ));
+            break;
         }
+        last_ch = ch;
         current_ch = file.next();
     }
 
-    Ok((
-        if is_float {
-            Token::Float(res.parse().unwrap())
-        } else {
-            Token::Int(res.parse().unwrap())
-        },
-        None,
-    ))
+    if last_ch == '_' {
+        return Err(misplaced_separator(line, is_float));
+    }
+
+    if is_float {
+        return match res.parse() {
+            Ok(value) => Ok((Token::Float(value), current_ch)),
+            Err(_) => Err(CustomError::InvalidFloat(
+                format!("Invalid float {}", res),
+                line,
+            )),
+        };
+    }
+
+    match res.parse() {
+        Ok(value) => Ok((Token::Int(value), current_ch)),
+        Err(_) => Err(CustomError::InvalidInt(
+            format!("The number {} is too big", res),
+            line,
+        )),
+    }
 }
 
 fn tokenize_word(file: &mut Chars, first_char: char) -> Result<(Token, Option<char>), CustomError> {
@@ -196,96 +518,425 @@ fn word_to_token(res: String) -> Token {
         "skr_app" => Token::NatCall,
         "io" => Token::Bool(true),
         "no" => Token::Bool(false),
+        "nul" => Token::Null,
         "ums" => Token::KeywordFunction,
         "kat" => Token::KeywordClass,
         "ei" => Token::KeywordReturn,
         "biuli" => Token::KeywordBubbleScope,
         "kodi" => Token::KeywordSimpleScope,
         "spoki" => Token::KeywordUnusedScope,
+        "luba" => Token::KeywordLoop,
+        "kasu" => Token::KeywordMatch,
         _ => Token::Identifier(res),
     }
 }
 
-fn tokenize_comment_classic(file: &mut Chars) {
+/// Consume a line comment, the opening `//` must already be consumed. Returns the text of the
+/// comment and the character after it : the `\n` ending the comment is not consumed.
+fn tokenize_comment_classic(file: &mut Chars) -> (String, Option<char>) {
+    let mut text = String::new();
     let mut current_ch = file.next();
     while let Some(ch) = current_ch {
-        if ch == '\n' {
-            return;
+        if ch == '\n' || ch == '\r' {
+            return (text, Some(ch));
         }
+        text.push(ch);
         current_ch = file.next();
     }
+    (text, None)
 }
 
-macro_rules! add_token {
-    ($tokens:expr, $line:expr, $column:expr, $token:expr) => {
-        $tokens.push_back(TokenContainer {
-            token: $token,
-            line: $line,
-            column: $column,
-        });
-    };
+/// Consume a block comment until the matching `*/` and return its text. The opening `/*` must
+/// already be consumed. Block comments can be nested, and `line` is incremented for each new line
+/// inside the comment.
+fn tokenize_comment_block(file: &mut Chars, line: &mut usize) -> Result<String, CustomError> {
+    let start_line = *line;
+    let mut text = String::new();
+    let mut depth = 1;
+    let mut previous_ch = None;
+    let mut current_ch = file.next();
+
+    while let Some(ch) = current_ch {
+        match (previous_ch, ch) {
+            (Some('*'), '/') => {
+                depth -= 1;
+                if depth == 0 {
+                    text.pop();
+                    return Ok(text);
+                }
+                previous_ch = None;
+            }
+            (Some('/'), '*') => {
+                depth += 1;
+                previous_ch = None;
+            }
+            _ => {
+                // \r\n is counted once, on the \n
+                if ch == '\n' || (ch == '\r' && !file.as_str().starts_with('\n')) {
+                    *line += 1;
+                }
+                previous_ch = Some(ch);
+            }
+        }
+        text.push(ch);
+        current_ch = file.next();
+    }
+
+    Err(CustomError::InvalidComment(
+        "Block comment not closed".to_string(),
+        start_line,
+    ))
 }
 
-pub(crate) fn tokenize(file: String) -> Result<VecDeque<TokenContainer>, CustomError> {
-    let mut tokens: VecDeque<TokenContainer> = VecDeque::new();
-    let mut line = 1;
-    let column = 0;
+/// Get the column (starting at 1) of the character at the byte `offset` of the file.
+fn column_at(file: &str, offset: usize) -> usize {
+    let line_start = file[..offset]
+        .rfind(['\n', '\r'])
+        .map_or(0, |index| index + 1);
+    file[line_start..offset].chars().count() + 1
+}
 
-    let mut file_ch = file.chars();
-    let mut current_ch = file_ch.next();
-    // let mut operator2 = false;
+/// Options changing the tokens produced by [TokenStream].
+#[derive(Debug, Default, Clone, Copy)]
+pub struct TokenizeOptions {
+    /// Emit the comments as [Token::Comment] instead of discarding them. Used by the tools that
+    /// must keep the documentation, like a formatter.
+    pub keep_comments: bool,
+    /// Emit a [Token::Space] for each space and tab, so the source can be rebuilt from the tokens.
+    pub keep_spaces: bool,
+    /// Emit a [Token::Invalid] for the characters used by no token instead of returning a
+    /// [CustomError::InvalidCharacter], for the bloc titles that can contain any character.
+    pub keep_invalid: bool,
+}
 
-    while let Some(ch) = current_ch {
+/// Lazy tokenizer : the tokens are read from the characters of the file only when they are
+/// asked for. After an error, the stream stops.
+pub struct TokenStream<'a> {
+    file: &'a str,
+    file_ch: Chars<'a>,
+    /// The last character consumed from `file_ch`, not yet used by a token
+    current_ch: Option<char>,
+    line: usize,
+    options: TokenizeOptions,
+}
+
+impl<'a> TokenStream<'a> {
+    pub fn new(file: &'a str) -> Self {
+        Self::with_options(file, TokenizeOptions::default())
+    }
+
+    pub fn with_options(file: &'a str, options: TokenizeOptions) -> Self {
+        let mut file_ch = file.chars();
+        let current_ch = file_ch.next();
+        Self {
+            file,
+            file_ch,
+            current_ch,
+            line: 1,
+            options,
+        }
+    }
+
+    /// The end of the token is set by [TokenStream::set_end] once the token is read.
+    fn container(&self, token: Token, column: usize) -> Option<TokenContainer> {
+        Some(TokenContainer {
+            token,
+            line: self.line,
+            column,
+            end_line: self.line,
+            end_column: column,
+        })
+    }
+
+    /// Byte offset in the file of the next character to read.
+    fn offset(&self) -> usize {
+        self.file.len()
+            - self.file_ch.as_str().len()
+            - self.current_ch.map_or(0, |ch| ch.len_utf8())
+    }
+
+    /// Set the end of the token read from the text at `start..self.offset()`. A new line ends
+    /// where it starts, even when it ends a comment.
+    fn set_end(&self, token: &mut TokenContainer, start: usize) {
+        if token.token == Token::Space(SpaceTypes::NewLine) {
+            return;
+        }
+        let text = &self.file[start..self.offset()];
+        let Some(last) = text.chars().next_back() else {
+            return;
+        };
+        token.end_line = token.line + text.replace("\r\n", "\n").matches(['\n', '\r']).count();
+        token.end_column = column_at(self.file, start + text.len() - last.len_utf8());
+    }
+
+    /// The token for a character used by no other token, see [TokenizeOptions::keep_invalid].
+    fn invalid(&self, ch: char, column: usize) -> Result<Option<TokenContainer>, CustomError> {
+        if self.options.keep_invalid {
+            Ok(self.container(Token::Invalid(ch.to_string()), column))
+        } else {
+            Err(CustomError::InvalidCharacter(ch, self.line, column))
+        }
+    }
+
+    /// Read the characters starting with `ch`. Some characters, like spaces and comments, do not
+    /// produce any token.
+    fn read_token(&mut self, ch: char) -> Result<Option<TokenContainer>, CustomError> {
+        let file_ch = &mut self.file_ch;
+        let start = self.file.len() - file_ch.as_str().len() - ch.len_utf8();
+        let column = column_at(self.file, start);
         if ch == '/' {
             if let Some(next_ch) = file_ch.next() {
                 if next_ch == '/' {
-                    tokenize_comment_classic(&mut file_ch);
-                    add_token!(tokens, line, column, Token::Space(SpaceTypes::NewLine));
-                    current_ch = file_ch.next();
+                    let (text, after) = tokenize_comment_classic(file_ch);
+                    if self.options.keep_comments {
+                        // The new line is the next token
+                        self.current_ch = after;
+                        return Ok(self.container(Token::Comment(text), column));
+                    }
+                    if after == Some('\r') && self.file_ch.as_str().starts_with('\n') {
+                        self.file_ch.next();
+                    }
+                    self.current_ch = self.file_ch.next();
+                    let token = self.container(Token::Space(SpaceTypes::NewLine), column);
+                    self.line += 1;
+                    Ok(token)
+                } else if next_ch == '*' {
+                    let start_line = self.line;
+                    let text = tokenize_comment_block(file_ch, &mut self.line)?;
+                    self.current_ch = self.file_ch.next();
+                    if self.options.keep_comments {
+                        Ok(Some(TokenContainer {
+                            token: Token::Comment(text),
+                            line: start_line,
+                            column,
+                            end_line: start_line,
+                            end_column: column,
+                        }))
+                    } else if self.line != start_line {
+                        Ok(self.container(Token::Space(SpaceTypes::NewLine), column))
+                    } else {
+                        Ok(None)
+                    }
+                } else {
+                    self.current_ch = Some(next_ch);
+                    Ok(self.container(Token::Div, column))
+                }
+            } else {
+                self.current_ch = None;
+                Ok(self.container(Token::Div, column))
+            }
+        } else if ch == '<' || ch == '>' {
+            let next_ch = file_ch.next();
+            let token = if let Some('=') = next_ch {
+                self.current_ch = file_ch.next();
+                if ch == '<' {
+                    Token::LessEqual
+                } else {
+                    Token::GreaterEqual
+                }
+            } else {
+                self.current_ch = next_ch;
+                if ch == '<' {
+                    Token::Less
+                } else {
+                    Token::Greater
+                }
+            };
+            Ok(self.container(token, column))
+        } else if ch == '=' || ch == '!' {
+            let next_ch = file_ch.next();
+            let token = if let Some('=') = next_ch {
+                self.current_ch = file_ch.next();
+                if ch == '=' {
+                    Token::Equal
                 } else {
-                    add_token!(tokens, line, column, Token::Div);
-                    current_ch = Some(next_ch);
+                    Token::NotEqual
                 }
             } else {
-                add_token!(tokens, line, column, Token::Div);
+                self.current_ch = next_ch;
+                if ch == '=' {
+                    Token::Assign
+                } else {
+                    Token::Not
+                }
+            };
+            Ok(self.container(token, column))
+        } else if (ch == '+' || ch == '-')
+            && file_ch.as_str().starts_with(ch)
+            && follows_identifier(self.file, start)
+        {
+            // elsewhere, the signs are kept apart : `--5` is a double negation
+            file_ch.next();
+            self.current_ch = file_ch.next();
+            let token = if ch == '+' {
+                Token::Increment
+            } else {
+                Token::Decrement
+            };
+            Ok(self.container(token, column))
+        } else if ch == '-' {
+            let next_ch = file_ch.next();
+            let token = if let Some('>') = next_ch {
+                self.current_ch = file_ch.next();
+                Token::Arrow
+            } else {
+                self.current_ch = next_ch;
+                Token::Sub
+            };
+            Ok(self.container(token, column))
+        } else if ch == '*' {
+            let next_ch = file_ch.next();
+            let token = if let Some('*') = next_ch {
+                self.current_ch = file_ch.next();
+                Token::Pow
+            } else {
+                self.current_ch = next_ch;
+                Token::Mul
+            };
+            Ok(self.container(token, column))
+        } else if ch == '&' || ch == '|' {
+            // only the doubled symbols are operators, a single one stays invalid
+            let next_ch = file_ch.next();
+            if next_ch == Some(ch) {
+                self.current_ch = file_ch.next();
+                let token = if ch == '&' { Token::And } else { Token::Or };
+                Ok(self.container(token, column))
+            } else {
+                self.current_ch = next_ch;
+                self.invalid(ch, column)
             }
+        } else if ch == 'r' && file_ch.as_str().starts_with('"') {
+            file_ch.next();
+            let token = tokenize_raw_string(file_ch, self.line)?;
+            self.current_ch = self.file_ch.next();
+            Ok(self.container(token, column))
         } else if ch.is_alphabetic() || ch == '_' {
-            let token = tokenize_word(&mut file_ch, ch)?;
-            add_token!(tokens, line, column, token.0);
-            current_ch = token.1;
+            let token = tokenize_word(file_ch, ch)?;
+            self.current_ch = token.1;
+            Ok(self.container(token.0, column))
         } else if ch.is_numeric() {
-            let token = tokenize_number(&mut file_ch, line, ch)?;
-            add_token!(tokens, line, column, token.0);
-            current_ch = token.1;
+            let token = tokenize_number(file_ch, self.line, ch)?;
+            // `3abc` is neither a number nor an identifier
+            if let Some(letter) = token.1.filter(|next_ch| next_ch.is_alphabetic()) {
+                let message = |number: &dyn Display| {
+                    format!(
+                        "A number cannot be followed by a letter, found {} after {}",
+                        letter, number
+                    )
+                };
+                return Err(match token.0 {
+                    Token::Float(value) => CustomError::InvalidFloat(message(&value), self.line),
+                    Token::Int(value) => CustomError::InvalidInt(message(&value), self.line),
+                    token => CustomError::InvalidInt(message(&token), self.line),
+                });
+            }
+            self.current_ch = token.1;
+            Ok(self.container(token.0, column))
         } else {
-            if ch == ' ' {
-                // unused - tokens.push(Token::Space(Space::Space));
+            let token = if ch == ' ' || ch == '\t' {
+                if self.options.keep_spaces {
+                    let space = if ch == ' ' {
+                        SpaceTypes::Space
+                    } else {
+                        SpaceTypes::Tab
+                    };
+                    self.container(Token::Space(space), column)
+                } else {
+                    None
+                }
+            } else if ch == '\n' || ch == '\r' {
+                // \r\n and a lone \r are a single new line too
+                if ch == '\r' && file_ch.as_str().starts_with('\n') {
+                    file_ch.next();
+                }
+                let token = self.container(Token::Space(SpaceTypes::NewLine), column);
+                self.line += 1;
+                token
             } else {
-                add_token!(
-                    tokens,
-                    line,
-                    column,
-                    match ch {
-                        '+' => Token::Add,
-                        '-' => Token::Sub,
-                        '*' => Token::Mul,
-                        '"' => tokenize_string(&mut file_ch, line)?,
-                        ':' => Token::Inside,
-                        '(' => Token::LeftParenthesis,
-                        ')' => Token::RightParenthesis,
-                        '{' => Token::LeftBrace,
-                        '}' => Token::RightBrace,
-                        '\n' => {
-                            line += 1;
-                            Token::Space(SpaceTypes::NewLine)
-                        }
-                        _ => Token::Invalid(ch.to_string()),
+                let token = match ch {
+                    '+' => Token::Add,
+                    '%' => Token::Mod,
+                    '^' => Token::Pow,
+                    '"' => tokenize_string(file_ch, self.line)?,
+                    '\'' => tokenize_char(file_ch, self.line)?,
+                    ':' => Token::Inside,
+                    '.' => Token::Dot,
+                    '(' => Token::LeftParenthesis,
+                    ')' => Token::RightParenthesis,
+                    '{' => Token::LeftBrace,
+                    '}' => Token::RightBrace,
+                    '[' => Token::LeftBracket,
+                    ']' => Token::RightBracket,
+                    ',' => Token::Comma,
+                    ';' => Token::Semicolon,
+                    '?' => Token::Question,
+                    _ => {
+                        let token = self.invalid(ch, column);
+                        self.current_ch = self.file_ch.next();
+                        return token;
                     }
-                );
+                };
+                self.container(token, column)
+            };
+            self.current_ch = self.file_ch.next();
+            Ok(token)
+        }
+    }
+}
+
+impl Iterator for TokenStream<'_> {
+    type Item = Result<TokenContainer, CustomError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(ch) = self.current_ch {
+            let start = self.offset();
+            match self.read_token(ch) {
+                Ok(Some(mut token)) => {
+                    self.set_end(&mut token, start);
+                    return Some(Ok(token));
+                }
+                Ok(None) => {}
+                Err(err) => {
+                    self.current_ch = None;
+                    return Some(Err(err));
+                }
             }
-            current_ch = file_ch.next();
         }
+        None
     }
+}
+
+/// Whether the text before `offset` ends with a name, like `x` or `count_2`, and not with a number
+/// or a space.
+fn follows_identifier(file: &str, offset: usize) -> bool {
+    let before = &file[..offset];
+    let word_start = before
+        .trim_end_matches(|ch: char| ch.is_alphanumeric() || ch == '_')
+        .len();
+    before[word_start..].starts_with(|ch: char| ch.is_alphabetic() || ch == '_')
+}
+
+/// Write the tokens one per line, as `line:column-end_line:end_column Token`, to debug the
+/// tokenizer. The output only depends on the tokens, so two dumps can be compared with a diff.
+pub fn dump_tokens(tokens: &VecDeque<TokenContainer>) -> String {
+    tokens
+        .iter()
+        .map(|token| {
+            format!(
+                "{}:{}-{}:{} {}\n",
+                token.line, token.column, token.end_line, token.end_column, token.token
+            )
+        })
+        .collect()
+}
+
+/// Read all the tokens of the file. Use [TokenStream] to read them one by one, or to change the
+/// [TokenizeOptions].
+pub fn tokenize_str(file: &str) -> Result<VecDeque<TokenContainer>, CustomError> {
+    TokenStream::new(file).collect()
+}
 
-    Ok(tokens)
+/// Same as [tokenize_str], for an owned file.
+pub fn tokenize(file: String) -> Result<VecDeque<TokenContainer>, CustomError> {
+    tokenize_str(&file)
 }
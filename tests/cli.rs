@@ -0,0 +1,99 @@
+use std::process::{Command, Output};
+
+const PROGRAM: &str = "resources/test_programs/fibo.skrb";
+
+fn run_cli(args: &[&str]) -> Output {
+    let output = Command::new(env!("CARGO_BIN_EXE_skribi_language_source"))
+        .args(args)
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{:?}", output);
+    output
+}
+
+/// Write `program` in a file of the temporary directory and give its path.
+fn write_program(name: &str, program: &str) -> String {
+    let path = std::env::temp_dir().join(format!("skribi_{}.skrb", name));
+    std::fs::write(&path, program).unwrap();
+    path.to_str().unwrap().to_string()
+}
+
+#[test]
+fn test_dumps_start_with_the_data() {
+    for (flag, first_byte) in [("--tokens", b'1'), ("--ast", b'f'), ("--ast-json", b'{')] {
+        let output = run_cli(&[PROGRAM, flag]);
+        // the shell is not cleared before a dump
+        assert_eq!(
+            Some(&first_byte),
+            output.stdout.first(),
+            "{}: {}",
+            flag,
+            String::from_utf8_lossy(&output.stdout)
+        );
+    }
+}
+
+#[test]
+fn test_ast_json_round_trip() {
+    let output = run_cli(&[PROGRAM, "--ast-json"]);
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let value: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    assert!(value["statements"].is_array(), "{}", value);
+    // the JSON written back is the same tree
+    let written = serde_json::to_string(&value).unwrap();
+    assert_eq!(
+        value,
+        serde_json::from_str::<serde_json::Value>(&written).unwrap()
+    );
+}
+
+#[test]
+fn test_deeply_nested_blocks() {
+    let depth = 300;
+    let program = format!(
+        "{}skr_app println(\"deep\"){}\n",
+        "{ ".repeat(depth),
+        " }".repeat(depth)
+    );
+    let output = run_cli(&[&write_program("deeply_nested_blocks", &program)]);
+    // the shell is cleared before the run
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.ends_with("deep\n"), "{}", stdout);
+}
+
+#[test]
+fn test_recursion_limit() {
+    let countdown = "ums f (int n) { ij n > 0 { ei f(n - 1) }\nei n }\n";
+    let program = format!("{}skr_app println(f(9990))", countdown);
+    let output = run_cli(&[&write_program("deep_recursion", &program)]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.ends_with("0\n"), "{}", stdout);
+
+    // a recursion without end is an error, not a stack overflow
+    let program = write_program("infinite_recursion", "ums f () { ei f() }\nf()");
+    let output = Command::new(env!("CARGO_BIN_EXE_skribi_language_source"))
+        .arg(&program)
+        .output()
+        .unwrap();
+    assert_eq!(Some(1), output.status.code());
+    assert_eq!(
+        "error: Recursion limit: the maximum call depth of 10000 is exceeded\n",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+#[test]
+fn test_strict_flag() {
+    let program = write_program("ignored_bubble", "biuli { 1 }\n2");
+    run_cli(&[&program, "--compiler-debug"]);
+
+    let output = Command::new(env!("CARGO_BIN_EXE_skribi_language_source"))
+        .args([&program, "--strict"])
+        .output()
+        .unwrap();
+    assert_eq!(Some(1), output.status.code());
+    assert_eq!(
+        "error: Strict mode: The value of a biuli scope is never used, use kodi instead\n",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}